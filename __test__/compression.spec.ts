@@ -0,0 +1,84 @@
+import test from 'ava'
+import { appendFileSync, readFile, readFileSync, writeFile, writeFileSync } from '../index.js'
+import * as nodeFs from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+import * as zlib from 'node:zlib'
+
+const payload = JSON.stringify(Array.from({ length: 2000 }, (_, i) => ({ i, name: `item ${i}` })))
+
+function tmpPath(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-compress-${Date.now()}-${Math.random().toString(36).slice(2)}`)
+  nodeFs.mkdirSync(dir, { recursive: true })
+  return join(dir, name)
+}
+
+test('readFileSync: decompresses gzip and brotli written by node:zlib', (t) => {
+  const gz = tmpPath('data.json.gz')
+  nodeFs.writeFileSync(gz, zlib.gzipSync(payload))
+  t.is(readFileSync(gz, { encoding: 'utf8', compression: 'gzip' }), payload)
+
+  const br = tmpPath('data.json.br')
+  nodeFs.writeFileSync(br, zlib.brotliCompressSync(payload))
+  t.is(readFileSync(br, { encoding: 'utf8', compression: 'brotli' }), payload)
+})
+
+test('writeFileSync: output is readable by node:zlib', (t) => {
+  const gz = tmpPath('out.gz')
+  writeFileSync(gz, payload, { compression: 'gzip', level: 9 })
+  t.is(zlib.gunzipSync(nodeFs.readFileSync(gz)).toString(), payload)
+
+  const br = tmpPath('out.br')
+  writeFileSync(br, Buffer.from(payload), { compression: 'brotli', level: 4 })
+  t.is(zlib.brotliDecompressSync(nodeFs.readFileSync(br)).toString(), payload)
+})
+
+test('zstd round trip', (t) => {
+  const file = tmpPath('out.zst')
+  writeFileSync(file, payload, { compression: 'zstd', level: 19 })
+  t.true(nodeFs.statSync(file).size < payload.length / 4)
+  t.is(readFileSync(file, { encoding: 'utf8', compression: 'zstd' }), payload)
+})
+
+test('auto: extension on write, magic bytes or .br on read', (t) => {
+  const zst = tmpPath('auto.zst')
+  writeFileSync(zst, payload, { compression: 'auto' })
+  t.not(nodeFs.readFileSync(zst, 'utf8'), payload)
+  t.is(readFileSync(zst, { encoding: 'utf8', compression: 'auto' }), payload)
+
+  // gzip data is recognised without the extension; other files are read as is
+  const gz = tmpPath('auto.bin')
+  nodeFs.writeFileSync(gz, zlib.gzipSync(payload))
+  t.is(readFileSync(gz, { encoding: 'utf8', compression: 'auto' }), payload)
+  const plain = tmpPath('plain.txt')
+  writeFileSync(plain, 'raw', { compression: 'auto' })
+  t.is(nodeFs.readFileSync(plain, 'utf8'), 'raw')
+  t.is(readFileSync(plain, { encoding: 'utf8', compression: 'auto' }), 'raw')
+})
+
+test('appendFileSync: gzip members and zstd frames concatenate', (t) => {
+  for (const name of ['log.gz', 'log.zst']) {
+    const file = tmpPath(name)
+    writeFileSync(file, 'l1\nl2\n', { compression: 'auto' })
+    appendFileSync(file, 'l3\nl4\n', { compression: 'auto' })
+    t.is(readFileSync(file, { encoding: 'utf8', compression: 'auto' }), 'l1\nl2\nl3\nl4\n')
+    t.is(readFileSync(file, { encoding: 'utf8', compression: 'auto', lines: { from: 2, to: 3 } }), 'l2\nl3')
+  }
+  const br = tmpPath('log.br')
+  writeFileSync(br, 'a', { compression: 'brotli' })
+  t.throws(() => appendFileSync(br, 'b', { compression: 'brotli' }), { message: /Cannot append to a brotli stream/ })
+})
+
+test('errors: corrupt data, bad level, unknown codec', (t) => {
+  const file = tmpPath('corrupt.gz')
+  nodeFs.writeFileSync(file, 'definitely not gzip')
+  t.throws(() => readFileSync(file, { compression: 'gzip' }), { message: /Z_DATA_ERROR/ })
+  t.throws(() => writeFileSync(file, 'x', { compression: 'gzip', level: 10 }), { message: /ERR_OUT_OF_RANGE/ })
+  t.throws(() => writeFileSync(file, 'x', { compression: 'lz4' }), { message: /ERR_INVALID_ARG_VALUE/ })
+})
+
+test('async: codec work runs off the main thread', async (t) => {
+  const file = tmpPath('async.json.gz')
+  await writeFile(file, payload, { compression: 'auto' })
+  t.is(await readFile(file, { encoding: 'utf8', compression: 'auto' }), payload)
+})
@@ -1,7 +1,7 @@
 import test from 'ava'
-import { cpSync, cp } from '../index.js'
+import { cpSync, cp, type DryRunPlan } from '../index.js'
 import * as nodeFs from 'node:fs'
-import { writeFileSync, readFileSync, existsSync, mkdirSync, readdirSync } from 'node:fs'
+import { writeFileSync, readFileSync, existsSync, mkdirSync, readdirSync, symlinkSync } from 'node:fs'
 import { join } from 'node:path'
 import { tmpdir } from 'node:os'
 
@@ -124,8 +124,311 @@ test('cpSync: preserveTimestamps should keep mtime', (t) => {
   t.true(Math.abs(srcStat.mtimeMs - destStat.mtimeMs) < 1000)
 })
 
+// ===== cpSync: update / mirror =====
+
+test('cpSync / cp: plain copy should return undefined', async (t) => {
+  const dir = tmpDir('summary-undefined')
+  const src = join(dir, 'src.txt')
+  writeFileSync(src, 'x')
+
+  t.is(cpSync(src, join(dir, 'dest.txt')), undefined)
+  t.is(await cp(src, join(dir, 'dest2.txt')), undefined)
+})
+
+test('cpSync: update size-mtime should skip unchanged files on second run', (t) => {
+  const dir = tmpDir('update-size-mtime')
+  const src = join(dir, 'src')
+  mkdirSync(join(src, 'sub'), { recursive: true })
+  writeFileSync(join(src, 'a.txt'), 'aaa')
+  writeFileSync(join(src, 'sub', 'b.txt'), 'bbb')
+  const dest = join(dir, 'dest')
+
+  const first = cpSync(src, dest, { recursive: true, update: 'size-mtime' })
+  t.deepEqual(first, { copied: 2, skipped: 0, deleted: 0 })
+
+  const second = cpSync(src, dest, { recursive: true, update: 'size-mtime' })
+  t.deepEqual(second, { copied: 0, skipped: 2, deleted: 0 })
+})
+
+test('cpSync: update checksum should copy only changed content', (t) => {
+  const dir = tmpDir('update-checksum')
+  const src = join(dir, 'src')
+  const dest = join(dir, 'dest')
+  mkdirSync(src)
+  mkdirSync(dest)
+  writeFileSync(join(src, 'same.txt'), 'same')
+  writeFileSync(join(dest, 'same.txt'), 'same')
+  writeFileSync(join(src, 'diff.txt'), 'new!')
+  writeFileSync(join(dest, 'diff.txt'), 'old!')
+
+  const summary = cpSync(src, dest, { recursive: true, update: 'checksum' })
+
+  t.deepEqual(summary, { copied: 1, skipped: 1, deleted: 0 })
+  t.is(readFileSync(join(dest, 'diff.txt'), 'utf8'), 'new!')
+})
+
+test('cpSync: update should replace a destination symlink to an identical file', (t) => {
+  const dir = tmpDir('update-symlink')
+  const src = join(dir, 'src')
+  const dest = join(dir, 'dest')
+  mkdirSync(src)
+  mkdirSync(dest)
+  writeFileSync(join(src, 'same.txt'), 'same')
+  writeFileSync(join(dir, 'elsewhere.txt'), 'same')
+  symlinkSync(join(dir, 'elsewhere.txt'), join(dest, 'same.txt'))
+
+  const summary = cpSync(src, dest, { recursive: true, update: 'checksum' })
+
+  t.deepEqual(summary, { copied: 1, skipped: 0, deleted: 0 })
+  t.false(nodeFs.lstatSync(join(dest, 'same.txt')).isSymbolicLink())
+  t.is(readFileSync(join(dest, 'same.txt'), 'utf8'), 'same')
+})
+
+test('cpSync: update newer should not overwrite a newer destination', (t) => {
+  const dir = tmpDir('update-newer')
+  const src = join(dir, 'src.txt')
+  const dest = join(dir, 'dest.txt')
+  writeFileSync(src, 'older')
+  writeFileSync(dest, 'newer')
+  nodeFs.utimesSync(src, new Date('2020-01-01T00:00:00Z'), new Date('2020-01-01T00:00:00Z'))
+
+  const summary = cpSync(src, dest, { update: 'newer' })
+
+  t.deepEqual(summary, { copied: 0, skipped: 1, deleted: 0 })
+  t.is(readFileSync(dest, 'utf8'), 'newer')
+})
+
+test('cpSync: mirror should delete entries missing from source', (t) => {
+  const dir = tmpDir('mirror')
+  const src = join(dir, 'src')
+  const dest = join(dir, 'dest')
+  mkdirSync(src)
+  mkdirSync(join(dest, 'stale-dir'), { recursive: true })
+  writeFileSync(join(src, 'keep.txt'), 'keep')
+  writeFileSync(join(dest, 'stale.txt'), 'stale')
+  writeFileSync(join(dest, 'stale-dir', 'inner.txt'), 'inner')
+
+  const summary = cpSync(src, dest, { recursive: true, mirror: true })
+
+  t.deepEqual(summary, { copied: 1, skipped: 0, deleted: 3 })
+  t.deepEqual(readdirSync(dest), ['keep.txt'])
+})
+
+test('cpSync: mirror should not follow symlinks inside stale directories', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('mirror-symlink')
+  const src = join(dir, 'src')
+  const dest = join(dir, 'dest')
+  const outside = join(dir, 'outside')
+  mkdirSync(src)
+  mkdirSync(join(dest, 'stale-dir'), { recursive: true })
+  mkdirSync(outside)
+  writeFileSync(join(outside, 'keep.txt'), 'keep')
+  symlinkSync(outside, join(dest, 'stale-dir', 'link'))
+
+  const summary = cpSync(src, dest, { recursive: true, mirror: true })
+
+  t.deepEqual(summary, { copied: 0, skipped: 0, deleted: 2 })
+  t.deepEqual(readdirSync(dest), [])
+  t.deepEqual(readdirSync(outside), ['keep.txt'])
+})
+
+test('cpSync: mirror should not replace a directory destination with a file', (t) => {
+  const dir = tmpDir('mirror-non-dir-to-dir')
+  const src = join(dir, 'file.txt')
+  const dest = join(dir, 'existing')
+  writeFileSync(src, 'x')
+  mkdirSync(dest)
+  writeFileSync(join(dest, 'keep.txt'), 'keep')
+
+  t.throws(() => cpSync(src, dest, { mirror: true }), { message: /ERR_FS_CP_NON_DIR_TO_DIR/ })
+  t.deepEqual(readdirSync(dest), ['keep.txt'])
+})
+
+test('cpSync: invalid update mode should throw', (t) => {
+  const dir = tmpDir('update-invalid')
+  const src = join(dir, 'src.txt')
+  writeFileSync(src, 'x')
+
+  t.throws(() => cpSync(src, join(dir, 'dest.txt'), { update: 'sometimes' }), { message: /ERR_INVALID_ARG_VALUE/ })
+})
+
+test('cp: async mirror with concurrency should return summary', async (t) => {
+  const dir = tmpDir('async-mirror')
+  const src = join(dir, 'src')
+  const dest = join(dir, 'dest')
+  mkdirSync(join(src, 'sub'), { recursive: true })
+  mkdirSync(dest)
+  writeFileSync(join(src, 'sub', 'f.txt'), 'f')
+  writeFileSync(join(dest, 'gone.txt'), 'gone')
+
+  const summary = await cp(src, dest, { recursive: true, mirror: true, update: 'size-mtime', concurrency: 4 })
+
+  t.deepEqual(summary, { copied: 1, skipped: 0, deleted: 1 })
+  t.false(existsSync(join(dest, 'gone.txt')))
+})
+
+// ===== cpSync: preserve =====
+
+test('cpSync: preserve.mode should copy directory and file modes', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping mode test on Windows')
+    return
+  }
+  const dir = tmpDir('preserve-mode')
+  const src = join(dir, 'src')
+  mkdirSync(join(src, 'locked'), { recursive: true })
+  writeFileSync(join(src, 'locked', 'f.txt'), 'data')
+  nodeFs.chmodSync(join(src, 'locked', 'f.txt'), 0o640)
+  nodeFs.chmodSync(join(src, 'locked'), 0o550)
+
+  const dest = join(dir, 'dest')
+  cpSync(src, dest, { recursive: true, preserve: { mode: true } })
+
+  t.is(nodeFs.statSync(join(dest, 'locked')).mode & 0o777, 0o550)
+  t.is(nodeFs.statSync(join(dest, 'locked', 'f.txt')).mode & 0o777, 0o640)
+  t.is(readFileSync(join(dest, 'locked', 'f.txt'), 'utf8'), 'data')
+  nodeFs.chmodSync(join(src, 'locked'), 0o755)
+  nodeFs.chmodSync(join(dest, 'locked'), 0o755)
+})
+
+test('cpSync: preserve.ownership should keep uid/gid', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping ownership test on Windows')
+    return
+  }
+  const dir = tmpDir('preserve-owner')
+  const src = join(dir, 'src.txt')
+  writeFileSync(src, 'owned')
+
+  const dest = join(dir, 'dest.txt')
+  cpSync(src, dest, { preserve: { ownership: true } })
+
+  const srcStat = nodeFs.statSync(src)
+  const destStat = nodeFs.statSync(dest)
+  t.is(destStat.uid, srcStat.uid)
+  t.is(destStat.gid, srcStat.gid)
+})
+
+test('cpSync: preserve.timestamps should keep directory mtime', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping timestamp test on Windows')
+    return
+  }
+  const dir = tmpDir('preserve-ts')
+  const src = join(dir, 'src')
+  mkdirSync(src)
+  writeFileSync(join(src, 'f.txt'), 'f')
+  const pastTime = new Date('2020-01-01T00:00:00Z')
+  nodeFs.utimesSync(src, pastTime, pastTime)
+
+  const dest = join(dir, 'dest')
+  cpSync(src, dest, { recursive: true, preserve: { timestamps: true } })
+
+  t.true(Math.abs(nodeFs.statSync(dest).mtimeMs - pastTime.getTime()) < 1000)
+})
+
+// ===== cpSync: preserveHardlinks =====
+
+test('cpSync: preserveHardlinks should keep hard link topology', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping hard link test on Windows')
+    return
+  }
+  const dir = tmpDir('hardlinks')
+  const src = join(dir, 'src')
+  mkdirSync(join(src, 'a', 'b'), { recursive: true })
+  writeFileSync(join(src, 'orig.txt'), 'shared')
+  nodeFs.linkSync(join(src, 'orig.txt'), join(src, 'a', 'link1.txt'))
+  nodeFs.linkSync(join(src, 'orig.txt'), join(src, 'a', 'b', 'link2.txt'))
+  writeFileSync(join(src, 'alone.txt'), 'alone')
+
+  const dest = join(dir, 'dest')
+  cpSync(src, dest, { recursive: true, preserveHardlinks: true })
+
+  const ino = nodeFs.statSync(join(dest, 'orig.txt')).ino
+  t.is(nodeFs.statSync(join(dest, 'a', 'link1.txt')).ino, ino)
+  t.is(nodeFs.statSync(join(dest, 'a', 'b', 'link2.txt')).ino, ino)
+  t.is(nodeFs.statSync(join(dest, 'orig.txt')).nlink, 3)
+  t.is(nodeFs.statSync(join(dest, 'alone.txt')).nlink, 1)
+  t.is(readFileSync(join(dest, 'a', 'b', 'link2.txt'), 'utf8'), 'shared')
+})
+
+test('cpSync: without preserveHardlinks links become separate copies', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping hard link test on Windows')
+    return
+  }
+  const dir = tmpDir('no-hardlinks')
+  const src = join(dir, 'src')
+  mkdirSync(src)
+  writeFileSync(join(src, 'orig.txt'), 'shared')
+  nodeFs.linkSync(join(src, 'orig.txt'), join(src, 'link.txt'))
+
+  const dest = join(dir, 'dest')
+  cpSync(src, dest, { recursive: true })
+
+  t.not(nodeFs.statSync(join(dest, 'orig.txt')).ino, nodeFs.statSync(join(dest, 'link.txt')).ino)
+})
+
+test('cp: async preserveHardlinks should be consistent with concurrency', async (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping hard link test on Windows')
+    return
+  }
+  const dir = tmpDir('hardlinks-par')
+  const src = join(dir, 'src')
+  mkdirSync(src)
+  writeFileSync(join(src, 'orig.txt'), 'shared')
+  for (let i = 0; i < 16; i++) {
+    mkdirSync(join(src, `d${i}`))
+    nodeFs.linkSync(join(src, 'orig.txt'), join(src, `d${i}`, 'link.txt'))
+  }
+
+  const dest = join(dir, 'dest')
+  await cp(src, dest, { recursive: true, preserveHardlinks: true, concurrency: 8 })
+
+  t.is(nodeFs.statSync(join(dest, 'orig.txt')).nlink, 17)
+})
+
 // ===== async cp =====
 
+test('cpSync: dryRun should report the plan without writing', (t) => {
+  const dir = tmpDir('dry-run')
+  const src = join(dir, 'src')
+  const dest = join(dir, 'dest')
+  mkdirSync(join(src, 'sub'), { recursive: true })
+  mkdirSync(dest)
+  writeFileSync(join(src, 'a.txt'), 'hello')
+  writeFileSync(join(src, 'keep.txt'), 'same')
+  writeFileSync(join(src, 'sub', 'b.txt'), 'bb')
+  writeFileSync(join(dest, 'a.txt'), 'old')
+  writeFileSync(join(dest, 'keep.txt'), 'same')
+  writeFileSync(join(dest, 'extra.txt'), 'extra')
+
+  const plan = cpSync(src, dest, { recursive: true, update: 'checksum', mirror: true, dryRun: true }) as DryRunPlan
+  t.deepEqual(plan.removed, [join(dest, 'extra.txt')])
+  t.deepEqual(plan.created, [join(dest, 'sub'), join(dest, 'sub', 'b.txt')])
+  t.deepEqual(plan.overwritten, [join(dest, 'a.txt')])
+  t.deepEqual(plan.skipped, [join(dest, 'keep.txt')])
+  t.deepEqual(plan.totals, { removed: 1, created: 2, overwritten: 1, skipped: 1, bytes: 7 })
+  t.deepEqual(readdirSync(dest).sort(), ['a.txt', 'extra.txt', 'keep.txt'])
+  t.is(readFileSync(join(dest, 'a.txt'), 'utf8'), 'old')
+})
+
+test('cp: async dryRun should list a fresh destination as created', async (t) => {
+  const dir = tmpDir('dry-run-async')
+  const src = join(dir, 'src')
+  mkdirSync(src)
+  writeFileSync(join(src, 'a.txt'), 'abc')
+  const dest = join(dir, 'out', 'dest')
+
+  const plan = (await cp(src, dest, { recursive: true, dryRun: true, concurrency: 4 })) as DryRunPlan
+  t.deepEqual(plan.created, [join(dir, 'out'), dest, join(dest, 'a.txt')])
+  t.is(plan.totals.bytes, 3)
+  t.false(existsSync(join(dir, 'out')))
+})
+
 test('cp: async should copy a file', async (t) => {
   const dir = tmpDir('async')
   const src = join(dir, 'src.txt')
@@ -190,3 +493,40 @@ test('dual-run: cpSync recursive should produce same tree as node:fs', (t) => {
     readFileSync(join(nodeDest, 'sub', 'child.txt'), 'utf8'),
   )
 })
+
+// ===== onError =====
+
+test("cpSync: onError 'skip' / 'collect' should copy the entries that do not fail", async (t) => {
+  const dir = tmpDir('on-error')
+  const src = join(dir, 'src')
+  mkdirSync(join(src, 'sub'), { recursive: true })
+  writeFileSync(join(src, 'a.txt'), 'a')
+  writeFileSync(join(src, 'b.txt'), 'b')
+  writeFileSync(join(src, 'sub', 'c.txt'), 'c')
+  const dest = join(dir, 'dest')
+  mkdirSync(join(dest, 'sub'), { recursive: true })
+  writeFileSync(join(dest, 'a.txt'), 'old')
+  writeFileSync(join(dest, 'sub', 'c.txt'), 'old')
+  const opts = { recursive: true, force: false, errorOnExist: true }
+
+  const error = t.throws(() => cpSync(src, dest, { ...opts, onError: 'collect' }), {
+    instanceOf: AggregateError,
+  }) as AggregateError
+  t.is(error.message, `2 errors occurred, cp '${src}'`)
+  const failed = error.errors.map((e) => `${e.code} ${e.path}`).sort()
+  t.deepEqual(failed, [`EEXIST ${join(src, 'a.txt')}`, `EEXIST ${join(src, 'sub', 'c.txt')}`])
+  t.is(readFileSync(join(dest, 'b.txt'), 'utf8'), 'b')
+  t.is(readFileSync(join(dest, 'a.txt'), 'utf8'), 'old')
+  t.throws(() => cpSync(src, dest, opts), { message: /^EEXIST/ })
+
+  const skipDest = join(dir, 'skip')
+  mkdirSync(skipDest)
+  writeFileSync(join(skipDest, 'a.txt'), 'old')
+  cpSync(src, skipDest, { ...opts, onError: 'skip', concurrency: 4 })
+  t.is(readFileSync(join(skipDest, 'b.txt'), 'utf8'), 'b')
+  t.is(readFileSync(join(skipDest, 'sub', 'c.txt'), 'utf8'), 'c')
+
+  await t.throwsAsync(async () => await cp(src, dest, { ...opts, onError: 'collect' }), {
+    instanceOf: AggregateError,
+  })
+})
@@ -0,0 +1,50 @@
+import test from 'ava'
+import { detectEncoding, detectEncodingSync } from '../index.js'
+import * as nodeFs from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+// '简体中文文本，用于检测编码。' in GBK and '日本語のテキストです。' in Shift_JIS.
+const GBK_HEX = 'bcf2cce5d6d0cec4cec4b1bea3acd3c3d3dabcecb2e2b1e0c2eba1a3'
+const SHIFT_JIS_HEX = '93fa967b8cea82cc8365834c8358836782c582b78142'
+
+function tmpFile(name: string, content: string | Buffer): string {
+  const dir = join(tmpdir(), `rush-fs-test-detect-${Date.now()}-${Math.random().toString(36).slice(2)}`)
+  nodeFs.mkdirSync(dir, { recursive: true })
+  const file = join(dir, name)
+  nodeFs.writeFileSync(file, content)
+  return file
+}
+
+test('detectEncodingSync: BOMs win', (t) => {
+  const utf8 = tmpFile('bom8.txt', Buffer.concat([Buffer.from([0xef, 0xbb, 0xbf]), Buffer.from('hi')]))
+  t.deepEqual(detectEncodingSync(utf8), { encoding: 'UTF-8', bom: true, confident: true })
+  const utf16le = tmpFile('bom16le.txt', Buffer.concat([Buffer.from([0xff, 0xfe]), Buffer.from('hi', 'utf16le')]))
+  t.is(detectEncodingSync(utf16le).encoding, 'UTF-16LE')
+  const utf16be = tmpFile('bom16be.txt', Buffer.from([0xfe, 0xff, 0x00, 0x68]))
+  t.is(detectEncodingSync(utf16be).encoding, 'UTF-16BE')
+})
+
+test('detectEncodingSync: heuristics for legacy encodings', (t) => {
+  const gbk = tmpFile('gbk.txt', Buffer.from(GBK_HEX.repeat(20), 'hex'))
+  t.deepEqual(detectEncodingSync(gbk), { encoding: 'GBK', bom: false, confident: true })
+  const sjis = tmpFile('sjis.txt', Buffer.from(SHIFT_JIS_HEX.repeat(20), 'hex'))
+  t.is(detectEncodingSync(sjis).encoding, 'Shift_JIS')
+  const utf8 = tmpFile('utf8.txt', 'héllo wörld, ça va très bien')
+  t.is(detectEncodingSync(utf8).encoding, 'UTF-8')
+})
+
+test('detectEncodingSync: empty and ASCII files are UTF-8 without confidence', (t) => {
+  t.deepEqual(detectEncodingSync(tmpFile('empty.txt', '')), { encoding: 'UTF-8', bom: false, confident: false })
+  t.false(detectEncodingSync(tmpFile('ascii.txt', 'plain ascii')).confident)
+})
+
+test('detectEncodingSync: errors like node:fs', (t) => {
+  t.throws(() => detectEncodingSync('/tmp/no-such-file-rush-fs-detect'), { message: /ENOENT/ })
+  t.throws(() => detectEncodingSync(tmpdir()), { message: /EISDIR/ })
+})
+
+test('detectEncoding: async matches sync', async (t) => {
+  const gbk = tmpFile('gbk-async.txt', Buffer.from(GBK_HEX.repeat(20), 'hex'))
+  t.deepEqual(await detectEncoding(gbk), detectEncodingSync(gbk))
+})
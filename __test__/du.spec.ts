@@ -0,0 +1,95 @@
+import test from 'ava'
+import { duSync, du } from '../index.js'
+import { writeFileSync, mkdirSync, linkSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-du-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+function makeTree(dir: string) {
+  mkdirSync(join(dir, 'a', 'b'), { recursive: true })
+  mkdirSync(join(dir, 'c'), { recursive: true })
+  writeFileSync(join(dir, 'a', 'one.bin'), Buffer.alloc(1000))
+  writeFileSync(join(dir, 'a', 'b', 'two.bin'), Buffer.alloc(2000))
+  writeFileSync(join(dir, 'c', 'three.bin'), Buffer.alloc(3000))
+}
+
+test('duSync: apparent size should sum file lengths plus directories', (t) => {
+  const dir = tmpDir('apparent')
+  makeTree(dir)
+  const result = duSync(dir, { apparent: true })
+  t.is(result.files, 3)
+  t.is(result.dirs, 3)
+  t.true(result.bytes >= 6000)
+})
+
+test('duSync: disk usage should be a multiple of 512 and match blocks', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping block test on Windows')
+    return
+  }
+  const dir = tmpDir('blocks')
+  makeTree(dir)
+  const result = duSync(dir)
+  t.is(result.bytes, result.blocks * 512)
+  t.true(result.blocks > 0)
+})
+
+test('duSync: children should break down direct children', (t) => {
+  const dir = tmpDir('children')
+  makeTree(dir)
+  const result = duSync(dir, { apparent: true, children: true })
+  const byName = Object.fromEntries(result.children!.map((c) => [c.name, c]))
+  t.deepEqual(Object.keys(byName).sort(), ['a', 'c'])
+  t.is(byName.a.files, 2)
+  t.is(byName.a.dirs, 1)
+  t.is(byName.c.files, 1)
+  t.true(byName.c.bytes >= 3000)
+})
+
+test('duSync: hard links should be counted once by default', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping hard link test on Windows')
+    return
+  }
+  const dir = tmpDir('hardlinks')
+  writeFileSync(join(dir, 'orig.bin'), Buffer.alloc(10000))
+  linkSync(join(dir, 'orig.bin'), join(dir, 'link.bin'))
+  const once = duSync(dir, { apparent: true })
+  const twice = duSync(dir, { apparent: true, countHardlinksOnce: false })
+  t.is(once.files, 1)
+  t.is(twice.files, 2)
+  t.is(twice.bytes - once.bytes, 10000)
+})
+
+test('duSync: maxDepth should limit traversal', (t) => {
+  const dir = tmpDir('max-depth')
+  makeTree(dir)
+  const result = duSync(dir, { maxDepth: 1 })
+  t.is(result.files, 0)
+  t.is(result.dirs, 2)
+})
+
+test('duSync: should report a single file', (t) => {
+  const dir = tmpDir('file')
+  const file = join(dir, 'f.bin')
+  writeFileSync(file, Buffer.alloc(1234))
+  const result = duSync(file, { apparent: true })
+  t.is(result.bytes, 1234)
+  t.is(result.files, 1)
+  t.is(result.dirs, 0)
+})
+
+test('duSync: should throw ENOENT on missing path', (t) => {
+  t.throws(() => duSync(join(tmpDir('missing'), 'nope')), { message: /ENOENT/ })
+})
+
+test('du: async should match sync', async (t) => {
+  const dir = tmpDir('async')
+  makeTree(dir)
+  t.deepEqual(await du(dir, { apparent: true, concurrency: 4 }), duSync(dir, { apparent: true }))
+})
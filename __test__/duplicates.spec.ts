@@ -0,0 +1,105 @@
+import test from 'ava'
+import { findDuplicatesSync, findDuplicates } from '../index.js'
+import { writeFileSync, readFileSync, unlinkSync, mkdirSync, statSync, readdirSync, linkSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-dup-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+function makeTree(dir: string) {
+  mkdirSync(join(dir, 'a'), { recursive: true })
+  mkdirSync(join(dir, 'b'), { recursive: true })
+  const big = Buffer.alloc(10000, 1)
+  const almost = Buffer.alloc(10000, 1)
+  almost[9999] = 2
+  writeFileSync(join(dir, 'a', 'big.bin'), big)
+  writeFileSync(join(dir, 'b', 'big.bin'), big)
+  writeFileSync(join(dir, 'b', 'almost.bin'), almost)
+  writeFileSync(join(dir, 'a', 'small.txt'), 'hi')
+  writeFileSync(join(dir, 'b', 'small.txt'), 'hi')
+  writeFileSync(join(dir, 'a', 'empty'), '')
+  writeFileSync(join(dir, 'b', 'empty'), '')
+}
+
+test('findDuplicatesSync: should group identical files, largest first', (t) => {
+  const dir = tmpDir('groups')
+  makeTree(dir)
+  const groups = findDuplicatesSync([join(dir, 'a'), join(dir, 'b')])
+  t.deepEqual(groups, [
+    { size: 10000, paths: [join(dir, 'a', 'big.bin'), join(dir, 'b', 'big.bin')] },
+    { size: 2, paths: [join(dir, 'a', 'small.txt'), join(dir, 'b', 'small.txt')] },
+  ])
+})
+
+test('findDuplicatesSync: minSize should skip small files', (t) => {
+  const dir = tmpDir('min-size')
+  makeTree(dir)
+  const groups = findDuplicatesSync([dir], { minSize: 100 })
+  t.is(groups.length, 1)
+  t.is(groups[0].size, 10000)
+})
+
+test('findDuplicatesSync: minSize 0 should include empty files', (t) => {
+  const dir = tmpDir('empty')
+  makeTree(dir)
+  const groups = findDuplicatesSync([dir], { minSize: 0 })
+  t.true(groups.some((g) => g.size === 0 && g.paths.length === 2))
+})
+
+test('findDuplicatesSync: exclude should skip matching paths', (t) => {
+  const dir = tmpDir('exclude')
+  makeTree(dir)
+  const groups = findDuplicatesSync([dir], { exclude: ['**/*.txt'] })
+  t.is(groups.length, 1)
+  t.is(groups[0].size, 10000)
+})
+
+test('findDuplicatesSync: existing hard links and overlapping roots are not duplicates', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping hard link test on Windows')
+    return
+  }
+  const dir = tmpDir('linked')
+  writeFileSync(join(dir, 'orig.bin'), Buffer.alloc(5000, 7))
+  linkSync(join(dir, 'orig.bin'), join(dir, 'link.bin'))
+  t.deepEqual(findDuplicatesSync([dir, dir]), [])
+})
+
+test('findDuplicatesSync: action hardlink should link duplicates to the first path', (t) => {
+  if (process.platform === 'win32') {
+    t.pass('Skipping hard link test on Windows')
+    return
+  }
+  const dir = tmpDir('hardlink')
+  makeTree(dir)
+  writeFileSync(join(dir, 'b', '.big.bin.rush-fs-link'), 'not ours')
+  const groups = findDuplicatesSync([dir], { action: 'hardlink' })
+  t.is(groups.length, 2)
+  t.deepEqual(groups[0].linked, [join(dir, 'b', 'big.bin')])
+  t.deepEqual(groups[0].failed, [])
+  t.is(readFileSync(join(dir, 'b', '.big.bin.rush-fs-link'), 'utf8'), 'not ours')
+  unlinkSync(join(dir, 'b', '.big.bin.rush-fs-link'))
+  t.is(statSync(join(dir, 'a', 'big.bin')).ino, statSync(join(dir, 'b', 'big.bin')).ino)
+  t.not(statSync(join(dir, 'a', 'big.bin')).ino, statSync(join(dir, 'b', 'almost.bin')).ino)
+  t.deepEqual(readdirSync(join(dir, 'b')).sort(), ['almost.bin', 'big.bin', 'empty', 'small.txt'])
+  t.deepEqual(findDuplicatesSync([dir]), [])
+})
+
+test('findDuplicatesSync: should reject unknown action', (t) => {
+  const dir = tmpDir('bad-action')
+  t.throws(() => findDuplicatesSync([dir], { action: 'delete' }), { message: /ERR_INVALID_ARG_VALUE/ })
+})
+
+test('findDuplicatesSync: should throw ENOENT on missing root', (t) => {
+  t.throws(() => findDuplicatesSync([join(tmpDir('missing'), 'nope')]), { message: /ENOENT/ })
+})
+
+test('findDuplicates: async should match sync', async (t) => {
+  const dir = tmpDir('async')
+  makeTree(dir)
+  t.deepEqual(await findDuplicates([dir], { concurrency: 2 }), findDuplicatesSync([dir]))
+})
@@ -1,5 +1,5 @@
 import test from 'ava'
-import { readFileSync, writeFileSync } from '../index.js'
+import { appendFileSync, readFileSync, tailSync, writeFileSync } from '../index.js'
 import * as nodeFs from 'node:fs'
 import { mkdirSync } from 'node:fs'
 import { join } from 'node:path'
@@ -74,6 +74,28 @@ test('readFile: no encoding returns Buffer', (t) => {
   t.true(Buffer.isBuffer(result))
 })
 
+test('readFile: invalid utf8 is replaced, not thrown', (t) => {
+  const file = tmpFile('lossy.txt', Buffer.from([0x68, 0xc3, 0x28, 0xff, 0x69]))
+  t.is(readFileSync(file, { encoding: 'utf8' }), nodeFs.readFileSync(file, 'utf8'))
+})
+
+test('readFile: utf16le / ucs2 and case-insensitive names', (t) => {
+  const file = tmpFile('utf16.txt', Buffer.from('héllo 😀', 'utf16le'))
+  t.is(readFileSync(file, { encoding: 'utf16le' }), 'héllo 😀')
+  t.is(readFileSync(file, { encoding: 'UCS-2' }), 'héllo 😀')
+  t.is(readFileSync(file, { encoding: 'UTF8' }), nodeFs.readFileSync(file, 'utf8'))
+})
+
+test('readFile: ascii clears the high bit like node:fs', (t) => {
+  const file = tmpFile('ascii-high.txt', Buffer.from([0x41, 0xe9, 0xff, 0x80]))
+  t.is(readFileSync(file, { encoding: 'ascii' }), nodeFs.readFileSync(file, 'ascii'))
+})
+
+test('readFile: buffer encoding returns Buffer', (t) => {
+  const file = tmpFile('buffer-enc.txt', 'abc')
+  t.true(Buffer.isBuffer(readFileSync(file, { encoding: 'buffer' })))
+})
+
 // ===== writeFile encoding =====
 
 test('writeFile: hex encoding', (t) => {
@@ -118,3 +140,86 @@ test('dual-run: readFile hex should match node:fs', (t) => {
   const nodeResult = nodeFs.readFileSync(file, { encoding: 'hex' })
   t.is(hyperResult, nodeResult)
 })
+
+test('writeFile: utf16le encoding', (t) => {
+  const file = tmpFile('write-utf16.txt')
+  writeFileSync(file, 'héllo 😀', { encoding: 'ucs2' })
+  t.deepEqual(nodeFs.readFileSync(file), Buffer.from('héllo 😀', 'utf16le'))
+})
+
+test('writeFile: latin1 and ascii keep the low byte of each UTF-16 unit', (t) => {
+  const file = tmpFile('write-low-byte.txt')
+  for (const encoding of ['latin1', 'ascii'] as const) {
+    writeFileSync(file, 'Ā€é😀', { encoding })
+    t.deepEqual(nodeFs.readFileSync(file), Buffer.from('Ā€é😀', encoding))
+  }
+})
+
+test('writeFile: lenient base64 / hex match Buffer.from', (t) => {
+  const file = tmpFile('write-lenient.bin')
+  for (const [data, encoding] of [
+    ['SGVs bG8*\nIQ==', 'base64'],
+    ['SGVs😀bG8=', 'base64'],
+    ['SGVsbG8gV29y-_bGQ', 'base64url'],
+    ['1ag123', 'hex'],
+    ['1a7', 'hex'],
+  ] as const) {
+    writeFileSync(file, data, { encoding })
+    t.deepEqual(nodeFs.readFileSync(file), Buffer.from(data, encoding), `${encoding} ${data}`)
+  }
+})
+
+test('writeFile: strict rejects malformed base64 / hex', (t) => {
+  const file = tmpFile('write-strict.bin')
+  const invalid = { message: /ERR_INVALID_ARG_VALUE/ }
+  t.throws(() => writeFileSync(file, 'abc', { encoding: 'hex', strict: true }), invalid)
+  t.throws(() => writeFileSync(file, 'zz', { encoding: 'hex', strict: true }), invalid)
+  t.throws(() => writeFileSync(file, 'SGk*', { encoding: 'base64', strict: true }), invalid)
+  t.throws(() => appendFileSync(file, 'SGk', { encoding: 'base64', strict: true }), invalid)
+  writeFileSync(file, 'SGVsbG8=', { encoding: 'base64', strict: true })
+  t.is(nodeFs.readFileSync(file, 'utf8'), 'Hello')
+})
+
+test('tail: line APIs share the encodings but reject utf16le', (t) => {
+  const file = tmpFile('lines-latin1.txt', Buffer.from('a\r\nb\xe9\nc\n', 'latin1'))
+  t.deepEqual(tailSync(file, 2, { encoding: 'latin1' }), ['b\xe9', 'c'])
+  t.deepEqual(readFileSync(file, { encoding: 'latin1', lines: { from: 2, to: 3 } }), 'b\xe9\nc')
+  t.throws(() => tailSync(file, 1, { encoding: 'utf16le' }), { message: /ERR_INVALID_ARG_VALUE/ })
+})
+
+// ===== WHATWG transcoding =====
+
+test('readFile: legacy encodings via WHATWG labels', (t) => {
+  const gbk = tmpFile('gbk.txt', Buffer.from([0xd6, 0xd0, 0xce, 0xc4]))
+  t.is(readFileSync(gbk, { encoding: 'gbk' }), '中文')
+  const sjis = tmpFile('sjis.txt', Buffer.from([0x93, 0xfa, 0x96, 0x7b, 0x0a, 0x8c, 0xea]))
+  t.is(readFileSync(sjis, { encoding: 'Shift_JIS' }), '日本\n語')
+  t.deepEqual(tailSync(sjis, 1, { encoding: 'sjis' }), ['語'])
+})
+
+test('readFile: utf-16 label sniffs the BOM', (t) => {
+  const file = tmpFile('utf16be-bom.txt', Buffer.from([0xfe, 0xff, 0x00, 0x68, 0x00, 0x69]))
+  t.is(readFileSync(file, { encoding: 'utf-16' }), 'hi')
+  t.throws(() => readFileSync(file, { encoding: 'utf-16be', lines: { from: 1, to: 1 } }), {
+    message: /ERR_INVALID_ARG_VALUE/,
+  })
+})
+
+test('writeFile: transcodes to legacy encodings', (t) => {
+  const file = tmpFile('write-sjis.txt')
+  writeFileSync(file, '日本 ✓', { encoding: 'shift_jis' })
+  t.deepEqual(nodeFs.readFileSync(file), Buffer.from([0x93, 0xfa, 0x96, 0x7b, 0x20, 0x3f]))
+  t.throws(() => writeFileSync(file, '日本 ✓', { encoding: 'shift_jis', strict: true }), { message: /U\+2713/ })
+  writeFileSync(file, 'hi', { encoding: 'utf-16be' })
+  t.deepEqual(nodeFs.readFileSync(file), Buffer.from([0x00, 0x68, 0x00, 0x69]))
+})
+
+test('writeFile: bom option', (t) => {
+  const file = tmpFile('write-bom.txt')
+  writeFileSync(file, 'hi', { encoding: 'utf16le', bom: true })
+  t.deepEqual(nodeFs.readFileSync(file), Buffer.from([0xff, 0xfe, 0x68, 0x00, 0x69, 0x00]))
+  writeFileSync(file, 'hi', { bom: true })
+  appendFileSync(file, '!', { bom: true })
+  t.deepEqual(nodeFs.readFileSync(file), Buffer.from([0xef, 0xbb, 0xbf, 0x68, 0x69, 0x21]))
+  t.throws(() => writeFileSync(file, 'hi', { encoding: 'gbk', bom: true }), { message: /ERR_INVALID_ARG_VALUE/ })
+})
@@ -0,0 +1,94 @@
+import test from 'ava'
+import { fallocateSync, fallocate } from '../index.js'
+import { writeFileSync, readFileSync, mkdirSync, statSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpFile(name: string, content: string | Buffer = ''): string {
+  const dir = join(tmpdir(), `rush-fs-test-fallocate-${Date.now()}-${Math.random().toString(36).slice(2)}`)
+  mkdirSync(dir, { recursive: true })
+  const file = join(dir, name)
+  writeFileSync(file, content)
+  return file
+}
+
+const linux = process.platform === 'linux'
+
+test('fallocateSync: should grow the file and reserve its blocks', (t) => {
+  const file = tmpFile('grow.bin', 'abc')
+  fallocateSync(file, 0, 1 << 20)
+  const stats = statSync(file)
+  t.is(stats.size, 1 << 20)
+  if (process.platform !== 'win32') {
+    t.true(stats.blocks * 512 >= 1 << 20)
+  }
+  const content = readFileSync(file)
+  t.is(content.subarray(0, 3).toString(), 'abc')
+  t.is(content[content.length - 1], 0)
+})
+
+test('fallocateSync: should never shrink the file', (t) => {
+  const file = tmpFile('shrink.bin', 'hello world')
+  fallocateSync(file, 0, 5)
+  t.is(readFileSync(file, 'utf8'), 'hello world')
+})
+
+test('fallocateSync: keepSize should reserve blocks without changing the size', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const file = tmpFile('keep.bin', 'abc')
+  fallocateSync(file, 0, 1 << 20, { keepSize: true })
+  const stats = statSync(file)
+  t.is(stats.size, 3)
+  t.true(stats.blocks * 512 >= 1 << 20)
+})
+
+test('fallocateSync: punchHole should deallocate a range that then reads as zeros', (t) => {
+  if (!linux) return t.pass()
+  const file = tmpFile('punch.bin', Buffer.alloc(1 << 16, 1))
+  const before = statSync(file).blocks
+  try {
+    fallocateSync(file, 4096, 8192, { punchHole: true })
+  } catch (e) {
+    // Not every filesystem supports hole punching.
+    t.regex((e as Error).message, /^ENOTSUP/)
+    return
+  }
+  const content = readFileSync(file)
+  t.is(content.length, 1 << 16)
+  t.is(content[4095], 1)
+  t.true(content.subarray(4096, 12288).every((b) => b === 0))
+  t.is(content[12288], 1)
+  t.true(statSync(file).blocks < before)
+})
+
+test('fallocateSync: zeroRange should zero a range', (t) => {
+  if (!linux) return t.pass()
+  const file = tmpFile('zero.bin', Buffer.alloc(8192, 1))
+  try {
+    fallocateSync(file, 0, 4096, { zeroRange: true })
+  } catch (e) {
+    t.regex((e as Error).message, /^ENOTSUP/)
+    return
+  }
+  const content = readFileSync(file)
+  t.is(content.length, 8192)
+  t.true(content.subarray(0, 4096).every((b) => b === 0))
+  t.is(content[4096], 1)
+})
+
+test('fallocateSync: should reject invalid arguments', (t) => {
+  const file = tmpFile('invalid.bin')
+  t.throws(() => fallocateSync(file, -1, 10), { message: `EINVAL: invalid argument, fallocate '${file}'` })
+  t.throws(() => fallocateSync(file, 0, 0), { message: /^EINVAL/ })
+  t.throws(() => fallocateSync(file, 0, 10, { punchHole: true, zeroRange: true }), {
+    message: /^ERR_INVALID_ARG_VALUE/,
+  })
+  t.throws(() => fallocateSync(join(file, 'missing'), 0, 10), { message: /^(ENOENT|ENOTDIR)/ })
+})
+
+test('fallocate: async should grow the file', async (t) => {
+  const file = tmpFile('async.bin')
+  await fallocate(file, 4096, 4096)
+  t.is(statSync(file).size, 8192)
+  await t.throwsAsync(async () => await fallocate(file, 0, -1), { message: /^EINVAL/ })
+})
@@ -356,3 +356,148 @@ test('glob: deep tree fixture — Rush-FS and node-glob return same match count'
     rmSync(root, { recursive: true })
   }
 })
+
+// ===== onError =====
+
+// Permission bits do not stop root, so these tests need an unprivileged user.
+const canDeny = process.platform !== 'win32' && process.getuid?.() !== 0
+
+test("glob: onError should default to 'skip' for unreadable directories", async (t) => {
+  if (!canDeny) return t.pass()
+  const base = makeDirFixture()
+  const locked = join(base, 'dist')
+  nodeFs.chmodSync(locked, 0o000)
+  try {
+    t.deepEqual(globSync('**/*.ts', { cwd: base }).sort(), ['src/a.ts', 'src/b.ts', 'src/sub/c.ts'])
+    t.throws(() => globSync('**/*.ts', { cwd: base, onError: 'throw', concurrency: 1 }), {
+      message: `EACCES: permission denied, scandir '${locked}'`,
+    })
+
+    const error = t.throws(() => globSync('**/*.ts', { cwd: base, onError: 'collect' }), {
+      instanceOf: AggregateError,
+    }) as AggregateError
+    t.is(error.message, "1 error occurred, glob '**/*.ts'")
+    t.is(error.errors[0].code, 'EACCES')
+    t.is(error.errors[0].path, locked)
+    await t.throwsAsync(async () => await glob('**/*.ts', { cwd: base, onError: 'collect' }), {
+      instanceOf: AggregateError,
+    })
+  } finally {
+    nodeFs.chmodSync(locked, 0o755)
+    rmSync(base, { recursive: true })
+  }
+})
+
+// ===== Multiple patterns =====
+
+test('globSync: should accept an array of patterns with ! negations', (t) => {
+  const base = makeDirFixture()
+  nodeFs.writeFileSync(join(base, 'src/a.test.ts'), '')
+  try {
+    t.deepEqual(globSync(['src/**/*.ts', 'dist/*.js', '!**/*.test.ts'], { cwd: base }).sort(), [
+      'dist/out.js',
+      'src/a.ts',
+      'src/b.ts',
+      'src/sub/c.ts',
+    ])
+    t.deepEqual(globSync(['src/**/*.ts', '!src/sub/**'], { cwd: base }).sort(), [
+      'src/a.test.ts',
+      'src/a.ts',
+      'src/b.ts',
+    ])
+    t.deepEqual(globSync(['!**/*.ts'], { cwd: base }), [])
+    t.deepEqual(globSync([], { cwd: base }), [])
+  } finally {
+    rmSync(base, { recursive: true })
+  }
+})
+
+test('globSync: should expand braces and ranges', (t) => {
+  const base = makeDirFixture()
+  nodeFs.writeFileSync(join(base, 'src/d.tsx'), '')
+  for (const n of [1, 2, 3, 10]) nodeFs.writeFileSync(join(base, `file${n}.txt`), '')
+  try {
+    t.deepEqual(globSync('src/*.{ts,tsx}', { cwd: base }).sort(), ['src/a.ts', 'src/b.ts', 'src/d.tsx', 'src/sub/c.ts'])
+    t.deepEqual(globSync('{src,dist}/*.js', { cwd: base }), ['dist/out.js'])
+    t.deepEqual(globSync('file{1..2}.txt', { cwd: base }).sort(), ['file1.txt', 'file2.txt'])
+    t.deepEqual(globSync('file{1..10..9}.txt', { cwd: base }).sort(), ['file1.txt', 'file10.txt'])
+  } finally {
+    rmSync(base, { recursive: true })
+  }
+})
+
+test('globSync: should support extglob groups', (t) => {
+  const base = makeDirFixture()
+  try {
+    t.deepEqual(globSync('src/@(a|b).ts', { cwd: base }).sort(), ['src/a.ts', 'src/b.ts'])
+    t.deepEqual(globSync('src/!(a|b).ts', { cwd: base }), ['src/sub/c.ts'])
+    t.deepEqual(globSync('+(src|dist)/*.js', { cwd: base }), ['dist/out.js'])
+    // Like bash, `!(a)` may take the empty string and leave `a` to the `*` after it.
+    t.deepEqual(globSync('src/!(a)*.ts', { cwd: base }).sort(), ['src/a.ts', 'src/b.ts', 'src/sub/c.ts'])
+    t.deepEqual(globSync('src/!(a|b).t*', { cwd: base }), ['src/sub/c.ts'])
+    t.throws(() => globSync('src/@(!(a)|b).ts', { cwd: base }), { message: /^ERR_INVALID_ARG_VALUE/ })
+  } finally {
+    rmSync(base, { recursive: true })
+  }
+})
+
+test('globSync: overlapping patterns should walk once and return each entry once', (t) => {
+  const base = makeDirFixture()
+  try {
+    const result = globSync(['src/**/*.ts', 'src/sub/*.ts', './src/*.ts', 'src/{a,a}.ts'], { cwd: base }) as string[]
+    t.is(result.length, 3)
+    t.deepEqual(result.map((p) => p.replace(/^\.\//, '')).sort(), ['src/a.ts', 'src/b.ts', 'src/sub/c.ts'])
+
+    const dirents = globSync(['src/*.ts', 'src/sub/*.ts'], { cwd: base, withFileTypes: true })
+    t.is(dirents.length, 3)
+  } finally {
+    rmSync(base, { recursive: true })
+  }
+})
+
+test('globSync: with gitIgnore, hidden roots should be walked on their own', (t) => {
+  const base = makeDirFixture()
+  nodeFs.mkdirSync(join(base, '.hidden'))
+  nodeFs.writeFileSync(join(base, '.hidden', 'h.js'), '')
+  try {
+    const result = globSync(['.hidden/**', '**/*.js', 'dist/*.js'], { cwd: base, gitIgnore: true }) as string[]
+    t.deepEqual(result.sort(), ['.hidden/h.js', 'dist/out.js'])
+  } finally {
+    rmSync(base, { recursive: true })
+  }
+})
+
+test('async: should accept an array of patterns', async (t) => {
+  const base = makeDirFixture()
+  try {
+    const result = (await glob(['src/*.ts', 'dist/*.js', '!src/b.ts'], { cwd: base })) as string[]
+    t.deepEqual(result.sort(), ['dist/out.js', 'src/a.ts', 'src/sub/c.ts'])
+  } finally {
+    rmSync(base, { recursive: true })
+  }
+})
+
+test('globSync: a negated dir/** should not be walked into', (t) => {
+  if (!canDeny) return t.pass()
+  const base = makeDirFixture()
+  const locked = join(base, 'node_modules', 'pkg')
+  nodeFs.mkdirSync(locked, { recursive: true })
+  nodeFs.writeFileSync(join(locked, 'index.ts'), '')
+  nodeFs.chmodSync(locked, 0o000)
+  try {
+    // Walking into node_modules/pkg would fail and be collected into an AggregateError.
+    for (const negation of ['!node_modules/**', '!**/node_modules/**', '!./node_modules/**']) {
+      t.deepEqual(globSync(['**/*.ts', negation], { cwd: base, onError: 'collect' }).sort(), [
+        'src/a.ts',
+        'src/b.ts',
+        'src/sub/c.ts',
+      ])
+    }
+    t.throws(() => globSync(['**/*.ts', '!node_modules/*.ts'], { cwd: base, onError: 'collect' }), {
+      instanceOf: AggregateError,
+    })
+  } finally {
+    nodeFs.chmodSync(locked, 0o755)
+    rmSync(base, { recursive: true })
+  }
+})
@@ -0,0 +1,142 @@
+import test from 'ava'
+import { hashFileSync, hashFile, hashTreeSync, hashTree } from '../index.js'
+import { writeFileSync, mkdirSync, symlinkSync } from 'node:fs'
+import { createHash } from 'node:crypto'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-hash-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+function makeTree(dir: string) {
+  mkdirSync(join(dir, 'src', 'lib'), { recursive: true })
+  mkdirSync(join(dir, 'node_modules'), { recursive: true })
+  writeFileSync(join(dir, 'package.json'), '{"name":"x"}')
+  writeFileSync(join(dir, 'src', 'index.ts'), 'export {}')
+  writeFileSync(join(dir, 'src', 'lib', 'util.ts'), 'export const a = 1')
+  writeFileSync(join(dir, 'node_modules', 'dep.js'), 'module.exports = 1')
+}
+
+// ===== hashFile =====
+
+test('hashFileSync: sha256 hex should match node:crypto', (t) => {
+  const file = join(tmpDir('sha256'), 'f.txt')
+  writeFileSync(file, 'hello rush-fs')
+  t.is(hashFileSync(file), createHash('sha256').update('hello rush-fs').digest('hex'))
+})
+
+test('hashFileSync: base64 and buffer encodings should match node:crypto', (t) => {
+  const file = join(tmpDir('encodings'), 'f.txt')
+  writeFileSync(file, 'abc')
+  const expected = createHash('sha256').update('abc').digest()
+  t.is(hashFileSync(file, { encoding: 'base64' }), expected.toString('base64'))
+  t.deepEqual(hashFileSync(file, { encoding: 'buffer' }), expected)
+})
+
+test('hashFileSync: blake3 and xxh3 should produce digests of the right size', (t) => {
+  const file = join(tmpDir('algos'), 'f.txt')
+  writeFileSync(file, 'abc')
+  t.is((hashFileSync(file, { algorithm: 'blake3' }) as string).length, 64)
+  t.is((hashFileSync(file, { algorithm: 'xxh3' }) as string).length, 32)
+})
+
+test('hashFileSync: should throw on unknown algorithm', (t) => {
+  const file = join(tmpDir('bad-algo'), 'f.txt')
+  writeFileSync(file, 'abc')
+  t.throws(() => hashFileSync(file, { algorithm: 'md5' }), { message: /ERR_INVALID_ARG_VALUE/ })
+})
+
+test('hashFileSync: should throw ENOENT on missing file', (t) => {
+  t.throws(() => hashFileSync(join(tmpDir('missing'), 'nope')), { message: /ENOENT/ })
+})
+
+test('hashFile: async should match sync', async (t) => {
+  const file = join(tmpDir('async-file'), 'f.txt')
+  writeFileSync(file, 'async')
+  t.is(await hashFile(file, { algorithm: 'blake3' }), hashFileSync(file, { algorithm: 'blake3' }))
+})
+
+// ===== hashTree =====
+
+test('hashTreeSync: identical trees should have identical digests', (t) => {
+  const a = tmpDir('tree-a')
+  const b = tmpDir('tree-b')
+  makeTree(a)
+  makeTree(b)
+  t.is(hashTreeSync(a).hash, hashTreeSync(b).hash)
+})
+
+test('hashTreeSync: digest should not depend on concurrency', (t) => {
+  const dir = tmpDir('tree-conc')
+  makeTree(dir)
+  t.is(hashTreeSync(dir, { concurrency: 1 }).hash, hashTreeSync(dir, { concurrency: 8 }).hash)
+})
+
+test('hashTreeSync: content change should change the digest', (t) => {
+  const dir = tmpDir('tree-change')
+  makeTree(dir)
+  const before = hashTreeSync(dir).hash
+  writeFileSync(join(dir, 'src', 'lib', 'util.ts'), 'export const a = 2')
+  t.not(hashTreeSync(dir).hash, before)
+})
+
+test('hashTreeSync: exclude should leave paths out of the digest', (t) => {
+  const a = tmpDir('tree-ex-a')
+  const b = tmpDir('tree-ex-b')
+  makeTree(a)
+  makeTree(b)
+  writeFileSync(join(b, 'node_modules', 'dep.js'), 'changed')
+  t.not(hashTreeSync(a).hash, hashTreeSync(b).hash)
+  t.is(hashTreeSync(a, { exclude: ['node_modules'] }).hash, hashTreeSync(b, { exclude: ['node_modules'] }).hash)
+})
+
+test('hashTreeSync: perFile should return sorted per-file hashes', (t) => {
+  const dir = tmpDir('tree-per-file')
+  makeTree(dir)
+  const result = hashTreeSync(dir, { perFile: true, exclude: ['node_modules'] })
+  t.deepEqual(
+    result.files!.map((f) => f.path),
+    ['package.json', 'src/index.ts', 'src/lib/util.ts'],
+  )
+  t.is(result.files![0].hash, createHash('sha256').update('{"name":"x"}').digest('hex'))
+})
+
+test('hashTreeSync: names differing only in non-UTF-8 bytes should hash differently', (t) => {
+  // Linux filenames are arbitrary bytes; other platforms reject these names.
+  if (process.platform !== 'linux') return t.pass()
+  const [a, b] = [tmpDir('tree-bytes-a'), tmpDir('tree-bytes-b')]
+  writeFileSync(Buffer.concat([Buffer.from(`${a}/`), Buffer.from([0xff])]), 'x')
+  writeFileSync(Buffer.concat([Buffer.from(`${b}/`), Buffer.from([0xfe])]), 'x')
+  t.not(hashTreeSync(a).hash, hashTreeSync(b).hash)
+})
+
+test('hashTreeSync: followSymlinks should hash dangling and looping links as links', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('tree-follow')
+  mkdirSync(join(dir, 'd'))
+  writeFileSync(join(dir, 'd', 'f.txt'), 'f')
+  symlinkSync('nowhere', join(dir, 'dangling'))
+  symlinkSync('.', join(dir, 'd', 'loop'))
+  symlinkSync('d', join(dir, 'linked'))
+  const followed = hashTreeSync(dir, { followSymlinks: true, perFile: true })
+  t.deepEqual(
+    followed.files!.map((f) => f.path),
+    ['d/f.txt', 'd/loop', 'dangling', 'linked/f.txt', 'linked/loop'],
+  )
+  const plain = hashTreeSync(dir, { perFile: true })
+  t.is(followed.files![2].hash, plain.files!.find((f) => f.path === 'dangling')!.hash)
+})
+
+test('hashTreeSync: should throw ENOENT on missing root', (t) => {
+  t.throws(() => hashTreeSync(join(tmpDir('tree-missing'), 'nope')), { message: /ENOENT/ })
+})
+
+test('hashTree: async should match sync', async (t) => {
+  const dir = tmpDir('tree-async')
+  makeTree(dir)
+  const result = await hashTree(dir, { algorithm: 'xxh3' })
+  t.is(result.hash, hashTreeSync(dir, { algorithm: 'xxh3' }).hash)
+})
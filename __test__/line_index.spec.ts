@@ -0,0 +1,126 @@
+import test from 'ava'
+import { buildLineIndex, buildLineIndexSync, LineIndex } from '../index.js'
+import { writeFileSync, appendFileSync, mkdirSync, renameSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-line-index-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+test('buildLineIndexSync: should count lines like readLines', (t) => {
+  const dir = tmpDir('count')
+  const cases: [string, number][] = [
+    ['', 0],
+    ['\n', 1],
+    ['a', 1],
+    ['a\n', 1],
+    ['a\r\nb\n\nd', 4],
+  ]
+  for (const [content, count] of cases) {
+    const file = join(dir, `f${count}-${content.length}`)
+    writeFileSync(file, content)
+    const index = buildLineIndexSync(file)
+    t.is(index.lineCount, count, JSON.stringify(content))
+    t.is(index.size, Buffer.byteLength(content))
+  }
+})
+
+test('LineIndex.readLinesSync: should read ranges and clamp to the line count', (t) => {
+  const file = join(tmpDir('range'), 'a.txt')
+  writeFileSync(file, 'one\r\ntwo\n\nfour')
+  const index = buildLineIndexSync(file)
+  t.deepEqual(index.readLinesSync(1, 10), ['one', 'two', '', 'four'])
+  t.deepEqual(index.readLinesSync(2, 3), ['two', ''])
+  t.deepEqual(index.readLinesSync(4, 4), ['four'])
+  t.deepEqual(index.readLinesSync(5, 6), [])
+  t.deepEqual(index.readLinesSync(0, 2), [])
+  t.deepEqual(index.readLinesSync(3, 2), [])
+})
+
+test('LineIndex.readLinesSync: buffer encoding keeps raw bytes', (t) => {
+  const file = join(tmpDir('buffer'), 'a.bin')
+  writeFileSync(file, Buffer.from([0x61, 0x0a, 0xff, 0xfe, 0x0a]))
+  const index = buildLineIndexSync(file)
+  t.deepEqual(index.readLinesSync(2, 2, 'buffer'), [Buffer.from([0xff, 0xfe])])
+  t.deepEqual(index.readLinesSync(2, 2), ['��'])
+})
+
+test('LineIndex.refreshSync: should pick up appended lines, including a completed last line', (t) => {
+  const file = join(tmpDir('append'), 'a.log')
+  writeFileSync(file, 'one\ntw')
+  const index = buildLineIndexSync(file)
+  t.is(index.lineCount, 2)
+  appendFileSync(file, 'o\nthree\n')
+  t.is(index.refreshSync(), 3)
+  t.deepEqual(index.readLinesSync(2, 3), ['two', 'three'])
+})
+
+test('LineIndex.refreshSync: should rebuild after truncation or replacement', (t) => {
+  const dir = tmpDir('rotate')
+  const file = join(dir, 'a.log')
+  writeFileSync(file, 'a\nb\nc\n')
+  const index = buildLineIndexSync(file)
+  writeFileSync(file, 'x\n')
+  t.is(index.refreshSync(), 1)
+  t.deepEqual(index.readLinesSync(1, 5), ['x'])
+
+  writeFileSync(join(dir, 'next.log'), 'rotated file\nwith more content\n')
+  renameSync(join(dir, 'next.log'), file)
+  t.is(index.refreshSync(), 2)
+  t.deepEqual(index.readLinesSync(1, 2), ['rotated file', 'with more content'])
+})
+
+test('LineIndex.readLinesSync: should throw ESTALE once the file was replaced or truncated', (t) => {
+  const dir = tmpDir('stale')
+  const file = join(dir, 'a.log')
+  writeFileSync(file, 'a\nb\nc\n')
+  const index = buildLineIndexSync(file)
+  writeFileSync(file, 'x\n')
+  t.throws(() => index.readLinesSync(1, 3), {
+    message: `ESTALE: file was replaced or truncated since it was indexed, readLines '${file}'`,
+  })
+
+  index.refreshSync()
+  writeFileSync(join(dir, 'next.log'), 'y\n')
+  renameSync(join(dir, 'next.log'), file)
+  t.throws(() => index.readLinesSync(1, 1), { message: /^ESTALE/ })
+  index.refreshSync()
+  t.deepEqual(index.readLinesSync(1, 1), ['y'])
+})
+
+test('buildLineIndexSync: should index large files', (t) => {
+  const file = join(tmpDir('large'), 'a.txt')
+  const lines = Array.from({ length: 200000 }, (_, i) => `row ${i + 1}`)
+  writeFileSync(file, lines.join('\n') + '\n')
+  const index = buildLineIndexSync(file)
+  t.is(index.lineCount, 200000)
+  t.deepEqual(index.readLinesSync(150000, 150002), lines.slice(149999, 150002))
+})
+
+test('buildLineIndexSync: should throw ENOENT on missing file', (t) => {
+  t.throws(() => buildLineIndexSync(join(tmpDir('missing'), 'nope')), { message: /ENOENT/ })
+})
+
+test('buildLineIndex: async should return a LineIndex', async (t) => {
+  const file = join(tmpDir('async'), 'a.txt')
+  writeFileSync(file, 'a\nb\n')
+  const index = (await buildLineIndex(file)) as LineIndex
+  t.true(index instanceof LineIndex)
+  t.deepEqual(index.readLinesSync(1, 2), ['a', 'b'])
+})
+
+test('LineIndex.refresh / readLines: async variants should run off the JS thread', async (t) => {
+  const file = join(tmpDir('async-methods'), 'a.log')
+  writeFileSync(file, 'one\n')
+  const index = buildLineIndexSync(file)
+  appendFileSync(file, 'two\nthree\n')
+  const refreshing = index.refresh()
+  t.is(await refreshing, 3)
+  t.deepEqual(await index.readLines(2, 3), ['two', 'three'])
+  t.deepEqual(await index.readLines(3, 3, 'buffer'), [Buffer.from('three')])
+  writeFileSync(file, 'x\n')
+  await t.throwsAsync(index.readLines(1, 1), { message: /^ESTALE/ })
+})
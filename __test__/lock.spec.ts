@@ -0,0 +1,165 @@
+import test from 'ava'
+import { lockSync, lock, lockfileSync, lockfile, FileLock, Lockfile } from '../index.js'
+import { writeFileSync, mkdirSync, existsSync, readFileSync, utimesSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+import { execFileSync } from 'node:child_process'
+import { fileURLToPath } from 'node:url'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-lock-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+const methods = process.platform === 'linux' ? (['flock', 'fcntl'] as const) : (['flock'] as const)
+
+for (const method of methods) {
+  test(`lockSync (${method}): exclusive locks should conflict until released`, (t) => {
+    const file = join(tmpDir(`excl-${method}`), 'cache.db')
+    writeFileSync(file, '')
+    const held = lockSync(file, { method })
+    t.true(held instanceof FileLock)
+    t.true(held.exclusive)
+    t.false(held.released)
+    t.throws(() => lockSync(file, { method, wait: false }), {
+      message: `EAGAIN: resource temporarily unavailable, lock '${file}'`,
+    })
+    t.throws(() => lockSync(file, { method, shared: true, wait: false }), { message: /^EAGAIN/ })
+    held.release()
+    held.release()
+    t.true(held.released)
+    lockSync(file, { method, wait: false }).release()
+  })
+
+  test(`lockSync (${method}): shared locks should coexist and block exclusive ones`, (t) => {
+    const file = join(tmpDir(`shared-${method}`), 'cache.db')
+    writeFileSync(file, '')
+    const a = lockSync(file, { method, shared: true })
+    const b = lockSync(file, { method, exclusive: false, wait: false })
+    t.false(a.exclusive)
+    const start = Date.now()
+    t.throws(() => lockSync(file, { method, timeout: 100 }), {
+      message: `ETIMEDOUT: lock not acquired within 100ms, lock '${file}'`,
+    })
+    t.true(Date.now() - start >= 90)
+    a.release()
+    b.release()
+  })
+}
+
+test('lockSync: should lock directories and reject bad arguments', (t) => {
+  const dir = tmpDir('dir')
+  const held = lockSync(dir)
+  t.throws(() => lockSync(dir, { wait: false }), { message: /^EAGAIN/ })
+  held.release()
+  t.throws(() => lockSync(join(dir, 'missing')), {
+    message: `ENOENT: no such file or directory, lock '${join(dir, 'missing')}'`,
+  })
+  t.throws(() => lockSync(dir, { shared: true, exclusive: true }), { message: /^ERR_INVALID_ARG_VALUE/ })
+  t.throws(() => lockSync(dir, { method: 'lockf' as 'flock' }), { message: /^ERR_INVALID_ARG_VALUE/ })
+})
+
+test('lock: async should wait for the lock to be released', async (t) => {
+  const file = join(tmpDir('async'), 'cache.db')
+  writeFileSync(file, '')
+  const held = lockSync(file)
+  setTimeout(() => held.release(), 50)
+  const next = (await lock(file, { timeout: 5000 })) as FileLock
+  t.true(held.released)
+  t.false(next.released)
+  const { dispose } = Symbol as unknown as { dispose?: symbol }
+  if (dispose) {
+    Reflect.get(next, dispose).call(next)
+    t.true(next.released)
+  }
+  next.release()
+})
+
+test('lock: async waiters should not hold up other async calls', async (t) => {
+  const dir = tmpDir('waiters')
+  const file = join(dir, 'cache.db')
+  writeFileSync(file, '')
+  const held = lockSync(file)
+  const waiters = Array.from({ length: 8 }, () => lock(file).then((l) => (l as FileLock).release()))
+  await t.throwsAsync(lock(file, { timeout: 50 }), { message: /^ETIMEDOUT/ })
+  await t.throwsAsync(lockfile(join(dir, 'missing', 'x.lock')), { message: /^ENOENT/ })
+  held.release()
+  await Promise.all(waiters)
+  t.pass()
+})
+
+test('lockfileSync: should create the lockfile exclusively and remove it on release', (t) => {
+  const path = join(tmpDir('lockfile'), 'cache.lock')
+  const held = lockfileSync(path)
+  t.true(held instanceof Lockfile)
+  t.is(readFileSync(path, 'utf8').split('\n')[0], String(process.pid))
+  t.throws(() => lockfileSync(path), { message: `EEXIST: file already exists, lockfile '${path}'` })
+  const start = Date.now()
+  t.throws(() => lockfileSync(path, { retries: 2, retryWait: 30 }), { message: /^EEXIST/ })
+  t.true(Date.now() - start >= 50)
+  held.touch()
+  held.release()
+  held.release()
+  t.true(held.released)
+  t.false(existsSync(path))
+  t.throws(() => held.touch(), { message: /^ECOMPROMISED/ })
+})
+
+test('lockfileSync: should take over stale lockfiles only', (t) => {
+  const path = join(tmpDir('stale'), 'cache.lock')
+  writeFileSync(path, '1')
+  const old = new Date(Date.now() - 10_000)
+  utimesSync(path, old, old)
+  t.throws(() => lockfileSync(path, { stale: 60_000 }), { message: /^EEXIST/ })
+  const held = lockfileSync(path, { stale: 5_000 })
+  t.is(readFileSync(path, 'utf8').split('\n')[0], String(process.pid))
+
+  // The previous holder's handle must not delete the new lock.
+  const other = lockfileSync(join(tmpDir('stale-other'), 'x.lock'))
+  utimesSync(path, old, old)
+  const takeover = lockfileSync(path, { stale: 5_000 })
+  t.throws(() => held.touch(), { message: /^ECOMPROMISED/ })
+  held.release()
+  t.true(existsSync(path))
+  takeover.release()
+  t.false(existsSync(path))
+  other.release()
+})
+
+test('lockfile: async should retry until the lockfile is released', async (t) => {
+  const path = join(tmpDir('async-lockfile'), 'cache.lock')
+  const held = lockfileSync(path)
+  setTimeout(() => held.release(), 50)
+  const next = await lockfile(path, { retries: 50, retryWait: 10 })
+  t.false(next.released)
+  next.release()
+  await t.throwsAsync(async () => await lockfile(join(path, 'missing', 'x.lock')), { message: /^ENOENT/ })
+})
+
+test('lockfile: retrying callers should not hold up other async calls', async (t) => {
+  const dir = tmpDir('lockfile-waiters')
+  const path = join(dir, 'cache.lock')
+  const held = lockfileSync(path)
+  const waiters = Array.from({ length: 8 }, () =>
+    lockfile(path, { retries: 1000, retryWait: 5 }).then((l) => l.release()),
+  )
+  await t.throwsAsync(lockfile(join(dir, 'missing', 'x.lock')), { message: /^ENOENT/ })
+  held.release()
+  await Promise.all(waiters)
+  t.false(existsSync(path))
+})
+
+test('lockfile: should be removed when the process exits', (t) => {
+  const path = join(tmpDir('exit'), 'cache.lock')
+  const index = fileURLToPath(new URL('../index.js', import.meta.url))
+  const script = `
+    const { lockfileSync } = require(${JSON.stringify(index)})
+    globalThis.held = lockfileSync(${JSON.stringify(path)})
+    require('node:fs').writeFileSync(${JSON.stringify(path + '.seen')}, '')
+    process.exit(0)
+  `
+  execFileSync(process.execPath, ['-e', script])
+  t.true(existsSync(path + '.seen'))
+  t.false(existsSync(path))
+})
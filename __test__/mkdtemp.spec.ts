@@ -1,7 +1,7 @@
 import test from 'ava'
-import { mkdtempSync, mkdtemp } from '../index.js'
+import { mkdtempSync, mkdtemp, mkstempSync, mkstemp } from '../index.js'
 import * as nodeFs from 'node:fs'
-import { existsSync } from 'node:fs'
+import { existsSync, closeSync, writeSync, readFileSync, statSync } from 'node:fs'
 import { join } from 'node:path'
 import { tmpdir } from 'node:os'
 
@@ -60,3 +60,37 @@ test('dual-run: mkdtempSync should behave like node:fs.mkdtempSync', (t) => {
   t.true(nodeFs.statSync(nodeDir).isDirectory())
   t.true(nodeFs.statSync(hyperDir).isDirectory())
 })
+
+// ===== mkstemp =====
+
+test('mkstempSync: should create a new file and return an open fd', (t) => {
+  const { path, fd } = mkstempSync(prefix)
+  t.true(path.startsWith(prefix))
+  t.is(path.length, prefix.length + 6)
+  writeSync(fd, 'hello')
+  closeSync(fd)
+  t.is(readFileSync(path, 'utf8'), 'hello')
+  if (process.platform !== 'win32') {
+    t.is(statSync(path).mode & 0o777, 0o600)
+  }
+})
+
+test('mkstempSync: should create unique files and throw ENOENT for non-existent parent', (t) => {
+  const a = mkstempSync(prefix)
+  const b = mkstempSync(prefix)
+  closeSync(a.fd)
+  closeSync(b.fd)
+  t.not(a.path, b.path)
+  t.throws(() => mkstempSync('/tmp/no-such-parent-dir-999/prefix-'), {
+    message: "ENOENT: no such file or directory, mkstemp '/tmp/no-such-parent-dir-999/prefix-'",
+  })
+})
+
+test('mkstemp: async should create a new file', async (t) => {
+  const { path, fd } = (await mkstemp(prefix)) as ReturnType<typeof mkstempSync>
+  closeSync(fd)
+  t.true(existsSync(path))
+  await t.throwsAsync(async () => await mkstemp('/tmp/no-such-parent-dir-999/prefix-'), {
+    message: /ENOENT/,
+  })
+})
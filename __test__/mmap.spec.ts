@@ -0,0 +1,74 @@
+import test from 'ava'
+import { mmapFile } from '../index.js'
+import { writeFileSync, readFileSync, mkdirSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpFile(name: string, content: string | Buffer): string {
+  const dir = join(tmpdir(), `rush-fs-test-mmap-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  const file = join(dir, 'data.bin')
+  writeFileSync(file, content)
+  return file
+}
+
+test('mmapFile: should return a Buffer with the file contents', (t) => {
+  const file = tmpFile('basic', 'hello mmap world')
+  const buf = mmapFile(file)
+  t.true(Buffer.isBuffer(buf))
+  t.is(buf.toString(), 'hello mmap world')
+  buf.close()
+})
+
+test('mmapFile: offset and length should select a range, clamped to EOF', (t) => {
+  const file = tmpFile('range', 'hello mmap world')
+  t.is(mmapFile(file, { offset: 6, length: 4 }).toString(), 'mmap')
+  t.is(mmapFile(file, { offset: 6, length: 1000 }).toString(), 'mmap world')
+  t.is(mmapFile(file, { offset: 16 }).length, 0)
+})
+
+test('mmapFile: unaligned offsets in large files', (t) => {
+  const data = Buffer.alloc(3 * 4096 + 10)
+  for (let i = 0; i < data.length; i++) data[i] = i % 251
+  const file = tmpFile('unaligned', data)
+  const buf = mmapFile(file, { offset: 4097, length: 5000 })
+  t.deepEqual(Buffer.from(buf), data.subarray(4097, 4097 + 5000))
+  buf.close()
+})
+
+test('mmapFile: writes should not reach the file (copy-on-write)', (t) => {
+  const file = tmpFile('cow', 'hello')
+  const buf = mmapFile(file)
+  buf[0] = 0x48
+  t.is(buf.toString(), 'Hello')
+  t.is(readFileSync(file, 'utf8'), 'hello')
+  buf.close()
+})
+
+test('mmapFile: close() should detach the Buffer and be idempotent', (t) => {
+  const file = tmpFile('close', 'hello')
+  const buf = mmapFile(file)
+  buf.close()
+  t.is(buf.length, 0)
+  t.notThrows(() => buf.close())
+})
+
+test('mmapFile: close() called on another Buffer should throw', (t) => {
+  const file = tmpFile('close-other', 'hello')
+  const a = mmapFile(file)
+  const b = mmapFile(file)
+  t.throws(() => a.close.call(b), { message: /close\(\) must be called/ })
+  a.close()
+  b.close()
+})
+
+test('mmapFile: should reject out of range offsets', (t) => {
+  const file = tmpFile('offset', 'hello')
+  t.throws(() => mmapFile(file, { offset: 6 }), { message: /ERR_OUT_OF_RANGE/ })
+  t.throws(() => mmapFile(file, { offset: -1 }), { message: /ERR_OUT_OF_RANGE/ })
+  t.throws(() => mmapFile(file, { length: -1 }), { message: /ERR_OUT_OF_RANGE/ })
+})
+
+test('mmapFile: should throw ENOENT on missing file', (t) => {
+  t.throws(() => mmapFile(join(tmpdir(), `rush-fs-mmap-missing-${Date.now()}`)), { message: /ENOENT/ })
+})
@@ -102,12 +102,11 @@ test('readFile: async should clamp line range to file length', async (t) => {
   t.is(result, ['line 10', 'line 11', 'line 12'].join('\n'))
 })
 
-test('readFile: async should ignore line range in Buffer mode', async (t) => {
+test('readFile: async should reject a line range in Buffer mode', async (t) => {
   const fixturePath = join(tmpdir(), `rush-fs-read-lines-${Date.now()}-buffer.txt`)
   writeFileSync(fixturePath, multilineFixture)
 
-  const result = await readFile(fixturePath, { lines: { from: 1, to: 2 } })
-
-  t.true(Buffer.isBuffer(result))
-  t.is((result as Buffer).toString('utf8'), multilineFixture)
+  await t.throwsAsync(readFile(fixturePath, { lines: { from: 1, to: 2 } }), {
+    message: /^ERR_INVALID_ARG_VALUE/,
+  })
 })
@@ -0,0 +1,136 @@
+import test from 'ava'
+import { readLines, tail, tailSync } from '../index.js'
+import { writeFileSync, mkdirSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-lines-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+async function collect<T>(iterable: AsyncIterable<T>): Promise<T[]> {
+  const batches: T[] = []
+  for await (const batch of iterable) {
+    batches.push(batch)
+  }
+  return batches
+}
+
+test('readLines: should handle CRLF, empty lines and a final line without newline', async (t) => {
+  const file = join(tmpDir('crlf'), 'a.txt')
+  writeFileSync(file, 'one\r\ntwo\n\nfour')
+  t.deepEqual(await collect(readLines(file)), [['one', 'two', '', 'four']])
+})
+
+test('readLines: trailing newline should not produce an extra empty line', async (t) => {
+  const file = join(tmpDir('trailing'), 'a.txt')
+  writeFileSync(file, 'a\nb\n')
+  t.deepEqual(await collect(readLines(file)), [['a', 'b']])
+})
+
+test('readLines: empty file should yield nothing', async (t) => {
+  const file = join(tmpDir('empty'), 'a.txt')
+  writeFileSync(file, '')
+  t.deepEqual(await collect(readLines(file)), [])
+})
+
+test('readLines: start and batchSize', async (t) => {
+  const file = join(tmpDir('start'), 'a.txt')
+  writeFileSync(file, '1\n2\n3\n4\n5\n')
+  t.deepEqual(await collect(readLines(file, { start: 2, batchSize: 2 })), [['2', '3'], ['4', '5']])
+})
+
+test('readLines: invalid UTF-8 should be replaced, buffer encoding keeps raw bytes', async (t) => {
+  const file = join(tmpDir('non-utf8'), 'a.bin')
+  writeFileSync(file, Buffer.from([0x61, 0xff, 0x62, 0x0a, 0x63]))
+  t.deepEqual(await collect(readLines(file)), [['a�b', 'c']])
+  const [[first, second]] = (await collect(readLines(file, { encoding: 'buffer' }))) as Buffer[][]
+  t.deepEqual(first, Buffer.from([0x61, 0xff, 0x62]))
+  t.deepEqual(second, Buffer.from('c'))
+})
+
+test('readLines: latin1 encoding', async (t) => {
+  const file = join(tmpDir('latin1'), 'a.txt')
+  writeFileSync(file, Buffer.from([0xe9, 0x0a]))
+  t.deepEqual(await collect(readLines(file, { encoding: 'latin1' })), [['é']])
+})
+
+test('readLines: large file should be split into batches', async (t) => {
+  const file = join(tmpDir('large'), 'a.txt')
+  const lines = Array.from({ length: 5000 }, (_, i) => `line ${i + 1}`)
+  writeFileSync(file, lines.join('\n') + '\n')
+  const batches = await collect(readLines(file))
+  t.true(batches.length > 1)
+  t.deepEqual(batches.flat(), lines)
+})
+
+test('readLines: abort signal should reject with AbortError', async (t) => {
+  const file = join(tmpDir('abort'), 'a.txt')
+  writeFileSync(file, Array.from({ length: 1000 }, (_, i) => String(i)).join('\n'))
+  const controller = new AbortController()
+  let batches = 0
+  await t.throwsAsync(
+    async () => {
+      for await (const _ of readLines(file, { signal: controller.signal, batchSize: 10 })) {
+        if (++batches === 2) controller.abort()
+      }
+    },
+    { message: /AbortError/ },
+  )
+  t.is(batches, 2)
+})
+
+test('readLines: already aborted signal should reject immediately', async (t) => {
+  const file = join(tmpDir('aborted'), 'a.txt')
+  writeFileSync(file, 'a\n')
+  const controller = new AbortController()
+  controller.abort()
+  await t.throwsAsync(() => collect(readLines(file, { signal: controller.signal })), { message: /AbortError/ })
+})
+
+test('readLines: should throw ENOENT and reject unknown encodings', (t) => {
+  const dir = tmpDir('errors')
+  t.throws(() => readLines(join(dir, 'nope')), { message: /ENOENT/ })
+  writeFileSync(join(dir, 'a'), 'x')
+  t.throws(() => readLines(join(dir, 'a'), { encoding: 'nope' }), { message: /Unknown encoding/ })
+})
+
+test('tailSync: should return the last n lines', (t) => {
+  const file = join(tmpDir('tail'), 'a.txt')
+  writeFileSync(file, 'one\r\ntwo\n\nfour')
+  t.deepEqual(tailSync(file, 2), ['', 'four'])
+  t.deepEqual(tailSync(file, 10), ['one', 'two', '', 'four'])
+  t.deepEqual(tailSync(file, 0), [])
+})
+
+test('tailSync: trailing newline and empty file', (t) => {
+  const dir = tmpDir('tail-edge')
+  writeFileSync(join(dir, 'a'), 'a\nb\n')
+  writeFileSync(join(dir, 'empty'), '')
+  writeFileSync(join(dir, 'nl'), '\n')
+  t.deepEqual(tailSync(join(dir, 'a'), 1), ['b'])
+  t.deepEqual(tailSync(join(dir, 'empty'), 3), [])
+  t.deepEqual(tailSync(join(dir, 'nl'), 3), [''])
+})
+
+test('tailSync: should read across chunk boundaries', (t) => {
+  const file = join(tmpDir('tail-large'), 'a.txt')
+  const lines = Array.from({ length: 100000 }, (_, i) => `line ${i + 1}`)
+  writeFileSync(file, lines.join('\n') + '\n')
+  t.deepEqual(tailSync(file, 30000), lines.slice(-30000))
+})
+
+test('tailSync: buffer encoding keeps raw bytes', (t) => {
+  const file = join(tmpDir('tail-buffer'), 'a.bin')
+  writeFileSync(file, Buffer.from([0x61, 0x0a, 0xff, 0xfe]))
+  t.deepEqual(tailSync(file, 1, { encoding: 'buffer' }), [Buffer.from([0xff, 0xfe])])
+  t.deepEqual(tailSync(file, 1), ['��'])
+})
+
+test('tail: async should match sync', async (t) => {
+  const file = join(tmpDir('tail-async'), 'a.txt')
+  writeFileSync(file, 'a\nb\nc\n')
+  t.deepEqual(await tail(file, 2), tailSync(file, 2))
+})
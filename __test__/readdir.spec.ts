@@ -1,6 +1,8 @@
 import test from 'ava'
 import * as nodeFs from 'node:fs'
 import { readdirSync, readdir } from '../index.js'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
 
 test('sync: should list files in current directory (strings by default)', (t) => {
   const files = readdirSync('.')
@@ -126,3 +128,68 @@ test('dual-run: readdirSync withFileTypes isFile/isDirectory should match', (t)
     }
   }
 })
+
+// ===== onError =====
+
+// Permission bits do not stop root, so these tests need an unprivileged user.
+const canDeny = process.platform !== 'win32' && process.getuid?.() !== 0
+
+function lockedTree(): { root: string; locked: string } {
+  const root = nodeFs.mkdtempSync(join(tmpdir(), 'rush-fs-readdir-onerror-'))
+  nodeFs.mkdirSync(join(root, 'open', 'deep'), { recursive: true })
+  nodeFs.writeFileSync(join(root, 'open', 'deep', 'a.txt'), 'a')
+  const locked = join(root, 'locked')
+  nodeFs.mkdirSync(locked)
+  nodeFs.writeFileSync(join(locked, 'secret.txt'), 's')
+  nodeFs.chmodSync(locked, 0o000)
+  return { root, locked }
+}
+
+test('onError: recursive readdir should throw on unreadable directories by default', (t) => {
+  if (!canDeny) return t.pass()
+  const { root, locked } = lockedTree()
+  try {
+    t.throws(() => readdirSync(root, { recursive: true }), {
+      message: `EACCES: permission denied, scandir '${locked}'`,
+    })
+  } finally {
+    nodeFs.chmodSync(locked, 0o755)
+  }
+})
+
+test("onError: 'skip' should leave unreadable directories' contents out", (t) => {
+  if (!canDeny) return t.pass()
+  const { root, locked } = lockedTree()
+  try {
+    const files = (readdirSync(root, { recursive: true, onError: 'skip' }) as string[]).sort()
+    t.deepEqual(files, ['locked', 'open', 'open/deep', 'open/deep/a.txt'])
+  } finally {
+    nodeFs.chmodSync(locked, 0o755)
+  }
+})
+
+test("onError: 'collect' should throw an AggregateError after listing everything else", async (t) => {
+  if (!canDeny) return t.pass()
+  const { root, locked } = lockedTree()
+  try {
+    const error = t.throws(() => readdirSync(root, { recursive: true, onError: 'collect' }), {
+      instanceOf: AggregateError,
+    }) as AggregateError
+    t.is(error.message, `1 error occurred, scandir '${root}'`)
+    t.is(error.errors.length, 1)
+    t.is(error.errors[0].code, 'EACCES')
+    t.is(error.errors[0].syscall, 'scandir')
+    t.is(error.errors[0].path, locked)
+    t.is(error.errors[0].message, `EACCES: permission denied, scandir '${locked}'`)
+
+    await t.throwsAsync(async () => await readdir(root, { recursive: true, onError: 'collect' }), {
+      instanceOf: AggregateError,
+    })
+  } finally {
+    nodeFs.chmodSync(locked, 0o755)
+  }
+})
+
+test('onError: should reject unknown values', (t) => {
+  t.throws(() => readdirSync('.', { onError: 'ignore' }), { message: /^ERR_INVALID_ARG_VALUE/ })
+})
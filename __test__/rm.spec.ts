@@ -1,6 +1,6 @@
 import test from 'ava'
-import { rmSync, rm } from '../index.js'
-import { mkdirSync, writeFileSync, existsSync, rmSync as nodeRmSync } from 'node:fs'
+import { rmSync, rm, type DryRunPlan } from '../index.js'
+import { mkdirSync, writeFileSync, existsSync, symlinkSync, lstatSync, chmodSync, rmSync as nodeRmSync } from 'node:fs'
 import { join } from 'node:path'
 import { tmpdir } from 'node:os'
 
@@ -17,7 +17,7 @@ test('sync: should remove a file', (t) => {
   writeFileSync(testFile, 'test content')
 
   t.true(existsSync(testFile), 'File should exist before removal')
-  rmSync(testFile)
+  t.is(rmSync(testFile), undefined)
   t.false(existsSync(testFile), 'File should not exist after removal')
 })
 
@@ -27,7 +27,7 @@ test('async: should remove a file', async (t) => {
   writeFileSync(testFile, 'test content')
 
   t.true(existsSync(testFile), 'File should exist before removal')
-  await rm(testFile)
+  t.is(await rm(testFile), undefined)
   t.false(existsSync(testFile), 'File should not exist after removal')
 })
 
@@ -280,3 +280,174 @@ test('async: maxRetries with recursive should work', async (t) => {
   await rm(testDir, { recursive: true, maxRetries: 2, retryDelay: 50 })
   t.false(existsSync(testDir))
 })
+
+test('sync: recursive should remove symlinks without following them', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const tempDir = createTempDir()
+  const outside = join(tempDir, 'outside')
+  mkdirSync(outside)
+  writeFileSync(join(outside, 'keep.txt'), 'keep')
+  const testDir = join(tempDir, 'tree')
+  mkdirSync(join(testDir, 'a', 'b'), { recursive: true })
+  symlinkSync(outside, join(testDir, 'link'))
+  symlinkSync(outside, join(testDir, 'a', 'b', 'link'))
+
+  rmSync(testDir, { recursive: true })
+  t.false(existsSync(testDir))
+  t.true(existsSync(join(outside, 'keep.txt')))
+})
+
+test('async: recursive with concurrency should remove symlinks without following them', async (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const tempDir = createTempDir()
+  const outside = join(tempDir, 'outside')
+  mkdirSync(outside)
+  writeFileSync(join(outside, 'keep.txt'), 'keep')
+  const testDir = join(tempDir, 'tree')
+  for (let i = 0; i < 8; i++) {
+    mkdirSync(join(testDir, `d${i}`), { recursive: true })
+    symlinkSync(outside, join(testDir, `d${i}`, 'link'))
+  }
+
+  await rm(testDir, { recursive: true, concurrency: 4 })
+  t.false(existsSync(testDir))
+  t.true(existsSync(join(outside, 'keep.txt')))
+})
+
+test('sync: should remove a tree deeper than the parallel fan-out, with and without concurrency', (t) => {
+  const tempDir = createTempDir()
+  for (const concurrency of [0, 4]) {
+    const testDir = join(tempDir, `deep-${concurrency}`)
+    const leaf = join(testDir, ...Array.from({ length: 64 }, () => 'd'))
+    mkdirSync(leaf, { recursive: true })
+    writeFileSync(join(leaf, 'file.txt'), 'content')
+    for (let i = 0; i < 50; i++) {
+      mkdirSync(join(testDir, 'd', 'd', 'd', 'd', 'd', `w${i}`, 'sub'), { recursive: true })
+    }
+
+    rmSync(testDir, { recursive: true, concurrency })
+    t.false(existsSync(testDir))
+  }
+})
+
+test('sync: should remove a symlink to a directory, not the directory', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const tempDir = createTempDir()
+  const target = join(tempDir, 'target')
+  mkdirSync(target)
+  writeFileSync(join(target, 'keep.txt'), 'keep')
+  symlinkSync(target, join(tempDir, 'link'))
+  symlinkSync(join(tempDir, 'missing'), join(tempDir, 'dangling'))
+
+  rmSync(join(tempDir, 'link'), { recursive: true })
+  t.throws(() => lstatSync(join(tempDir, 'link')))
+  t.true(existsSync(join(target, 'keep.txt')))
+
+  rmSync(join(tempDir, 'dangling'))
+  t.throws(() => lstatSync(join(tempDir, 'dangling')))
+})
+
+test('sync: dryRun should report what would be removed without removing it', (t) => {
+  const tempDir = createTempDir()
+  const testDir = join(tempDir, 'tree')
+  mkdirSync(join(testDir, 'sub'), { recursive: true })
+  writeFileSync(join(testDir, 'a.txt'), 'hello')
+  writeFileSync(join(testDir, 'sub', 'b.txt'), 'bb')
+
+  const plan = rmSync(testDir, { recursive: true, dryRun: true })
+  t.deepEqual(plan?.removed, [testDir, join(testDir, 'a.txt'), join(testDir, 'sub'), join(testDir, 'sub', 'b.txt')])
+  t.deepEqual(plan?.created, [])
+  t.deepEqual(plan?.totals, { removed: 4, created: 0, overwritten: 0, skipped: 0, bytes: 7 })
+  t.true(existsSync(join(testDir, 'sub', 'b.txt')))
+
+  t.throws(() => rmSync(testDir, { dryRun: true }), { message: /ENOTEMPTY/ })
+  t.throws(() => rmSync(join(tempDir, 'missing'), { dryRun: true }), { message: /ENOENT/ })
+  const empty = rmSync(join(tempDir, 'missing'), { force: true, dryRun: true })
+  t.is(empty?.totals.removed, 0)
+})
+
+test('sync: dryRun should resolve paths without a name like a real removal', (t) => {
+  const tempDir = createTempDir()
+  const testDir = join(tempDir, 'tree')
+  mkdirSync(join(testDir, 'sub'), { recursive: true })
+  writeFileSync(join(testDir, 'a.txt'), 'a')
+
+  const plan = rmSync(join(testDir, 'sub', '..'), { recursive: true, dryRun: true })
+  t.deepEqual(plan?.removed, [testDir, join(testDir, 'a.txt'), join(testDir, 'sub')])
+  t.true(existsSync(testDir))
+})
+
+test('async: dryRun should resolve to the plan', async (t) => {
+  const tempDir = createTempDir()
+  const testFile = join(tempDir, 'file.txt')
+  writeFileSync(testFile, 'data')
+
+  const plan = (await rm(testFile, { dryRun: true })) as DryRunPlan
+  t.deepEqual(plan.removed, [testFile])
+  t.true(existsSync(testFile))
+})
+
+// ===== onError =====
+
+// Permission bits do not stop root, so these tests need an unprivileged user.
+const canDeny = process.platform !== 'win32' && process.getuid?.() !== 0
+
+test("onError: 'collect' should remove what it can and report the rest", async (t) => {
+  if (!canDeny) return t.pass()
+  const tempDir = createTempDir()
+  const tree = join(tempDir, 'tree')
+  const locked = join(tree, 'locked')
+  mkdirSync(join(tree, 'open'), { recursive: true })
+  mkdirSync(locked)
+  writeFileSync(join(tree, 'open', 'a.txt'), 'a')
+  writeFileSync(join(tree, 'b.txt'), 'b')
+  writeFileSync(join(locked, 'c.txt'), 'c')
+  chmodSync(locked, 0o555)
+
+  try {
+    const error = t.throws(() => rmSync(tree, { recursive: true, onError: 'collect' }), {
+      instanceOf: AggregateError,
+    }) as AggregateError
+    t.is(error.message, `1 error occurred, rm '${tree}'`)
+    t.is(error.errors[0].code, 'EACCES')
+    t.is(error.errors[0].path, join(locked, 'c.txt'))
+    t.false(existsSync(join(tree, 'open')))
+    t.false(existsSync(join(tree, 'b.txt')))
+    t.true(existsSync(join(locked, 'c.txt')))
+
+    t.notThrows(() => rmSync(tree, { recursive: true, onError: 'skip', concurrency: 4 }))
+    t.true(existsSync(join(locked, 'c.txt')))
+    t.throws(() => rmSync(tree, { recursive: true }), { message: /^EACCES/ })
+    await t.throwsAsync(async () => await rm(tree, { recursive: true, onError: 'collect' }), {
+      instanceOf: AggregateError,
+    })
+  } finally {
+    chmodSync(locked, 0o755)
+    nodeRmSync(tempDir, { recursive: true })
+  }
+})
+
+test('onError: dryRun should skip and collect unreadable directories like a removal', (t) => {
+  if (!canDeny) return t.pass()
+  const tempDir = createTempDir()
+  const tree = join(tempDir, 'tree')
+  const locked = join(tree, 'locked')
+  mkdirSync(locked, { recursive: true })
+  writeFileSync(join(tree, 'a.txt'), 'a')
+  writeFileSync(join(locked, 'b.txt'), 'b')
+  chmodSync(locked, 0o000)
+
+  try {
+    t.throws(() => rmSync(tree, { recursive: true, dryRun: true }), { message: /^EACCES/ })
+    const plan = rmSync(tree, { recursive: true, dryRun: true, onError: 'skip' })
+    t.deepEqual(plan?.removed, [join(tree, 'a.txt')])
+    const error = t.throws(() => rmSync(tree, { recursive: true, dryRun: true, onError: 'collect' }), {
+      instanceOf: AggregateError,
+    }) as AggregateError
+    t.is(error.errors[0].path, locked)
+    t.true(existsSync(join(tree, 'a.txt')))
+  } finally {
+    chmodSync(locked, 0o755)
+    nodeRmSync(tempDir, { recursive: true })
+  }
+})
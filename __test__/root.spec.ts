@@ -0,0 +1,148 @@
+import test from 'ava'
+import { openRootSync, openRoot } from '../index.js'
+import { writeFileSync, mkdirSync, readFileSync, existsSync, symlinkSync, statSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-root-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+const unix = process.platform !== 'win32'
+
+test('openRootSync: should read, write and stat relative to the root', (t) => {
+  if (!unix) return t.pass()
+  const dir = tmpDir('basic')
+  const root = openRootSync(dir)
+  t.is(root.path, dir)
+
+  root.writeFileSync('a.txt', 'hello')
+  t.is(readFileSync(join(dir, 'a.txt'), 'utf8'), 'hello')
+  t.is(root.readFileSync('a.txt', 'utf8'), 'hello')
+  root.writeFileSync('a.txt', ' world', { flag: 'a' })
+  t.deepEqual(root.readFileSync('a.txt'), Buffer.from('hello world'))
+  t.throws(() => root.writeFileSync('a.txt', 'x', { flag: 'wx' }), { message: /^EEXIST/ })
+
+  const stats = root.statSync('a.txt')
+  t.true(stats.isFile())
+  t.is(stats.size, 11)
+  t.true(root.statSync('.').isDirectory())
+  t.throws(() => root.statSync('missing'), { message: "ENOENT: no such file or directory, stat 'missing'" })
+})
+
+test('openRootSync: mkdir, readdir and rm should work inside the root', (t) => {
+  if (!unix) return t.pass()
+  const dir = tmpDir('tree')
+  const root = openRootSync(dir)
+
+  t.is(root.mkdirSync('a/b/c', { recursive: true }), 'a')
+  t.falsy(root.mkdirSync('a/b', { recursive: true }))
+  root.mkdirSync('a/d')
+  t.throws(() => root.mkdirSync('a/d'), { message: /^EEXIST/ })
+  root.writeFileSync('a/b/c/f.txt', 'x')
+  root.writeFileSync('a/.hidden', 'x')
+
+  t.deepEqual((root.readdirSync('a') as string[]).sort(), ['.hidden', 'b', 'd'])
+  t.deepEqual((root.readdirSync('a', { skipHidden: true }) as string[]).sort(), ['b', 'd'])
+  t.deepEqual((root.readdirSync('a', { recursive: true }) as string[]).sort(), [
+    '.hidden',
+    'b',
+    'b/c',
+    'b/c/f.txt',
+    'd',
+  ])
+  const dirents = root.readdirSync('a/b', { withFileTypes: true, recursive: true }) as unknown[]
+  t.is(dirents.length, 2)
+
+  t.throws(() => root.rmSync('a/b'), { message: /^ENOTEMPTY/ })
+  root.rmSync('a/b', { recursive: true })
+  t.false(existsSync(join(dir, 'a', 'b')))
+  t.throws(() => root.rmSync('a/b'), { message: /^ENOENT/ })
+  root.rmSync('a/b', { force: true })
+})
+
+test('openRootSync: should refuse paths escaping the root', (t) => {
+  if (!unix) return t.pass()
+  const dir = tmpDir('escape')
+  writeFileSync(join(dir, 'secret.txt'), 'secret')
+  const inside = join(dir, 'inside')
+  mkdirSync(inside)
+  const root = openRootSync(inside)
+
+  t.throws(() => root.readFileSync('../secret.txt'), {
+    message: "EXDEV: path resolves outside of the root, open '../secret.txt'",
+  })
+  t.throws(() => root.readFileSync(join(dir, 'secret.txt')), { message: /^EXDEV/ })
+  t.throws(() => root.writeFileSync('../pwned.txt', 'x'), { message: /^EXDEV/ })
+  t.throws(() => root.mkdirSync('x/../../pwned', { recursive: true }), { message: /^EXDEV/ })
+  t.false(existsSync(join(dir, 'pwned.txt')))
+  t.false(existsSync(join(dir, 'pwned')))
+})
+
+test('openRootSync: should refuse symlinks pointing outside of the root', (t) => {
+  if (!unix) return t.pass()
+  const dir = tmpDir('symlinks')
+  writeFileSync(join(dir, 'secret.txt'), 'secret')
+  const inside = join(dir, 'inside')
+  mkdirSync(join(inside, 'sub'), { recursive: true })
+  symlinkSync(join(dir, 'secret.txt'), join(inside, 'absolute'))
+  symlinkSync('../secret.txt', join(inside, 'relative'))
+  symlinkSync('..', join(inside, 'up'))
+  const root = openRootSync(inside)
+
+  t.throws(() => root.readFileSync('absolute'), { message: /^EXDEV/ })
+  t.throws(() => root.readFileSync('relative'), { message: /^EXDEV/ })
+  t.throws(() => root.readFileSync('up/secret.txt'), { message: /^EXDEV/ })
+  t.throws(() => root.statSync('up'), { message: /^EXDEV/ })
+  t.throws(() => root.writeFileSync('up/pwned.txt', 'x'), { message: /^EXDEV/ })
+  t.false(existsSync(join(dir, 'pwned.txt')))
+})
+
+test('openRootSync: should follow symlinks that stay inside the root', (t) => {
+  if (!unix) return t.pass()
+  const dir = tmpDir('inner-links')
+  mkdirSync(join(dir, 'sub'))
+  writeFileSync(join(dir, 'sub', 'f.txt'), 'inside')
+  symlinkSync('sub/f.txt', join(dir, 'link'))
+  symlinkSync('../sub', join(dir, 'sub', 'self'))
+  const root = openRootSync(dir)
+
+  t.is(root.readFileSync('link', 'utf8'), 'inside')
+  t.is(root.readFileSync('sub/self/f.txt', 'utf8'), 'inside')
+  t.is(root.readFileSync('sub/../link', 'utf8'), 'inside')
+  t.true(root.statSync('sub/self').isDirectory())
+})
+
+test('openRootSync: rm recursive should remove symlinks, not their targets', (t) => {
+  if (!unix) return t.pass()
+  const dir = tmpDir('rm-links')
+  const outside = join(dir, 'outside')
+  mkdirSync(outside)
+  writeFileSync(join(outside, 'keep.txt'), 'keep')
+  mkdirSync(join(dir, 'inside', 'tree'), { recursive: true })
+  symlinkSync(outside, join(dir, 'inside', 'tree', 'link'))
+  const root = openRootSync(join(dir, 'inside'))
+
+  root.rmSync('tree', { recursive: true })
+  t.false(existsSync(join(dir, 'inside', 'tree')))
+  t.is(readFileSync(join(outside, 'keep.txt'), 'utf8'), 'keep')
+  t.throws(() => root.rmSync('../outside', { recursive: true }), { message: /^EXDEV/ })
+  t.true(statSync(outside).isDirectory())
+})
+
+test('openRoot: async methods should work', async (t) => {
+  if (!unix) return t.pass()
+  const dir = tmpDir('async')
+  const root = (await openRoot(dir)) as ReturnType<typeof openRootSync>
+  await root.mkdir('sub', { recursive: true })
+  await root.writeFile('sub/a.txt', Buffer.from('data'))
+  t.is(await root.readFile('sub/a.txt', 'utf8'), 'data')
+  t.is(((await root.stat('sub/a.txt')) as { size: number }).size, 4)
+  t.deepEqual(await root.readdir('sub'), ['a.txt'])
+  await root.rm('sub', { recursive: true })
+  t.false(existsSync(join(dir, 'sub')))
+  await t.throwsAsync(async () => await root.readFile('../x'), { message: /^EXDEV/ })
+  await t.throwsAsync(async () => await openRoot(join(dir, 'missing')), { message: /^ENOENT/ })
+})
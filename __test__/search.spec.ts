@@ -0,0 +1,145 @@
+import test from 'ava'
+import { searchSync, search, searchStream } from '../index.js'
+import { writeFileSync, mkdirSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-search-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+function makeTree(dir: string) {
+  mkdirSync(join(dir, 'src'), { recursive: true })
+  writeFileSync(join(dir, 'notes.txt'), 'one\nTODO two\r\nthree todo\nfour')
+  writeFileSync(join(dir, 'src', 'main.rs'), 'fn main() { // TODO: é TODO\n}\n')
+  writeFileSync(join(dir, 'data.bin'), Buffer.from([0x54, 0x4f, 0x44, 0x4f, 0x00, 0x01]))
+}
+
+test('searchSync: should return literal matches with line and column', (t) => {
+  const dir = tmpDir('literal')
+  makeTree(dir)
+  t.deepEqual(searchSync('TODO', { cwd: dir }), [
+    { path: 'notes.txt', line: 2, column: 1, text: 'TODO two' },
+    { path: join('src', 'main.rs'), line: 1, column: 16, text: 'fn main() { // TODO: é TODO' },
+    { path: join('src', 'main.rs'), line: 1, column: 24, text: 'fn main() { // TODO: é TODO' },
+  ])
+})
+
+test('searchSync: column should index into text in UTF-16 units', (t) => {
+  const dir = tmpDir('column')
+  makeTree(dir)
+  for (const m of searchSync('TODO', { cwd: dir })) {
+    t.true(m.text.slice(m.column - 1).startsWith('TODO'))
+  }
+})
+
+test('searchSync: should skip binary files', (t) => {
+  const dir = tmpDir('binary')
+  makeTree(dir)
+  t.false(searchSync('TODO', { cwd: dir }).some((m) => m.path === 'data.bin'))
+})
+
+test('searchSync: literal mode should escape regex metacharacters', (t) => {
+  const dir = tmpDir('escape')
+  writeFileSync(join(dir, 'f.txt'), 'a.b\naxb\n')
+  t.deepEqual(
+    searchSync('a.b', { cwd: dir }).map((m) => m.line),
+    [1],
+  )
+  t.deepEqual(
+    searchSync('a.b', { cwd: dir, regex: true }).map((m) => m.line),
+    [1, 2],
+  )
+})
+
+test('searchSync: anchored regexes should match every line, not only the file edges', (t) => {
+  const dir = tmpDir('anchors')
+  writeFileSync(join(dir, 'f.txt'), 'hello\nimport x\nend foo\n')
+  writeFileSync(join(dir, 'crlf.txt'), 'a foo\r\nimport y\r\n')
+  const lines = (pattern: string) =>
+    searchSync(pattern, { cwd: dir, regex: true })
+      .map((m) => `${m.path}:${m.line}`)
+      .sort()
+  t.deepEqual(lines('^import'), ['crlf.txt:2', 'f.txt:2'])
+  t.deepEqual(lines('foo$'), ['crlf.txt:1', 'f.txt:3'])
+  t.deepEqual(lines('^end foo$'), ['f.txt:3'])
+  t.deepEqual(lines('\\Aimport'), ['crlf.txt:2', 'f.txt:2'])
+})
+
+test('searchSync: caseInsensitive, include and context', (t) => {
+  const dir = tmpDir('context')
+  makeTree(dir)
+  t.deepEqual(searchSync('todo', { cwd: dir, caseInsensitive: true, include: ['*.txt'], context: 1 }), [
+    { path: 'notes.txt', line: 2, column: 1, text: 'TODO two', before: ['one'], after: ['three todo'] },
+    { path: 'notes.txt', line: 3, column: 7, text: 'three todo', before: ['TODO two'], after: ['four'] },
+  ])
+})
+
+test('searchSync: exclude should skip matching files', (t) => {
+  const dir = tmpDir('exclude')
+  makeTree(dir)
+  t.deepEqual(
+    searchSync('TODO', { cwd: dir, exclude: ['src/**'] }).map((m) => m.path),
+    ['notes.txt'],
+  )
+})
+
+test('searchSync: maxMatches should cap the number of results', (t) => {
+  const dir = tmpDir('max')
+  makeTree(dir)
+  t.is(searchSync('TODO', { cwd: dir, maxMatches: 2 }).length, 2)
+})
+
+test('searchSync: maxMatches should keep the first matches in path order', (t) => {
+  const dir = tmpDir('max-order')
+  for (let i = 0; i < 20; i++) {
+    writeFileSync(join(dir, `f${String(i).padStart(2, '0')}.txt`), 'foo\nfoo\n')
+  }
+  for (let run = 0; run < 5; run++) {
+    t.deepEqual(
+      searchSync('foo', { cwd: dir, maxMatches: 3, concurrency: 8 }).map((m) => `${m.path}:${m.line}`),
+      ['f00.txt:1', 'f00.txt:2', 'f01.txt:1'],
+    )
+  }
+})
+
+test('searchSync: should reject an invalid regex', (t) => {
+  const dir = tmpDir('invalid')
+  t.throws(() => searchSync('(', { cwd: dir, regex: true }), { message: /ERR_INVALID_ARG_VALUE/ })
+})
+
+test('searchSync: should throw ENOENT on missing cwd', (t) => {
+  t.throws(() => searchSync('x', { cwd: join(tmpDir('missing'), 'nope') }), { message: /ENOENT/ })
+})
+
+test('search: async should match sync', async (t) => {
+  const dir = tmpDir('async')
+  makeTree(dir)
+  t.deepEqual(await search('TODO', { cwd: dir }), searchSync('TODO', { cwd: dir }))
+})
+
+test('searchStream: should yield per-file batches', async (t) => {
+  const dir = tmpDir('stream')
+  makeTree(dir)
+  const all = []
+  for await (const batch of searchStream('TODO', { cwd: dir })) {
+    t.true(batch.length > 0)
+    t.true(batch.every((m) => m.path === batch[0].path))
+    all.push(...batch)
+  }
+  t.is(all.length, 3)
+})
+
+test('searchStream: breaking out should stop the search', async (t) => {
+  const dir = tmpDir('stream-break')
+  for (let i = 0; i < 50; i++) {
+    writeFileSync(join(dir, `f${i}.txt`), 'needle\n')
+  }
+  let batches = 0
+  for await (const _ of searchStream('needle', { cwd: dir, concurrency: 1 })) {
+    if (++batches === 2) break
+  }
+  t.is(batches, 2)
+})
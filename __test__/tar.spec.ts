@@ -0,0 +1,245 @@
+import test from 'ava'
+import { tarCreateSync, tarCreate, tarExtractSync, tarExtract } from '../index.js'
+import { writeFileSync, mkdirSync, readFileSync, existsSync, symlinkSync, readlinkSync, statSync } from 'node:fs'
+import { gunzipSync, gzipSync } from 'node:zlib'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-tar-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+function makeTree(dir: string) {
+  mkdirSync(join(dir, 'src', 'lib'), { recursive: true })
+  mkdirSync(join(dir, 'node_modules'), { recursive: true })
+  writeFileSync(join(dir, 'package.json'), '{"name":"x"}')
+  writeFileSync(join(dir, 'src', 'index.ts'), 'export {}')
+  writeFileSync(join(dir, 'src', 'lib', 'util.ts'), 'export const a = 1')
+  writeFileSync(join(dir, 'node_modules', 'dep.js'), 'module.exports = 1')
+}
+
+interface RawEntry {
+  name: string
+  type?: string
+  data?: string
+  link?: string
+}
+
+/** Minimal ustar writer, used to craft archives tarCreate would never produce. */
+function rawTar(entries: RawEntry[]): Buffer {
+  const blocks: Buffer[] = []
+  for (const entry of entries) {
+    const data = Buffer.from(entry.data ?? '')
+    const header = Buffer.alloc(512)
+    header.write(entry.name, 0)
+    header.write('0000644\0', 100)
+    header.write('0000000\0', 108)
+    header.write('0000000\0', 116)
+    header.write(data.length.toString(8).padStart(11, '0') + '\0', 124)
+    header.write('00000000000\0', 136)
+    header.write('        ', 148)
+    header.write(entry.type ?? '0', 156)
+    header.write(entry.link ?? '', 157)
+    header.write('ustar\0' + '00', 257)
+    let sum = 0
+    for (const byte of header) sum += byte
+    header.write(sum.toString(8).padStart(6, '0') + '\0 ', 148)
+    blocks.push(header, data, Buffer.alloc((512 - (data.length % 512)) % 512))
+  }
+  blocks.push(Buffer.alloc(1024))
+  return Buffer.concat(blocks)
+}
+
+test('tarCreateSync/tarExtractSync: should round-trip a tree', (t) => {
+  const src = tmpDir('src')
+  makeTree(src)
+  const archive = join(tmpDir('out'), 'a.tar')
+  const created = tarCreateSync(src, archive)
+  t.deepEqual(created, { files: 4, directories: 3, symlinks: 0, bytes: 57 })
+
+  const dest = tmpDir('dest')
+  const extracted = tarExtractSync(archive, dest)
+  t.deepEqual(extracted, created)
+  t.is(readFileSync(join(dest, 'src', 'lib', 'util.ts'), 'utf8'), 'export const a = 1')
+  t.is(readFileSync(join(dest, 'package.json'), 'utf8'), '{"name":"x"}')
+})
+
+test('tarCreateSync: output should be reproducible and gzip should be standard', (t) => {
+  const src = tmpDir('repro')
+  makeTree(src)
+  const out = tmpDir('repro-out')
+  tarCreateSync(src, join(out, 'a.tar'))
+  tarCreateSync(src, join(out, 'b.tar'))
+  t.deepEqual(readFileSync(join(out, 'a.tar')), readFileSync(join(out, 'b.tar')))
+
+  tarCreateSync(src, join(out, 'a.tar.gz'), { gzip: true })
+  t.deepEqual(gunzipSync(readFileSync(join(out, 'a.tar.gz'))), readFileSync(join(out, 'a.tar')))
+})
+
+test('tarCreateSync: should honour exclude and skip the archive itself', (t) => {
+  const src = tmpDir('exclude')
+  makeTree(src)
+  const summary = tarCreateSync(src, join(src, 'self.tar'), { exclude: ['node_modules'] })
+  t.is(summary.files, 3)
+
+  const dest = tmpDir('exclude-dest')
+  tarExtractSync(join(src, 'self.tar'), dest)
+  t.false(existsSync(join(dest, 'node_modules')))
+  t.false(existsSync(join(dest, 'self.tar')))
+})
+
+test('tarCreateSync: preserve.mode and preserve.timestamps should keep metadata', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const src = tmpDir('preserve')
+  writeFileSync(join(src, 'run.sh'), '#!/bin/sh', { mode: 0o700 })
+  const archive = join(tmpDir('preserve-out'), 'a.tar')
+  tarCreateSync(src, archive, { preserve: { mode: true, timestamps: true } })
+  const dest = tmpDir('preserve-dest')
+  tarExtractSync(archive, dest)
+  const stat = statSync(join(dest, 'run.sh'))
+  t.is(stat.mode & 0o777, 0o700)
+  t.is(Math.floor(stat.mtimeMs / 1000), Math.floor(statSync(join(src, 'run.sh')).mtimeMs / 1000))
+})
+
+test('tarExtractSync: should apply strip and filter', (t) => {
+  const src = tmpDir('strip')
+  makeTree(src)
+  const archive = join(tmpDir('strip-out'), 'a.tar')
+  tarCreateSync(src, archive)
+
+  const dest = tmpDir('strip-dest')
+  tarExtractSync(archive, dest, { strip: 1, filter: ['**/*.ts'] })
+  t.is(readFileSync(join(dest, 'lib', 'util.ts'), 'utf8'), 'export const a = 1')
+  t.true(existsSync(join(dest, 'index.ts')))
+  t.false(existsSync(join(dest, 'dep.js')))
+  t.false(existsSync(join(dest, 'package.json')))
+})
+
+test('tarExtractSync: should extract gzip archives and keep symlinks', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const src = tmpDir('links')
+  writeFileSync(join(src, 'target.txt'), 'hi')
+  symlinkSync('target.txt', join(src, 'link'))
+  const archive = join(tmpDir('links-out'), 'a.tgz')
+  t.is(tarCreateSync(src, archive, { gzip: true }).symlinks, 1)
+
+  const dest = tmpDir('links-dest')
+  tarExtractSync(archive, dest, { concurrency: 1 })
+  t.is(readlinkSync(join(dest, 'link')), 'target.txt')
+  t.is(readFileSync(join(dest, 'link'), 'utf8'), 'hi')
+})
+
+test('tarExtractSync: should reject .. entries', (t) => {
+  const dir = tmpDir('dotdot')
+  const archive = join(dir, 'evil.tar')
+  writeFileSync(archive, rawTar([{ name: '../escaped.txt', data: 'x' }]))
+  t.throws(() => tarExtractSync(archive, join(dir, 'dest')), { message: /ERR_UNSAFE_ARCHIVE_PATH/ })
+  t.false(existsSync(join(dir, 'escaped.txt')))
+})
+
+test('tarExtractSync: should extract absolute entries inside destDir', (t) => {
+  const dir = tmpDir('absolute')
+  const archive = join(dir, 'abs.tar.gz')
+  writeFileSync(archive, gzipSync(rawTar([{ name: '/abs/file.txt', data: 'x' }])))
+  tarExtractSync(archive, join(dir, 'dest'))
+  t.is(readFileSync(join(dir, 'dest', 'abs', 'file.txt'), 'utf8'), 'x')
+})
+
+test('tarExtractSync: should reject symlinks escaping destDir', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('symlink-escape')
+  const archive = join(dir, 'evil.tar')
+  writeFileSync(archive, rawTar([{ name: 'link', type: '2', link: '../..' }]))
+  t.throws(() => tarExtractSync(archive, join(dir, 'dest')), { message: /ERR_UNSAFE_ARCHIVE_PATH/ })
+  t.false(existsSync(join(dir, 'dest', 'link')))
+})
+
+test('tarExtractSync: should reject dangling chained symlinks escaping destDir', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('symlink-chain')
+  const archive = join(dir, 'evil.tar')
+  // Each target stays inside on its own; together `b` points at `dir/outside`, which does not exist yet.
+  writeFileSync(
+    archive,
+    rawTar([
+      { name: 'a', type: '2', link: '.' },
+      { name: 'b', type: '2', link: 'a/a/../../outside' },
+    ]),
+  )
+  t.throws(() => tarExtractSync(archive, join(dir, 'dest')), { message: /ERR_UNSAFE_ARCHIVE_PATH/ })
+  t.throws(() => readlinkSync(join(dir, 'dest', 'b')))
+})
+
+test('tarExtractSync: should not write through a symlink in the archive', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('symlink-write')
+  mkdirSync(join(dir, 'dest', 'inner'), { recursive: true })
+  const archive = join(dir, 'evil.tar')
+  // `dir -> inner` is allowed on its own, but `dir/f.txt` must land in a real directory.
+  writeFileSync(
+    archive,
+    rawTar([
+      { name: 'dir', type: '2', link: 'inner' },
+      { name: 'dir/f.txt', data: 'x' },
+    ]),
+  )
+  t.throws(() => tarExtractSync(archive, join(dir, 'dest')))
+  t.false(existsSync(join(dir, 'dest', 'inner', 'f.txt')))
+})
+
+test('tarExtractSync: should apply hard links in archive order', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('hardlink-order')
+  const archive = join(dir, 'links.tar')
+  writeFileSync(
+    archive,
+    rawTar([
+      { name: 'a', data: 'one' },
+      { name: 'b', type: '1', link: 'a' },
+      { name: 'a', data: 'two' },
+      { name: 'c', data: 'old' },
+      { name: 'c', type: '1', link: 'a' },
+      { name: 'sub/d', type: '1', link: 'b' },
+    ]),
+  )
+  for (const concurrency of [1, 8]) {
+    const dest = join(dir, `dest-${concurrency}`)
+    tarExtractSync(archive, dest, { concurrency })
+    t.deepEqual(
+      ['a', 'b', 'c', 'sub/d'].map((f) => readFileSync(join(dest, f), 'utf8')),
+      ['two', 'one', 'two', 'one'],
+    )
+  }
+})
+
+test('tarExtractSync: should refuse to follow symlinks already in destDir', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('existing-link')
+  const outside = join(dir, 'outside')
+  mkdirSync(outside)
+  mkdirSync(join(dir, 'dest'))
+  symlinkSync(outside, join(dir, 'dest', 'out'))
+  const archive = join(dir, 'evil.tar')
+  writeFileSync(archive, rawTar([{ name: 'out/pwned.txt', data: 'x' }]))
+  t.throws(() => tarExtractSync(archive, join(dir, 'dest')), { message: /ERR_UNSAFE_ARCHIVE_PATH/ })
+  t.false(existsSync(join(outside, 'pwned.txt')))
+})
+
+test('tarCreate/tarExtract: async should round-trip', async (t) => {
+  const src = tmpDir('async')
+  makeTree(src)
+  const archive = join(tmpDir('async-out'), 'a.tar.gz')
+  await tarCreate(src, archive, { gzip: true, exclude: ['node_modules'] })
+  const dest = tmpDir('async-dest')
+  const summary = (await tarExtract(archive, dest, { concurrency: 8 })) as { files: number }
+  t.is(summary.files, 3)
+  t.is(readFileSync(join(dest, 'src', 'index.ts'), 'utf8'), 'export {}')
+})
+
+test('tarExtract: should reject a missing archive', async (t) => {
+  await t.throwsAsync(async () => await tarExtract('/no/such/archive.tar', tmpDir('missing')), {
+    message: /ENOENT/,
+  })
+})
@@ -0,0 +1,79 @@
+import test from 'ava'
+import { tmpdirSync, tmpdir, TempDir } from '../index.js'
+import { existsSync, writeFileSync, mkdirSync, rmSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir as osTmpdir } from 'node:os'
+import { execFileSync } from 'node:child_process'
+import { fileURLToPath } from 'node:url'
+
+test('tmpdirSync: should create a directory under the OS temp directory', (t) => {
+  const dir = tmpdirSync()
+  t.true(dir instanceof TempDir)
+  t.true(dir.path.startsWith(join(osTmpdir(), 'rush-fs-')))
+  t.false(dir.keep)
+  t.true(existsSync(dir.path))
+  dir.removeSync()
+  t.false(existsSync(dir.path))
+  // Removing twice is fine.
+  dir.removeSync()
+})
+
+test('tmpdirSync: should honour prefix and remove whole trees', (t) => {
+  const dir = tmpdirSync({ prefix: 'rush-fs-test-tmpdir-' })
+  t.true(dir.path.startsWith(join(osTmpdir(), 'rush-fs-test-tmpdir-')))
+  mkdirSync(join(dir.path, 'a', 'b'), { recursive: true })
+  writeFileSync(join(dir.path, 'a', 'b', 'c.txt'), 'c')
+  dir.disposeSync()
+  t.false(existsSync(dir.path))
+})
+
+test('tmpdirSync: keep should make disposal a no-op but not remove()', async (t) => {
+  const dir = tmpdirSync({ keep: true })
+  t.true(dir.keep)
+  dir.disposeSync()
+  await dir.dispose()
+  t.true(existsSync(dir.path))
+  await dir.remove()
+  t.false(existsSync(dir.path))
+})
+
+test('tmpdir: Symbol.asyncDispose / Symbol.dispose should dispose the handle', async (t) => {
+  const { asyncDispose, dispose } = Symbol as unknown as { asyncDispose?: symbol; dispose?: symbol }
+  if (!asyncDispose || !dispose) return t.pass()
+  const dir = (await tmpdir()) as TempDir
+  t.true(dir instanceof TempDir)
+  writeFileSync(join(dir.path, 'a.txt'), 'a')
+  await Reflect.get(dir, asyncDispose).call(dir)
+  t.false(existsSync(dir.path))
+
+  const other = tmpdirSync()
+  Reflect.get(other, dispose).call(other)
+  t.false(existsSync(other.path))
+})
+
+test('removeOnExit: should remove leftover temp paths when the process exits', (t) => {
+  const index = fileURLToPath(new URL('../index.js', import.meta.url))
+  const script = `
+    const { tmpdirSync, mkstempSync } = require(${JSON.stringify(index)})
+    const { closeSync, writeFileSync } = require('node:fs')
+    const dir = tmpdirSync({ removeOnExit: true })
+    writeFileSync(dir.path + '/a.txt', 'a')
+    const kept = tmpdirSync()
+    const file = mkstempSync(dir.path + '-file-', { removeOnExit: true })
+    closeSync(file.fd)
+    console.log(JSON.stringify([dir.path, file.path, kept.path]))
+    process.exit(0)
+  `
+  const [dir, file, kept] = JSON.parse(execFileSync(process.execPath, ['-e', script], { encoding: 'utf8' }))
+  t.false(existsSync(dir))
+  t.false(existsSync(file))
+  t.true(existsSync(kept))
+  rmSync(kept, { recursive: true })
+})
+
+test('tmpdir: should throw ENOENT for a prefix in a missing directory', async (t) => {
+  t.throws(() => tmpdirSync({ prefix: join('no-such-parent-dir-999', 'x-') }), { message: /^ENOENT/ })
+  await t.throwsAsync(async () => await tmpdir({ prefix: join('no-such-parent-dir-999', 'x-') }), {
+    message: /^ENOENT/,
+  })
+})
@@ -0,0 +1,183 @@
+import test from 'ava'
+import { trashSync, trash, listTrashSync, listTrash, restoreFromTrashSync, restoreFromTrash } from '../index.js'
+import { writeFileSync, mkdirSync, readFileSync, readdirSync, existsSync, symlinkSync, statSync, rmSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-trash-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+const supported = process.platform !== 'win32' && process.platform !== 'darwin'
+
+// Every test points the home trash at its own directory.
+function withHomeTrash(name: string): { dir: string; trashDir: string } {
+  const dir = tmpDir(name)
+  process.env.XDG_DATA_HOME = join(dir, 'data')
+  return { dir, trashDir: join(dir, 'data', 'Trash') }
+}
+
+test.serial('trashSync: should move entries into the home trash with .trashinfo metadata', (t) => {
+  if (!supported) return t.pass()
+  const { dir, trashDir } = withHomeTrash('basic')
+  writeFileSync(join(dir, 'a.txt'), 'hello')
+  mkdirSync(join(dir, 'sub', 'deep'), { recursive: true })
+  writeFileSync(join(dir, 'sub', 'deep', 'b.txt'), 'b')
+
+  const before = Date.now()
+  const items = trashSync([join(dir, 'a.txt'), join(dir, 'sub')])
+  t.is(items.length, 2)
+  t.false(existsSync(join(dir, 'a.txt')))
+  t.false(existsSync(join(dir, 'sub')))
+
+  const [file, folder] = items
+  t.is(file.name, 'a.txt')
+  t.is(file.path, join(trashDir, 'files', 'a.txt'))
+  t.is(file.originalPath, join(dir, 'a.txt'))
+  t.is(file.trashDir, trashDir)
+  t.true(file.deletionDateMs >= Math.floor(before / 1000) * 1000 - 1000)
+  t.true(file.deletionDate instanceof Date)
+  t.is(readFileSync(file.path, 'utf8'), 'hello')
+  t.is(readFileSync(join(folder.path, 'deep', 'b.txt'), 'utf8'), 'b')
+
+  const info = readFileSync(join(trashDir, 'info', 'a.txt.trashinfo'), 'utf8')
+  t.regex(info, /^\[Trash Info\]\nPath=\/.*\/a\.txt\nDeletionDate=\d{4}-\d\d-\d\dT\d\d:\d\d:\d\d\n$/)
+  t.is(statSync(join(trashDir, 'files')).mode & 0o777, 0o700)
+})
+
+test.serial('trashSync: should pick unique names and percent-encode the original path', (t) => {
+  if (!supported) return t.pass()
+  const { dir, trashDir } = withHomeTrash('names')
+  const names = []
+  for (let i = 0; i < 3; i++) {
+    writeFileSync(join(dir, 'my file.txt'), String(i))
+    names.push(trashSync([join(dir, 'my file.txt')])[0].name)
+  }
+  t.deepEqual(names, ['my file.txt', 'my file.2.txt', 'my file.3.txt'])
+  t.is(readFileSync(join(trashDir, 'files', 'my file.3.txt'), 'utf8'), '2')
+  t.regex(readFileSync(join(trashDir, 'info', 'my file.txt.trashinfo'), 'utf8'), /Path=\/.*\/my%20file\.txt\n/)
+})
+
+test.serial('trashSync: should trash symlinks themselves and report missing paths', (t) => {
+  if (!supported) return t.pass()
+  const { dir } = withHomeTrash('links')
+  writeFileSync(join(dir, 'target.txt'), 'keep')
+  symlinkSync(join(dir, 'target.txt'), join(dir, 'link'))
+
+  const [item] = trashSync([join(dir, 'link')])
+  t.false(existsSync(join(dir, 'link')))
+  t.is(readFileSync(join(dir, 'target.txt'), 'utf8'), 'keep')
+  t.is(item.originalPath, join(dir, 'link'))
+  t.throws(() => trashSync([join(dir, 'missing')]), {
+    message: `ENOENT: no such file or directory, trash '${join(dir, 'missing')}'`,
+  })
+})
+
+test.serial('listTrashSync / restoreFromTrashSync: should list and restore trashed entries', (t) => {
+  if (!supported) return t.pass()
+  const { dir } = withHomeTrash('restore')
+  mkdirSync(join(dir, 'nested'))
+  writeFileSync(join(dir, 'nested', 'c.txt'), 'c')
+  const [item] = trashSync([join(dir, 'nested', 'c.txt')])
+  rmSync(join(dir, 'nested'), { recursive: true })
+
+  const listed = listTrashSync().filter((entry) => entry.originalPath.startsWith(dir))
+  t.is(listed.length, 1)
+  t.is(listed[0].path, item.path)
+  t.is(listed[0].deletionDateMs, item.deletionDateMs)
+
+  t.is(restoreFromTrashSync(item.path), join(dir, 'nested', 'c.txt'))
+  t.is(readFileSync(join(dir, 'nested', 'c.txt'), 'utf8'), 'c')
+  t.false(existsSync(item.path))
+  t.is(listTrashSync().filter((entry) => entry.originalPath.startsWith(dir)).length, 0)
+  t.throws(() => restoreFromTrashSync(item.path), { message: /^ENOENT/ })
+  t.throws(() => restoreFromTrashSync(join(dir, 'nested', 'c.txt')), { message: /^EINVAL/ })
+})
+
+test.serial('listTrashSync: should keep malformed percent escapes in .trashinfo as they are', (t) => {
+  if (!supported) return t.pass()
+  const { dir, trashDir } = withHomeTrash('malformed')
+  writeFileSync(join(dir, 'f.txt'), 'f')
+  trashSync([join(dir, 'f.txt')])
+  // Written by some other program: `%` followed by a non-ASCII char and by non-hex digits.
+  for (const [name, path] of [
+    ['bad1', `${dir}/x%aé`],
+    ['bad2', `${dir}/y%zz%4`],
+  ]) {
+    writeFileSync(join(trashDir, 'files', name), '')
+    writeFileSync(
+      join(trashDir, 'info', `${name}.trashinfo`),
+      `[Trash Info]\nPath=${path}\nDeletionDate=2026-01-01T00:00:00\n`,
+    )
+  }
+
+  const listed = listTrashSync()
+    .filter((entry) => entry.originalPath.startsWith(dir))
+    .map((entry) => entry.originalPath)
+    .sort()
+  t.deepEqual(listed, [join(dir, 'f.txt'), `${dir}/x%aé`, `${dir}/y%zz%4`])
+})
+
+test.serial('restoreFromTrashSync: should refuse to overwrite unless asked', (t) => {
+  if (!supported) return t.pass()
+  const { dir } = withHomeTrash('overwrite')
+  writeFileSync(join(dir, 'd.txt'), 'old')
+  const [item] = trashSync([join(dir, 'd.txt')])
+  writeFileSync(join(dir, 'd.txt'), 'new')
+
+  t.throws(() => restoreFromTrashSync(item.path), {
+    message: `EEXIST: file already exists, restoreFromTrash '${join(dir, 'd.txt')}'`,
+  })
+  t.true(existsSync(item.path))
+  restoreFromTrashSync(item.path, { overwrite: true })
+  t.is(readFileSync(join(dir, 'd.txt'), 'utf8'), 'old')
+
+  // A directory in the way is replaced as a whole, and nothing is left next to it.
+  const [again] = trashSync([join(dir, 'd.txt')])
+  mkdirSync(join(dir, 'd.txt', 'sub'), { recursive: true })
+  restoreFromTrashSync(again.path, { overwrite: true })
+  t.is(readFileSync(join(dir, 'd.txt'), 'utf8'), 'old')
+  t.deepEqual(readdirSync(dir).sort(), ['d.txt', 'data'])
+})
+
+test.serial('trashSync: should use the per-volume trash for other filesystems', (t) => {
+  if (!supported || !existsSync('/dev/shm')) return t.pass()
+  const { trashDir } = withHomeTrash('volume')
+  if (statSync('/dev/shm').dev === statSync(tmpdir()).dev) return t.pass()
+  const file = join('/dev/shm', `rush-fs-trash-${Date.now()}-${Math.random().toString(36).slice(2)}.txt`)
+  writeFileSync(file, 'shm')
+  let item
+  try {
+    item = trashSync([file])[0]
+  } catch {
+    // The volume trash may not be creatable here; the home trash fallback covers it.
+    return t.pass()
+  }
+  t.false(existsSync(file))
+  if (item.trashDir === trashDir) {
+    // Moved across devices into the home trash.
+    t.is(readFileSync(item.path, 'utf8'), 'shm')
+  } else {
+    t.true(item.trashDir.startsWith('/dev/shm/.Trash'))
+    t.regex(readFileSync(join(item.trashDir, 'info', `${item.name}.trashinfo`), 'utf8'), /\nPath=rush-fs-trash-/)
+    t.true(listTrashSync().some((entry) => entry.path === item.path && entry.originalPath === file))
+  }
+  restoreFromTrashSync(item.path)
+  t.is(readFileSync(file, 'utf8'), 'shm')
+  rmSync(file)
+})
+
+test.serial('trash / listTrash / restoreFromTrash: async variants should work', async (t) => {
+  if (!supported) return t.pass()
+  const { dir } = withHomeTrash('async')
+  writeFileSync(join(dir, 'e.txt'), 'e')
+  const [item] = (await trash([join(dir, 'e.txt')])) as ReturnType<typeof trashSync>
+  t.false(existsSync(join(dir, 'e.txt')))
+  const listed = (await listTrash()) as ReturnType<typeof listTrashSync>
+  t.true(listed.some((entry) => entry.path === item.path))
+  t.is(await restoreFromTrash(item.path), join(dir, 'e.txt'))
+  t.is(readFileSync(join(dir, 'e.txt'), 'utf8'), 'e')
+  await t.throwsAsync(async () => await trash([join(dir, 'missing')]), { message: /^ENOENT/ })
+})
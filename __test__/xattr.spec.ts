@@ -0,0 +1,148 @@
+import test from 'ava'
+import {
+  getxattrSync,
+  getxattr,
+  setxattrSync,
+  setxattr,
+  listxattrSync,
+  listxattr,
+  llistxattrSync,
+  removexattrSync,
+  removexattr,
+  cpSync,
+} from '../index.js'
+import { writeFileSync, mkdirSync, symlinkSync } from 'node:fs'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+const supported = process.platform === 'linux' || process.platform === 'darwin'
+
+function tmpFile(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-xattr-${Date.now()}-${Math.random().toString(36).slice(2)}`)
+  mkdirSync(dir, { recursive: true })
+  const file = join(dir, name)
+  writeFileSync(file, 'test')
+  return file
+}
+
+// tmpfs / some CI filesystems reject user.* attributes entirely.
+function xattrUsable(file: string): boolean {
+  try {
+    setxattrSync(file, 'user.probe', 'x')
+    removexattrSync(file, 'user.probe')
+    return true
+  } catch (e) {
+    return !/ENOTSUP/.test((e as Error).message)
+  }
+}
+
+test('setxattrSync/getxattrSync: should round-trip a value as Buffer', (t) => {
+  const file = tmpFile('roundtrip.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  setxattrSync(file, 'user.checksum', 'abc123')
+  const value = getxattrSync(file, 'user.checksum')
+  t.true(Buffer.isBuffer(value))
+  t.is(value.toString(), 'abc123')
+})
+
+test('listxattrSync: should list attribute names', (t) => {
+  const file = tmpFile('list.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  setxattrSync(file, 'user.a', Buffer.from([1, 2, 3]))
+  setxattrSync(file, 'user.b', 'b')
+  const names = listxattrSync(file)
+  t.true(names.includes('user.a'))
+  t.true(names.includes('user.b'))
+})
+
+test('getxattrSync: missing attribute should throw ENODATA', (t) => {
+  const file = tmpFile('missing.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  t.throws(() => getxattrSync(file, 'user.nope'), { message: /ENODATA/ })
+})
+
+test('setxattrSync: create flag should throw EEXIST on existing attribute', (t) => {
+  const file = tmpFile('create.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  setxattrSync(file, 'user.tag', 'one')
+  t.throws(() => setxattrSync(file, 'user.tag', 'two', { create: true }), { message: /EEXIST/ })
+  t.is(getxattrSync(file, 'user.tag').toString(), 'one')
+})
+
+test('setxattrSync: replace flag should throw ENODATA on missing attribute', (t) => {
+  const file = tmpFile('replace.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  t.throws(() => setxattrSync(file, 'user.tag', 'x', { replace: true }), { message: /ENODATA/ })
+})
+
+test('removexattrSync: should remove the attribute', (t) => {
+  const file = tmpFile('remove.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  setxattrSync(file, 'user.gone', 'x')
+  removexattrSync(file, 'user.gone')
+  t.false(listxattrSync(file).includes('user.gone'))
+})
+
+test('llistxattrSync: should not follow symlinks', (t) => {
+  const file = tmpFile('target.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  setxattrSync(file, 'user.target', 'x')
+  const link = file + '.lnk'
+  symlinkSync(file, link)
+  t.true(listxattrSync(link).includes('user.target'))
+  t.false(llistxattrSync(link).includes('user.target'))
+})
+
+test('getxattrSync: should throw ENOENT on non-existent file', (t) => {
+  if (!supported) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  t.throws(() => getxattrSync('/tmp/no-such-file-' + Date.now(), 'user.a'), { message: /ENOENT/ })
+})
+
+test('async: setxattr/getxattr/listxattr/removexattr should work', async (t) => {
+  const file = tmpFile('async.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  await setxattr(file, 'user.async', Buffer.from('v'))
+  t.is((await getxattr(file, 'user.async')).toString(), 'v')
+  t.true((await listxattr(file)).includes('user.async'))
+  await removexattr(file, 'user.async')
+  t.false((await listxattr(file)).includes('user.async'))
+})
+
+test('cpSync: preserve.xattrs should copy extended attributes', (t) => {
+  const file = tmpFile('cp-src.txt')
+  if (!supported || !xattrUsable(file)) {
+    t.pass('Skipping: extended attributes not supported here')
+    return
+  }
+  setxattrSync(file, 'user.checksum', 'deadbeef')
+  const dest = file + '.copy'
+  cpSync(file, dest, { preserve: { xattrs: true } })
+  t.is(getxattrSync(dest, 'user.checksum').toString(), 'deadbeef')
+})
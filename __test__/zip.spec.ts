@@ -0,0 +1,229 @@
+import test from 'ava'
+import { zipListSync, zipList, zipReadSync, zipRead, zipExtractSync, zipExtract } from '../index.js'
+import { writeFileSync, mkdirSync, readFileSync, existsSync, readlinkSync, statSync, symlinkSync } from 'node:fs'
+import { deflateRawSync } from 'node:zlib'
+import { join } from 'node:path'
+import { tmpdir } from 'node:os'
+
+function tmpDir(name: string): string {
+  const dir = join(tmpdir(), `rush-fs-test-zip-${Date.now()}-${Math.random().toString(36).slice(2)}-${name}`)
+  mkdirSync(dir, { recursive: true })
+  return dir
+}
+
+const CRC_TABLE = Array.from({ length: 256 }, (_, n) => {
+  let c = n
+  for (let k = 0; k < 8; k++) c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1
+  return c >>> 0
+})
+
+function crc32(data: Buffer): number {
+  let c = 0xffffffff
+  for (const byte of data) c = CRC_TABLE[(c ^ byte) & 0xff] ^ (c >>> 8)
+  return (c ^ 0xffffffff) >>> 0
+}
+
+interface RawEntry {
+  name: string
+  data?: string
+  mode?: number
+  deflate?: boolean
+}
+
+/** Minimal zip writer (stored / deflated, unix modes), to craft archives for the tests. */
+function rawZip(entries: RawEntry[]): Buffer {
+  const locals: Buffer[] = []
+  const centrals: Buffer[] = []
+  let offset = 0
+  for (const entry of entries) {
+    const name = Buffer.from(entry.name)
+    const data = Buffer.from(entry.data ?? '')
+    const body = entry.deflate ? deflateRawSync(data) : data
+    const crc = crc32(data)
+    const method = entry.deflate ? 8 : 0
+    // 2024-01-02 03:04:06 in DOS format.
+    const time = (3 << 11) | (4 << 5) | 3
+    const date = ((2024 - 1980) << 9) | (1 << 5) | 2
+
+    const local = Buffer.alloc(30)
+    local.writeUInt32LE(0x04034b50, 0)
+    local.writeUInt16LE(20, 4)
+    local.writeUInt16LE(method, 8)
+    local.writeUInt16LE(time, 10)
+    local.writeUInt16LE(date, 12)
+    local.writeUInt32LE(crc, 14)
+    local.writeUInt32LE(body.length, 18)
+    local.writeUInt32LE(data.length, 22)
+    local.writeUInt16LE(name.length, 26)
+    locals.push(local, name, body)
+
+    const central = Buffer.alloc(46)
+    central.writeUInt32LE(0x02014b50, 0)
+    central.writeUInt16LE((3 << 8) | 20, 4)
+    central.writeUInt16LE(20, 6)
+    central.writeUInt16LE(method, 10)
+    central.writeUInt16LE(time, 12)
+    central.writeUInt16LE(date, 14)
+    central.writeUInt32LE(crc, 16)
+    central.writeUInt32LE(body.length, 20)
+    central.writeUInt32LE(data.length, 24)
+    central.writeUInt16LE(name.length, 28)
+    central.writeUInt32LE(((entry.mode ?? (entry.name.endsWith('/') ? 0o40755 : 0o100644)) << 16) >>> 0, 38)
+    central.writeUInt32LE(offset, 42)
+    centrals.push(central, name)
+    offset += local.length + name.length + body.length
+  }
+  const directory = Buffer.concat(centrals)
+  const end = Buffer.alloc(22)
+  end.writeUInt32LE(0x06054b50, 0)
+  end.writeUInt16LE(entries.length, 8)
+  end.writeUInt16LE(entries.length, 10)
+  end.writeUInt32LE(directory.length, 12)
+  end.writeUInt32LE(offset, 16)
+  return Buffer.concat([...locals, directory, end])
+}
+
+function sampleZip(dir: string): string {
+  const file = join(dir, 'bundle.zip')
+  writeFileSync(
+    file,
+    rawZip([
+      { name: 'app/' },
+      { name: 'app/index.js', data: 'console.log(1)'.repeat(100), deflate: true },
+      { name: 'app/lib/util.js', data: 'export {}' },
+      { name: 'README.md', data: '# hi', mode: 0o100600 },
+    ]),
+  )
+  return file
+}
+
+test('zipListSync: should list entries like Dirent with sizes and CRCs', (t) => {
+  const file = sampleZip(tmpDir('list'))
+  const entries = zipListSync(file)
+  t.deepEqual(
+    entries.map((e) => e.entryName),
+    ['app/', 'app/index.js', 'app/lib/util.js', 'README.md'],
+  )
+  const index = entries[1]
+  t.is(index.name, 'index.js')
+  t.is(index.parentPath, 'app')
+  t.true(index.isFile())
+  t.false(index.isDirectory())
+  t.is(index.size, 1400)
+  t.true(index.compressedSize < index.size)
+  t.is(index.crc32, crc32(Buffer.from('console.log(1)'.repeat(100))))
+  t.is(index.mtime.getFullYear(), 2024)
+  t.true(entries[0].isDirectory())
+  t.is(entries[0].name, 'app')
+  t.is(entries[0].parentPath, '')
+  t.is(entries[3].mode, 0o100600)
+})
+
+test('zipReadSync: should return an entry as a Buffer', (t) => {
+  const file = sampleZip(tmpDir('read'))
+  t.deepEqual(zipReadSync(file, 'app/index.js'), Buffer.from('console.log(1)'.repeat(100)))
+  t.is(zipReadSync(file, 'README.md').toString(), '# hi')
+  t.throws(() => zipReadSync(file, 'missing.txt'), { message: /ENOENT/ })
+  t.throws(() => zipReadSync(file, 'app/'), { message: /EISDIR/ })
+})
+
+test('zipReadSync: should detect CRC mismatches', (t) => {
+  const file = join(tmpDir('crc'), 'bad.zip')
+  const zip = rawZip([{ name: 'a.txt', data: 'hello' }])
+  zip[30 + 'a.txt'.length] ^= 1 // flip a bit of the stored data
+  writeFileSync(file, zip)
+  t.throws(() => zipReadSync(file, 'a.txt'), { message: /ERR_INVALID_ARCHIVE/ })
+})
+
+test('zipReadSync: should not trust the entry size from the header', (t) => {
+  const file = join(tmpDir('size'), 'huge.zip')
+  const zip = rawZip([{ name: 'a.txt', data: 'hello', deflate: true }])
+  const central = zip.indexOf(Buffer.from([0x50, 0x4b, 0x01, 0x02]))
+  zip.writeUInt32LE(0xfffffff0, 22) // local header
+  zip.writeUInt32LE(0xfffffff0, central + 24)
+  writeFileSync(file, zip)
+  t.throws(() => zipReadSync(file, 'a.txt'), { message: /ERR_INVALID_ARCHIVE/ })
+})
+
+test('zipListSync: should reject files that are not zip archives', (t) => {
+  const file = join(tmpDir('invalid'), 'not.zip')
+  writeFileSync(file, 'definitely not a zip')
+  t.throws(() => zipListSync(file), { message: /ERR_INVALID_ARCHIVE/ })
+  t.throws(() => zipListSync(join(tmpDir('missing'), 'none.zip')), { message: /ENOENT/ })
+})
+
+test('zipExtractSync: should extract files, directories and modes', (t) => {
+  const dir = tmpDir('extract')
+  const file = sampleZip(dir)
+  const dest = join(dir, 'out')
+  const summary = zipExtractSync(file, dest)
+  t.deepEqual(summary, { files: 3, directories: 1, symlinks: 0, bytes: 1413 })
+  t.is(readFileSync(join(dest, 'app', 'index.js'), 'utf8'), 'console.log(1)'.repeat(100))
+  t.is(readFileSync(join(dest, 'app', 'lib', 'util.js'), 'utf8'), 'export {}')
+  if (process.platform !== 'win32') {
+    t.is(statSync(join(dest, 'README.md')).mode & 0o777, 0o600)
+  }
+})
+
+test('zipExtractSync: should apply filter and run sequentially', (t) => {
+  const dir = tmpDir('filter')
+  const file = sampleZip(dir)
+  const dest = join(dir, 'out')
+  zipExtractSync(file, dest, { filter: ['**/*.js'], concurrency: 1 })
+  t.true(existsSync(join(dest, 'app', 'lib', 'util.js')))
+  t.false(existsSync(join(dest, 'README.md')))
+})
+
+test('zipExtractSync: should reject zip-slip entries', (t) => {
+  const dir = tmpDir('slip')
+  const file = join(dir, 'evil.zip')
+  writeFileSync(file, rawZip([{ name: '../../evil.txt', data: 'x' }]))
+  t.throws(() => zipExtractSync(file, join(dir, 'out')), { message: /ERR_UNSAFE_ARCHIVE_PATH/ })
+  t.false(existsSync(join(dir, 'evil.txt')))
+
+  writeFileSync(file, rawZip([{ name: '..\\evil.txt', data: 'x' }]))
+  t.throws(() => zipExtractSync(file, join(dir, 'out')), { message: /ERR_UNSAFE_ARCHIVE_PATH/ })
+})
+
+test('zipExtractSync: should keep symlinks inside destDir only', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('links')
+  const ok = join(dir, 'ok.zip')
+  writeFileSync(
+    ok,
+    rawZip([
+      { name: 'target.txt', data: 'hi' },
+      { name: 'link', data: 'target.txt', mode: 0o120777 },
+    ]),
+  )
+  const summary = zipExtractSync(ok, join(dir, 'ok'))
+  t.is(summary.symlinks, 1)
+  t.is(readlinkSync(join(dir, 'ok', 'link')), 'target.txt')
+
+  const evil = join(dir, 'evil.zip')
+  writeFileSync(evil, rawZip([{ name: 'link', data: '/etc', mode: 0o120777 }]))
+  t.throws(() => zipExtractSync(evil, join(dir, 'evil')), { message: /ERR_UNSAFE_ARCHIVE_PATH/ })
+})
+
+test('zipExtractSync: should not follow symlinks already in destDir', (t) => {
+  if (process.platform === 'win32') return t.pass()
+  const dir = tmpDir('existing-link')
+  mkdirSync(join(dir, 'outside'))
+  mkdirSync(join(dir, 'out'))
+  symlinkSync(join(dir, 'outside'), join(dir, 'out', 'sub'))
+  const file = join(dir, 'evil.zip')
+  writeFileSync(file, rawZip([{ name: 'sub/pwned.txt', data: 'x' }]))
+  t.throws(() => zipExtractSync(file, join(dir, 'out')), { message: /ERR_UNSAFE_ARCHIVE_PATH/ })
+  t.false(existsSync(join(dir, 'outside', 'pwned.txt')))
+})
+
+test('zipList/zipRead/zipExtract: async variants should work', async (t) => {
+  const dir = tmpDir('async')
+  const file = sampleZip(dir)
+  const entries = (await zipList(file)) as unknown[]
+  t.is(entries.length, 4)
+  t.is(((await zipRead(file, 'README.md')) as Buffer).toString(), '# hi')
+  const summary = (await zipExtract(file, join(dir, 'out'), { concurrency: 8 })) as { files: number }
+  t.is(summary.files, 3)
+  await t.throwsAsync(async () => await zipRead(file, 'nope'), { message: /ENOENT/ })
+})
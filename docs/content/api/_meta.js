@@ -6,23 +6,39 @@ export default {
   chown: 'chown',
   'copy-file': 'copyFile',
   cp: 'cp',
+  'detect-encoding': 'detectEncoding',
+  du: 'du',
+  duplicates: 'findDuplicates',
   exists: 'exists',
+  fallocate: 'fallocate',
   glob: 'glob',
+  hash: 'hashFile / hashTree',
+  'line-index': 'buildLineIndex',
   link: 'link',
+  lock: 'lock / lockfile',
   lstat: 'lstat',
   mkdir: 'mkdir',
-  mkdtemp: 'mkdtemp',
+  mkdtemp: 'mkdtemp / mkstemp',
+  mmap: 'mmapFile',
   'read-file': 'readFile',
+  'read-lines': 'readLines / tail',
   readdir: 'readdir',
   readlink: 'readlink',
   realpath: 'realpath',
   rename: 'rename',
   rm: 'rm',
   rmdir: 'rmdir',
+  root: 'openRoot',
+  search: 'search',
   stat: 'stat',
   symlink: 'symlink',
+  tar: 'tarCreate / tarExtract',
+  tmpdir: 'tmpdir',
+  trash: 'trash / listTrash / restoreFromTrash',
   truncate: 'truncate',
   unlink: 'unlink',
   utimes: 'utimes',
   'write-file': 'writeFile',
+  xattr: 'xattr',
+  zip: 'zipList / zipRead / zipExtract',
 }
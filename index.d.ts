@@ -13,6 +13,111 @@ export declare class Dirent {
   get path(): string
 }
 
+/**
+ * An advisory lock on a file or directory, held until `release()` is called, the handle is
+ * garbage collected or the process exits. `using` / `await using` release it too.
+ */
+export declare class FileLock {
+  readonly path: string
+  readonly exclusive: boolean
+  /** Releases the lock (closing its descriptor). Releasing twice is a no-op. */
+  release(): void
+  get released(): boolean
+}
+
+/**
+ * Byte offsets of every line start in a file, for O(1) seeks to any line.
+ * Lines follow `readLines`: `\n` or `\r\n` separated, and a trailing newline does not start
+ * an empty last line.
+ */
+export declare class LineIndex {
+  /** Number of lines indexed. */
+  get lineCount(): number
+  /** Number of bytes indexed. */
+  get size(): number
+  /**
+   * Reads lines `from..=to` (1-based, like `readFile`'s `lines` option) by seeking straight to
+   * their offset. `to` is clamped to `lineCount`; lines appended after the last `refresh()`
+   * are not visible. Throws `ESTALE` if the file was replaced or shrank since then.
+   */
+  readLinesSync(from: number, to: number, encoding?: string | undefined | null): Array<string> | Array<Buffer>
+  readLines(from: number, to: number, encoding?: string | undefined | null): Promise<Array<string> | Array<Buffer>>
+  /**
+   * Extends the index with lines appended since it was built, and returns the new line count.
+   * A file that shrank, was replaced or whose first bytes changed is indexed again from the start.
+   */
+  refreshSync(): number
+  refresh(): Promise<number>
+}
+
+/**
+ * Async iterable of line batches returned by `readLines`. Lines are read on a background
+ * thread with a `BufReader`, a few batches ahead of the consumer.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ * 
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
+export declare class LineReader {
+  [Symbol.asyncIterator](): AsyncGenerator<Array<string> | Array<Buffer>, void, undefined>
+}
+
+/**
+ * A lockfile created by `lockfile`: the file exists while the lock is held. `release()`,
+ * garbage collection of the handle, process exit and `using` / `await using` remove it, unless
+ * another process has taken it over as stale in the meantime.
+ */
+export declare class Lockfile {
+  readonly path: string
+  /** Removes the lockfile if it is still ours. Releasing twice is a no-op. */
+  release(): void
+  /**
+   * Sets the lockfile's mtime to now, so that holding it for longer than `stale` does not let
+   * others take it over. Throws `ECOMPROMISED` when it was released, removed or taken over.
+   */
+  touch(): void
+  get released(): boolean
+}
+
+/**
+ * A directory opened with `openRoot`. Every method takes a path relative to it; absolute paths,
+ * `..` past the root and symlinks pointing outside of it fail with EXDEV.
+ */
+export declare class Root {
+  /** The directory this root was opened on, as passed to `openRoot`. */
+  get path(): string
+  readFileSync(path: string, options?: string | ReadFileOptions | undefined | null): string | Buffer
+  readFile(path: string, options?: string | ReadFileOptions | undefined | null): Promise<unknown>
+  writeFileSync(path: string, data: string | Buffer, options?: WriteFileOptions | undefined | null): void
+  writeFile(path: string, data: string | Buffer, options?: WriteFileOptions | undefined | null): Promise<unknown>
+  /** Like `statSync`: symlinks are followed, as long as they stay inside the root. */
+  statSync(path: string): Stats
+  stat(path: string): Promise<unknown>
+  /** Like `readdirSync`; `recursive` never descends into symlinked directories. */
+  readdirSync(path: string, options?: ReaddirOptions | undefined | null): Array<string> | Array<Dirent>
+  readdir(path: string, options?: ReaddirOptions | undefined | null): Promise<unknown>
+  /** Like `mkdirSync`; with `recursive` returns the first directory created, relative to the root. */
+  mkdirSync(path: string, options?: MkdirOptions | undefined | null): string | null
+  mkdir(path: string, options?: MkdirOptions | undefined | null): Promise<unknown>
+  /** Like `rmSync` (`force`, `recursive`, `concurrency`); symlinks are removed, never followed. */
+  rmSync(path: string, options?: RmOptions | undefined | null): void
+  rm(path: string, options?: RmOptions | undefined | null): Promise<unknown>
+}
+
+/**
+ * Async iterable of match batches (one batch per file, in the order files finish), returned
+ * by `searchStream`. Breaking out of `for await` stops the walk.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ * 
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
+export declare class SearchStream {
+  [Symbol.asyncIterator](): AsyncGenerator<Array<SearchMatch>, void, undefined>
+}
+
 export declare class Stats {
   readonly dev: number
   readonly mode: number
@@ -45,6 +150,63 @@ export declare class Stats {
   get birthtime(): Date
 }
 
+/**
+ * A temporary directory created by `tmpdir`. `await using` (`Symbol.asyncDispose`) and `using`
+ * (`Symbol.dispose`) remove it with `rm` unless it was created with `keep: true`.
+ */
+export declare class TempDir {
+  readonly path: string
+  readonly keep: boolean
+  /** Removes the directory tree, `keep` or not. */
+  remove(): Promise<unknown>
+  removeSync(): void
+  /** `Symbol.asyncDispose`: `remove()` unless `keep` is set. */
+  dispose(): Promise<unknown>
+  /** `Symbol.dispose`: `removeSync()` unless `keep` is set. */
+  disposeSync(): void
+}
+
+/** An entry of a trash directory, as returned by `trash` and `listTrash`. */
+export declare class TrashItem {
+  /** Name of the entry inside the trash (unique per trash directory). */
+  readonly name: string
+  /** Location of the trashed entry (`<trashDir>/files/<name>`), to pass to `restoreFromTrash`. */
+  readonly path: string
+  /** Absolute path the entry was trashed from. */
+  readonly originalPath: string
+  /**
+   * The trash directory holding the entry: `$XDG_DATA_HOME/Trash` or a per-volume
+   * `.Trash-$uid` / `.Trash/$uid`.
+   */
+  readonly trashDir: string
+  readonly deletionDateMs: number
+  /** Returns the deletion date as a Date object */
+  get deletionDate(): Date
+}
+
+/** An entry of a zip archive, shaped like `Dirent`. */
+export declare class ZipEntry {
+  /** Last path component (without the trailing `/` of directories). */
+  readonly name: string
+  /** Directory of the entry inside the archive (`''` at the top level). */
+  readonly parentPath: string
+  /** Full name as stored in the archive, to pass to `zipRead`. */
+  readonly entryName: string
+  /** Uncompressed size in bytes. */
+  readonly size: number
+  readonly compressedSize: number
+  readonly crc32: number
+  /** Unix mode (file type and permission bits) when the archive recorded one. */
+  readonly mode?: number
+  readonly encrypted: boolean
+  readonly mtimeMs: number
+  isFile(): boolean
+  isDirectory(): boolean
+  isSymbolicLink(): boolean
+  /** Returns mtime as a Date object */
+  get mtime(): Date
+}
+
 export declare function access(path: string, mode?: number | undefined | null): Promise<unknown>
 
 export declare function accessSync(path: string, mode?: number | undefined | null): void
@@ -61,6 +223,20 @@ export declare function appendFileSync(
   options?: WriteFileOptions | undefined | null,
 ): void
 
+/** Counts returned by the archive functions. */
+export interface ArchiveSummary {
+  /** Regular files, including hard links. */
+  files: number
+  directories: number
+  symlinks: number
+  /** Total size of the file contents (uncompressed). */
+  bytes: number
+}
+
+export declare function buildLineIndex(path: string): Promise<unknown>
+
+export declare function buildLineIndexSync(path: string): LineIndex
+
 export declare function chmod(path: string, mode: number): Promise<unknown>
 
 export declare function chmodSync(path: string, mode: number): void
@@ -87,15 +263,234 @@ export interface CpOptions {
    * 0 or 1 means sequential; > 1 enables rayon parallel traversal.
    */
   concurrency?: number
+  /**
+   * Rush-FS extension: incremental sync. Existing destination files that are already
+   * up to date are skipped instead of being overwritten (or skipped wholesale with `force: false`).
+   * - `'newer'`: copy only when the source mtime is newer than the destination's.
+   * - `'size-mtime'`: copy unless size and mtime (whole seconds, like rsync) both match.
+   * - `'checksum'`: copy unless size and content both match.
+   *
+   * Copied files always receive the source timestamps so later runs can skip them.
+   */
+  update?: string
+  /**
+   * Rush-FS extension: delete destination entries that do not exist in the source. Entries of
+   * another type (a file where the source has a directory, or the other way round) still fail.
+   */
+  mirror?: boolean
+  /** Rush-FS extension: metadata to carry over from the source. See `CpPreserveOptions`. */
+  preserve?: CpPreserveOptions
+  /**
+   * Rush-FS extension: recreate hard links between source files as hard links in the
+   * destination instead of writing one full copy per link (Unix only).
+   */
+  preserveHardlinks?: boolean
+  /**
+   * Rush-FS extension: write nothing and return the `DryRunPlan` (destination paths that would
+   * be created, overwritten, skipped or, with `mirror`, removed) instead.
+   */
+  dryRun?: boolean
+  /**
+   * Rush-FS extension: what to do when an entry inside a directory cannot be copied (or, with
+   * `mirror`, removed). `'throw'` (default) stops at the first error, `'skip'` carries on with
+   * the other entries, `'collect'` does the same and then throws an `AggregateError` of every
+   * failure. Errors on `src` / `dest` themselves always throw.
+   */
+  onError?: 'throw' | 'skip' | 'collect'
+}
+
+/**
+ * Metadata preserved by `cp` (all default to false).
+ *
+ * - `mode`: copy permission bits, including directories (applied after their children are written).
+ * - `ownership`: copy uid/gid with `lchown`. Silently skipped when the process lacks the privilege.
+ * - `xattrs`: copy extended attributes (Linux and macOS).
+ * - `timestamps`: same as `preserveTimestamps`.
+ */
+export interface CpPreserveOptions {
+  mode?: boolean
+  ownership?: boolean
+  xattrs?: boolean
+  timestamps?: boolean
+}
+
+/** Summary returned by `cp` when `update` or `mirror` is set. */
+export interface CpSummary {
+  copied: number
+  skipped: number
+  deleted: number
+}
+
+export declare function cpSync(
+  src: string,
+  dest: string,
+  options?: CpOptions | undefined | null,
+): undefined | CpSummary | DryRunPlan
+
+export interface DetectedEncoding {
+  /**
+   * WHATWG name of the encoding (e.g. `'UTF-8'`, `'UTF-16LE'`, `'Shift_JIS'`, `'GBK'`), usable as
+   * `readFile`'s `encoding`.
+   */
+  encoding: string
+  /** Whether the file starts with a byte order mark. */
+  bom: boolean
+  /**
+   * False when the guess did not clearly beat other candidates (or there was nothing to go on,
+   * such as an empty or pure ASCII file).
+   */
+  confident: boolean
+}
+
+export declare function detectEncoding(path: string): Promise<unknown>
+
+export declare function detectEncodingSync(path: string): DetectedEncoding
+
+/**
+ * What `rm` / `cp` would do with `dryRun: true`. Every list is sorted; `cp` reports destination
+ * paths.
+ */
+export interface DryRunPlan {
+  /** Paths that would be deleted: files, symlinks and directories (with their contents listed too). */
+  removed: Array<string>
+  /** Paths that would be created, including missing parent directories. */
+  created: Array<string>
+  /** Existing paths that would be replaced in place. */
+  overwritten: Array<string>
+  /** Paths left alone: already up to date (`update`), or existing with `force: false`. */
+  skipped: Array<string>
+  totals: DryRunTotals
+}
+
+export interface DryRunTotals {
+  removed: number
+  created: number
+  overwritten: number
+  skipped: number
+  /** Bytes of regular files that would be freed (`rm`) or written (`cp`). */
+  bytes: number
+}
+
+export declare function du(path: string, options?: DuOptions | undefined | null): Promise<unknown>
+
+export interface DuEntry {
+  name: string
+  bytes: number
+  blocks: number
+  files: number
+  dirs: number
+}
+
+export interface DuOptions {
+  /** Report apparent sizes (sum of file lengths) instead of allocated disk usage. Defaults to false. */
+  apparent?: boolean
+  /** Number of threads for the parallel walk. Defaults to the number of CPUs. */
+  concurrency?: number
+  /** Follow symlinks into their targets instead of counting the links. Defaults to false. */
+  followSymlinks?: boolean
+  /** Count a hard-linked inode once, like `du`. Defaults to true. */
+  countHardlinksOnce?: boolean
+  /** Do not descend more than this many levels below `path`; deeper entries are not counted. */
+  maxDepth?: number
+  /** Also return a per-child breakdown of the direct children of `path`. Defaults to false. */
+  children?: boolean
 }
 
-export declare function cpSync(src: string, dest: string, options?: CpOptions | undefined | null): void
+export interface DuplicateGroup {
+  /** Size of each file in bytes. */
+  size: number
+  /** Paths with identical content, sorted. The first one is kept by `action: 'hardlink'`. */
+  paths: Array<string>
+  /** With `action: 'hardlink'`: the paths now hard-linked to `paths[0]`. */
+  linked?: Array<string>
+  /** With `action: 'hardlink'`: the duplicates left in place, and why. */
+  failed?: Array<DuplicateLinkError>
+}
+
+/**
+ * A duplicate `action: 'hardlink'` did not replace. `code` is `EXDEV` for another filesystem,
+ * `ECHANGED` when either file changed after it was hashed, or the error of the failing call.
+ */
+export interface DuplicateLinkError {
+  path: string
+  code: string
+  message: string
+}
+
+export interface DuResult {
+  /** Apparent size or allocated bytes (`blocks * 512`), depending on `apparent`. */
+  bytes: number
+  /** 512-byte blocks allocated on disk. */
+  blocks: number
+  /** Number of non-directory entries (files, symlinks, ...). */
+  files: number
+  /** Number of directories below `path` (the root itself is not counted). */
+  dirs: number
+  children?: Array<DuEntry>
+}
+
+export declare function duSync(path: string, options?: DuOptions | undefined | null): DuResult
 
 export declare function exists(path: string): Promise<unknown>
 
 export declare function existsSync(path: string): boolean
 
-export declare function glob(pattern: string, options?: GlobOptions | undefined | null): Promise<unknown>
+export declare function fallocate(path: string, offset: number, length: number, options?: FallocateOptions | undefined | null): Promise<unknown>
+
+/** Modes of `fallocate`, named after the Linux `FALLOC_FL_*` flags they map to. */
+export interface FallocateOptions {
+  /**
+   * Allocate without changing the file size when the range extends past the end
+   * (`FALLOC_FL_KEEP_SIZE`). Not available on Windows.
+   */
+  keepSize?: boolean
+  /**
+   * Deallocate the range, which then reads as zeros; implies `keepSize`
+   * (`FALLOC_FL_PUNCH_HOLE`). Linux only.
+   */
+  punchHole?: boolean
+  /** Zero the range and keep it allocated (`FALLOC_FL_ZERO_RANGE`). Linux only. */
+  zeroRange?: boolean
+}
+
+/**
+ * Allocates (or with `punchHole` / `zeroRange`, deallocates or zeroes) `length` bytes of `path`
+ * starting at `offset`, like Linux `fallocate(2)`.
+ */
+export declare function fallocateSync(path: string, offset: number, length: number, options?: FallocateOptions | undefined | null): void
+
+export interface FileHash {
+  /** Path relative to the root, always '/'-separated. */
+  path: string
+  hash: string | Buffer
+}
+
+export declare function findDuplicates(roots: Array<string>, options?: FindDuplicatesOptions | undefined | null): Promise<unknown>
+
+export interface FindDuplicatesOptions {
+  /** Ignore files smaller than this many bytes. Defaults to 1 (empty files are skipped). */
+  minSize?: number
+  /** Glob patterns (relative to each root) to skip. */
+  exclude?: Array<string>
+  /** Threads used for walking and hashing. Defaults to 4. */
+  concurrency?: number
+  /**
+   * 'report' (default) only returns the groups. 'hardlink' also replaces every duplicate
+   * with a hard link to the first path of its group (same filesystem only).
+   */
+  action?: string
+}
+
+export declare function findDuplicatesSync(roots: Array<string>, options?: FindDuplicatesOptions | undefined | null): Array<DuplicateGroup>
+
+export declare function getxattr(path: string, name: string): Promise<unknown>
+
+export declare function getxattrSync(path: string, name: string): Buffer
+
+export declare function glob(
+  pattern: string | Array<string>,
+  options?: GlobOptions | undefined | null,
+): Promise<unknown>
 
 export interface GlobOptions {
   cwd?: string
@@ -103,13 +498,63 @@ export interface GlobOptions {
   exclude?: Array<string>
   concurrency?: number
   gitIgnore?: boolean
+  /**
+   * Rush-FS extension: what to do with directories that cannot be read. `'skip'` (default,
+   * like Node.js) leaves them out, `'throw'` fails on the first one, and `'collect'` finishes
+   * the walk, then throws an `AggregateError` of every failure.
+   */
+  onError?: 'throw' | 'skip' | 'collect'
 }
 
 export declare function globSync(
-  pattern: string,
+  pattern: string | Array<string>,
   options?: GlobOptions | undefined | null,
 ): Array<string> | Array<Dirent>
 
+export declare function hashFile(path: string, options?: HashFileOptions | undefined | null): Promise<unknown>
+
+export interface HashFileOptions {
+  /** 'sha256' (default), 'blake3' or 'xxh3' (128-bit). */
+  algorithm?: string
+  /** 'hex' (default), 'base64', 'base64url' or 'buffer'. */
+  encoding?: string
+}
+
+export declare function hashFileSync(path: string, options?: HashFileOptions | undefined | null): string | Buffer
+
+export declare function hashTree(dir: string, options?: HashTreeOptions | undefined | null): Promise<unknown>
+
+export interface HashTreeOptions {
+  /** 'sha256' (default), 'blake3' or 'xxh3' (128-bit). */
+  algorithm?: string
+  /** 'hex' (default), 'base64', 'base64url' or 'buffer'. */
+  encoding?: string
+  /** Glob patterns (relative to the root) to leave out of the digest. */
+  exclude?: Array<string>
+  /** Respect .gitignore / .ignore files. Defaults to false. */
+  gitIgnore?: boolean
+  /** Threads used for walking and hashing. Defaults to 4. */
+  concurrency?: number
+  /**
+   * Hash symlink targets' contents instead of the link paths. Dangling and looping links are
+   * still hashed by their target path. Defaults to false.
+   */
+  followSymlinks?: boolean
+  /** Also return the digest of every file. Defaults to false. */
+  perFile?: boolean
+}
+
+export interface HashTreeResult {
+  hash: string | Buffer
+  files?: Array<FileHash>
+}
+
+export declare function hashTreeSync(dir: string, options?: HashTreeOptions | undefined | null): HashTreeResult
+
+export declare function lgetxattr(path: string, name: string): Promise<unknown>
+
+export declare function lgetxattrSync(path: string, name: string): Buffer
+
 export interface LineRange {
   from: number
   to: number
@@ -119,6 +564,81 @@ export declare function link(existingPath: string, newPath: string): Promise<unk
 
 export declare function linkSync(existingPath: string, newPath: string): void
 
+export declare function listTrash(): Promise<unknown>
+
+/**
+ * Lists the entries of the home trash and of the per-volume trashes of mounted filesystems,
+ * oldest first.
+ */
+export declare function listTrashSync(): Array<TrashItem>
+
+export declare function listxattr(path: string): Promise<unknown>
+
+export declare function listxattrSync(path: string): Array<string>
+
+export declare function llistxattr(path: string): Promise<unknown>
+
+export declare function llistxattrSync(path: string): Array<string>
+
+export declare function lock(path: string, options?: LockOptions | undefined | null): Promise<FileLock>
+
+/** Retries sleep on the runtime's timer instead of holding a threadpool worker, like `lock`. */
+export declare function lockfile(path: string, options?: LockfileOptions | undefined | null): Promise<Lockfile>
+
+export interface LockfileOptions {
+  /**
+   * Treat an existing lockfile whose mtime is older than this many milliseconds as abandoned
+   * and take it over. Default: never.
+   */
+  stale?: number
+  /** How many more times to try while the lockfile exists. Default: 0. */
+  retries?: number
+  /** Milliseconds between retries. Default: 100. */
+  retryWait?: number
+}
+
+export declare function lockfileSync(path: string, options?: LockfileOptions | undefined | null): Lockfile
+
+export interface LockOptions {
+  /** Take an exclusive (write) lock. Default: true, unless `shared` is set. */
+  exclusive?: boolean
+  /** Take a shared (read) lock; shorthand for `exclusive: false`. */
+  shared?: boolean
+  /**
+   * Wait for a conflicting lock to go away. With `false`, throw `EAGAIN` right away.
+   * Default: true.
+   */
+  wait?: boolean
+  /** Give up with `ETIMEDOUT` after this many milliseconds of waiting. Default: wait forever. */
+  timeout?: number
+  /**
+   * `'flock'` (default) locks the whole file with `flock` (`LockFileEx` on Windows). `'fcntl'`
+   * takes an open file description lock (`F_OFD_SETLK`, Linux only), which also works over NFS
+   * and needs the file to be writable for exclusive locks.
+   */
+  method?: 'flock' | 'fcntl'
+}
+
+export declare function lockSync(path: string, options?: LockOptions | undefined | null): FileLock
+
+export declare function lremovexattr(path: string, name: string): Promise<unknown>
+
+export declare function lremovexattrSync(path: string, name: string): void
+
+export declare function lsetxattr(
+  path: string,
+  name: string,
+  value: string | Buffer,
+  options?: SetxattrOptions | undefined | null,
+): Promise<unknown>
+
+export declare function lsetxattrSync(
+  path: string,
+  name: string,
+  value: string | Buffer,
+  options?: SetxattrOptions | undefined | null,
+): void
+
 export declare function lstat(path: string): Promise<unknown>
 
 export declare function lstatSync(path: string): Stats
@@ -136,6 +656,38 @@ export declare function mkdtemp(prefix: string): Promise<unknown>
 
 export declare function mkdtempSync(prefix: string): string
 
+export declare function mkstemp(prefix: string, options?: MkstempOptions | undefined | null): Promise<unknown>
+
+export interface MkstempOptions {
+  /** Remove the file when the process exits, if it is still there. Default: false. */
+  removeOnExit?: boolean
+}
+
+export interface MkstempResult {
+  path: string
+  /** Open read/write descriptor of the new file, for `fs.write` / `fs.close`. */
+  fd: number
+}
+
+/**
+ * Creates `prefix` + 6 random characters exclusively (`O_CREAT | O_EXCL | O_CLOEXEC`, mode
+ * `0o600`) and returns its path with an open descriptor; closing it is up to the caller.
+ */
+export declare function mkstempSync(prefix: string, options?: MkstempOptions | undefined | null): MkstempResult
+
+export declare function mmapFile(path: string, options?: MmapFileOptions | undefined | null): Buffer & { close(): void }
+
+export interface MmapFileOptions {
+  /** Byte offset in the file where the mapping starts. Defaults to 0. */
+  offset?: number
+  /** Number of bytes to map. Defaults to (and is clamped to) the rest of the file. */
+  length?: number
+}
+
+export declare function openRoot(dir: string): Promise<unknown>
+
+export declare function openRootSync(dir: string): Root
+
 export declare function readdir(path: string, options?: ReaddirOptions | undefined | null): Promise<unknown>
 
 /** * Reads the contents of a directory.
@@ -162,6 +714,12 @@ export interface ReaddirOptions {
   concurrency?: number
   recursive?: boolean
   withFileTypes?: boolean
+  /**
+   * Rush-FS extension: what to do when an entry cannot be read (e.g. an unreadable
+   * subdirectory with `recursive`). `'throw'` (default) fails, `'skip'` leaves it out, and
+   * `'collect'` finishes the listing, then throws an `AggregateError` of every failure.
+   */
+  onError?: 'throw' | 'skip' | 'collect'
 }
 
 export declare function readdirSync(
@@ -175,6 +733,11 @@ export interface ReadFileOptions {
   encoding?: string
   flag?: string
   lines?: LineRange
+  /**
+   * Decompress the file: 'gzip', 'zstd', 'brotli', or 'auto' to detect gzip / zstd by their
+   * magic bytes and brotli by a `.br` extension (anything else is read as is).
+   */
+  compression?: string
 }
 
 export declare function readFileSync(
@@ -182,6 +745,22 @@ export declare function readFileSync(
   options?: string | ReadFileOptions | undefined | null,
 ): string | Buffer
 
+export declare function readLines(path: string, options?: ReadLinesOptions | undefined | null): LineReader
+
+export interface ReadLinesOptions {
+  /**
+   * 'utf8' (default, invalid bytes become U+FFFD), 'latin1', 'ascii', ... or 'buffer' to
+   * yield each line as a Buffer.
+   */
+  encoding?: string
+  /** 1-based line number to start from. Defaults to 1. */
+  start?: number
+  /** Maximum number of lines per batch. Defaults to 1024. */
+  batchSize?: number
+  /** Stops the iteration; pending and later `next()` calls reject with an AbortError. */
+  signal?: AbortSignal
+}
+
 export declare function readlink(path: string): Promise<unknown>
 
 export declare function readlinkSync(path: string): string
@@ -190,10 +769,30 @@ export declare function realpath(path: string): Promise<unknown>
 
 export declare function realpathSync(path: string): string
 
+export declare function removexattr(path: string, name: string): Promise<unknown>
+
+export declare function removexattrSync(path: string, name: string): void
+
 export declare function rename(oldPath: string, newPath: string): Promise<unknown>
 
 export declare function renameSync(oldPath: string, newPath: string): void
 
+export declare function restoreFromTrash(path: string, options?: RestoreFromTrashOptions | undefined | null): Promise<unknown>
+
+export interface RestoreFromTrashOptions {
+  /**
+   * Replace an entry that now exists at the original path. Default: false (fails with EEXIST).
+   * The entry is moved aside first and deleted only once the restore succeeded.
+   */
+  overwrite?: boolean
+}
+
+/**
+ * Moves a trashed entry (`TrashItem.path`) back to its original location, recreating missing
+ * parent directories. Returns the restored path.
+ */
+export declare function restoreFromTrashSync(path: string, options?: RestoreFromTrashOptions | undefined | null): string
+
 export declare function rm(path: string, options?: RmOptions | undefined | null): Promise<unknown>
 
 export declare function rmdir(path: string): Promise<unknown>
@@ -210,6 +809,11 @@ export declare function rmdirSync(path: string): void
  *   each try. This option represents the number of retries.
  * - `retryDelay`: The amount of time in milliseconds to wait between retries (default 100ms).
  * - `concurrency` (rush-fs extension): Number of parallel threads for recursive removal.
+ * - `dryRun` (rush-fs extension): Remove nothing and return the `DryRunPlan` instead.
+ * - `onError` (rush-fs extension): What to do when an entry inside the tree cannot be removed.
+ *   `'throw'` (default) stops at the first error, `'skip'` leaves the entry (and its parent
+ *   directories) in place and carries on, `'collect'` does the same and then throws an
+ *   `AggregateError` of every failure. Errors on `path` itself always throw.
  */
 export interface RmOptions {
   force?: boolean
@@ -217,9 +821,76 @@ export interface RmOptions {
   recursive?: boolean
   retryDelay?: number
   concurrency?: number
+  dryRun?: boolean
+  onError?: 'throw' | 'skip' | 'collect'
 }
 
-export declare function rmSync(path: string, options?: RmOptions | undefined | null): void
+export declare function rmSync(path: string, options?: RmOptions | undefined | null): undefined | DryRunPlan
+
+export declare function search(pattern: string, options?: SearchOptions | undefined | null): Promise<unknown>
+
+export interface SearchMatch {
+  /** Path relative to `cwd`. */
+  path: string
+  /** 1-based line number. */
+  line: number
+  /** 1-based column of the match in UTF-16 code units, so `text.slice(column - 1)` starts at it. */
+  column: number
+  /** The whole matching line, without its line terminator. */
+  text: string
+  /** Up to `context` lines before the match. Only set when `context > 0`. */
+  before?: Array<string>
+  /** Up to `context` lines after the match. Only set when `context > 0`. */
+  after?: Array<string>
+}
+
+export interface SearchOptions {
+  /** Directory to search. Defaults to the current directory. */
+  cwd?: string
+  /** Glob patterns (relative to `cwd`) a file must match to be searched. */
+  include?: Array<string>
+  /** Glob patterns (relative to `cwd`) to skip. */
+  exclude?: Array<string>
+  /** Respect .gitignore / .ignore files and skip hidden entries. Defaults to false. */
+  gitIgnore?: boolean
+  /** Treat `pattern` as a regular expression instead of a literal string. Defaults to false. */
+  regex?: boolean
+  caseInsensitive?: boolean
+  /**
+   * Stop after this many matches in total. `search` returns the first ones in path order;
+   * `searchStream` yields those of the files that finish first, which can change between runs.
+   */
+  maxMatches?: number
+  /** Number of lines of context to return before and after each match. Defaults to 0. */
+  context?: number
+  /** Threads for the parallel walk. Defaults to 4. */
+  concurrency?: number
+}
+
+export declare function searchStream(pattern: string, options?: SearchOptions | undefined | null): SearchStream
+
+export declare function searchSync(pattern: string, options?: SearchOptions | undefined | null): Array<SearchMatch>
+
+export declare function setxattr(
+  path: string,
+  name: string,
+  value: string | Buffer,
+  options?: SetxattrOptions | undefined | null,
+): Promise<unknown>
+
+export interface SetxattrOptions {
+  /** Fail with `EEXIST` if the attribute already exists. */
+  create?: boolean
+  /** Fail with `ENODATA` if the attribute does not exist yet. */
+  replace?: boolean
+}
+
+export declare function setxattrSync(
+  path: string,
+  name: string,
+  value: string | Buffer,
+  options?: SetxattrOptions | undefined | null,
+): void
 
 export declare function stat(path: string): Promise<unknown>
 
@@ -229,6 +900,80 @@ export declare function symlink(target: string, path: string, symlinkType?: stri
 
 export declare function symlinkSync(target: string, path: string, symlinkType?: string | undefined | null): void
 
+export declare function tail(path: string, n: number, options?: TailOptions | undefined | null): Promise<unknown>
+
+export interface TailOptions {
+  /** Same encodings as `readLines`. Defaults to 'utf8'. */
+  encoding?: string
+}
+
+export declare function tailSync(path: string, n: number, options?: TailOptions | undefined | null): Array<string> | Array<Buffer>
+
+export declare function tarCreate(dir: string, outFile: string, options?: TarCreateOptions | undefined | null): Promise<unknown>
+
+export interface TarCreateOptions {
+  /** Compress the archive with gzip (`.tar.gz`). */
+  gzip?: boolean
+  /** Glob patterns (relative to `dir`) of paths to leave out. */
+  exclude?: Array<string>
+  /** Honour `.gitignore` / `.ignore` files and skip hidden entries. */
+  gitIgnore?: boolean
+  /** Metadata to record as is. See `TarPreserveOptions`. */
+  preserve?: TarPreserveOptions
+}
+
+export declare function tarCreateSync(dir: string, outFile: string, options?: TarCreateOptions | undefined | null): ArchiveSummary
+
+export declare function tarExtract(archive: string, destDir: string, options?: TarExtractOptions | undefined | null): Promise<unknown>
+
+export interface TarExtractOptions {
+  /** Number of leading path components to drop from every entry (like `tar --strip-components`). */
+  strip?: number
+  /** Glob patterns (matched after `strip`); only matching entries are extracted. */
+  filter?: Array<string>
+  /** Number of threads writing files. 0 or 1 extracts sequentially. Default: 4. */
+  concurrency?: number
+}
+
+export declare function tarExtractSync(archive: string, destDir: string, options?: TarExtractOptions | undefined | null): ArchiveSummary
+
+/**
+ * Metadata `tarCreate` records from the filesystem (all default to false).
+ * 
+ * Without them the archive is reproducible: modes are normalised to 0o755 (directories and
+ * executables) or 0o644, owners to 0:0 and mtimes to a fixed date, so the same tree always
+ * produces the same bytes.
+ */
+export interface TarPreserveOptions {
+  mode?: boolean
+  ownership?: boolean
+  timestamps?: boolean
+}
+
+export declare function tmpdir(options?: TmpdirOptions | undefined | null): Promise<unknown>
+
+export interface TmpdirOptions {
+  /**
+   * Name prefix, joined onto the OS temp directory (an absolute prefix is used as-is).
+   * Default: `'rush-fs-'`.
+   */
+  prefix?: string
+  /** Leave the directory in place when the handle is disposed. Default: false. */
+  keep?: boolean
+  /** Remove the directory when the process exits, if it is still there. Default: false. */
+  removeOnExit?: boolean
+}
+
+export declare function tmpdirSync(options?: TmpdirOptions | undefined | null): TempDir
+
+export declare function trash(paths: Array<string>): Promise<unknown>
+
+/**
+ * Moves `paths` to the trash following the freedesktop.org Trash specification, returning
+ * the new entries in order. Stops at the first failure; earlier paths stay trashed.
+ */
+export declare function trashSync(paths: Array<string>): Array<TrashItem>
+
 export declare function truncate(path: string, len?: number | undefined | null): Promise<unknown>
 
 export declare function truncateSync(path: string, len?: number | undefined | null): void
@@ -251,6 +996,27 @@ export interface WriteFileOptions {
   encoding?: string
   mode?: number
   flag?: string
+  /**
+   * Reject base64/hex strings containing characters outside the alphabet (or an odd number of
+   * hex digits), and characters the target encoding cannot represent, instead of skipping or
+   * replacing them like Node. Defaults to false.
+   */
+  strict?: boolean
+  /**
+   * Start a string written as UTF-8 or UTF-16 with a byte order mark. Only written when the
+   * file is empty, so appending never puts one mid-file. Defaults to false.
+   */
+  bom?: boolean
+  /**
+   * Compress the data: 'gzip', 'zstd', 'brotli', or 'auto' to pick one from the file extension
+   * (`.gz`, `.zst`, `.br`) and write other files uncompressed.
+   */
+  compression?: string
+  /**
+   * Compression level: 0-9 for gzip (default 6), 1-22 for zstd (default 3, negative values
+   * trade ratio for speed), 0-11 for brotli (default 11).
+   */
+  level?: number
 }
 
 export declare function writeFileSync(
@@ -258,3 +1024,25 @@ export declare function writeFileSync(
   data: string | Buffer,
   options?: WriteFileOptions | undefined | null,
 ): void
+
+export declare function zipExtract(path: string, dest: string, options?: ZipExtractOptions | undefined | null): Promise<unknown>
+
+export interface ZipExtractOptions {
+  /**
+   * Number of threads decompressing and writing entries. 0 or 1 extracts sequentially.
+   * Default: 4.
+   */
+  concurrency?: number
+  /** Glob patterns; only matching entries are extracted. */
+  filter?: Array<string>
+}
+
+export declare function zipExtractSync(path: string, dest: string, options?: ZipExtractOptions | undefined | null): ArchiveSummary
+
+export declare function zipList(path: string): Promise<unknown>
+
+export declare function zipListSync(path: string): Array<ZipEntry>
+
+export declare function zipRead(path: string, entryName: string): Promise<unknown>
+
+export declare function zipReadSync(path: string, entryName: string): Buffer
@@ -577,11 +577,22 @@ if (!nativeBinding) {
 
 module.exports = nativeBinding
 module.exports.Dirent = nativeBinding.Dirent
+module.exports.FileLock = nativeBinding.FileLock
+module.exports.LineIndex = nativeBinding.LineIndex
+module.exports.LineReader = nativeBinding.LineReader
+module.exports.Lockfile = nativeBinding.Lockfile
+module.exports.Root = nativeBinding.Root
+module.exports.SearchStream = nativeBinding.SearchStream
 module.exports.Stats = nativeBinding.Stats
+module.exports.TempDir = nativeBinding.TempDir
+module.exports.TrashItem = nativeBinding.TrashItem
+module.exports.ZipEntry = nativeBinding.ZipEntry
 module.exports.access = nativeBinding.access
 module.exports.accessSync = nativeBinding.accessSync
 module.exports.appendFile = nativeBinding.appendFile
 module.exports.appendFileSync = nativeBinding.appendFileSync
+module.exports.buildLineIndex = nativeBinding.buildLineIndex
+module.exports.buildLineIndexSync = nativeBinding.buildLineIndexSync
 module.exports.chmod = nativeBinding.chmod
 module.exports.chmodSync = nativeBinding.chmodSync
 module.exports.chown = nativeBinding.chown
@@ -590,36 +601,91 @@ module.exports.copyFile = nativeBinding.copyFile
 module.exports.copyFileSync = nativeBinding.copyFileSync
 module.exports.cp = nativeBinding.cp
 module.exports.cpSync = nativeBinding.cpSync
+module.exports.detectEncoding = nativeBinding.detectEncoding
+module.exports.detectEncodingSync = nativeBinding.detectEncodingSync
+module.exports.du = nativeBinding.du
+module.exports.duSync = nativeBinding.duSync
 module.exports.exists = nativeBinding.exists
 module.exports.existsSync = nativeBinding.existsSync
+module.exports.fallocate = nativeBinding.fallocate
+module.exports.fallocateSync = nativeBinding.fallocateSync
+module.exports.findDuplicates = nativeBinding.findDuplicates
+module.exports.findDuplicatesSync = nativeBinding.findDuplicatesSync
+module.exports.getxattr = nativeBinding.getxattr
+module.exports.getxattrSync = nativeBinding.getxattrSync
 module.exports.glob = nativeBinding.glob
 module.exports.globSync = nativeBinding.globSync
+module.exports.hashFile = nativeBinding.hashFile
+module.exports.hashFileSync = nativeBinding.hashFileSync
+module.exports.hashTree = nativeBinding.hashTree
+module.exports.hashTreeSync = nativeBinding.hashTreeSync
+module.exports.lgetxattr = nativeBinding.lgetxattr
+module.exports.lgetxattrSync = nativeBinding.lgetxattrSync
 module.exports.link = nativeBinding.link
 module.exports.linkSync = nativeBinding.linkSync
+module.exports.listTrash = nativeBinding.listTrash
+module.exports.listTrashSync = nativeBinding.listTrashSync
+module.exports.listxattr = nativeBinding.listxattr
+module.exports.listxattrSync = nativeBinding.listxattrSync
+module.exports.llistxattr = nativeBinding.llistxattr
+module.exports.llistxattrSync = nativeBinding.llistxattrSync
+module.exports.lock = nativeBinding.lock
+module.exports.lockfile = nativeBinding.lockfile
+module.exports.lockfileSync = nativeBinding.lockfileSync
+module.exports.lockSync = nativeBinding.lockSync
+module.exports.lremovexattr = nativeBinding.lremovexattr
+module.exports.lremovexattrSync = nativeBinding.lremovexattrSync
+module.exports.lsetxattr = nativeBinding.lsetxattr
+module.exports.lsetxattrSync = nativeBinding.lsetxattrSync
 module.exports.lstat = nativeBinding.lstat
 module.exports.lstatSync = nativeBinding.lstatSync
 module.exports.mkdir = nativeBinding.mkdir
 module.exports.mkdirSync = nativeBinding.mkdirSync
 module.exports.mkdtemp = nativeBinding.mkdtemp
 module.exports.mkdtempSync = nativeBinding.mkdtempSync
+module.exports.mkstemp = nativeBinding.mkstemp
+module.exports.mkstempSync = nativeBinding.mkstempSync
+module.exports.mmapFile = nativeBinding.mmapFile
+module.exports.openRoot = nativeBinding.openRoot
+module.exports.openRootSync = nativeBinding.openRootSync
 module.exports.readdir = nativeBinding.readdir
 module.exports.readdirSync = nativeBinding.readdirSync
 module.exports.readFile = nativeBinding.readFile
 module.exports.readFileSync = nativeBinding.readFileSync
+module.exports.readLines = nativeBinding.readLines
 module.exports.readlink = nativeBinding.readlink
 module.exports.readlinkSync = nativeBinding.readlinkSync
 module.exports.realpath = nativeBinding.realpath
 module.exports.realpathSync = nativeBinding.realpathSync
+module.exports.removexattr = nativeBinding.removexattr
+module.exports.removexattrSync = nativeBinding.removexattrSync
 module.exports.rename = nativeBinding.rename
 module.exports.renameSync = nativeBinding.renameSync
+module.exports.restoreFromTrash = nativeBinding.restoreFromTrash
+module.exports.restoreFromTrashSync = nativeBinding.restoreFromTrashSync
 module.exports.rm = nativeBinding.rm
 module.exports.rmdir = nativeBinding.rmdir
 module.exports.rmdirSync = nativeBinding.rmdirSync
 module.exports.rmSync = nativeBinding.rmSync
+module.exports.search = nativeBinding.search
+module.exports.searchStream = nativeBinding.searchStream
+module.exports.searchSync = nativeBinding.searchSync
+module.exports.setxattr = nativeBinding.setxattr
+module.exports.setxattrSync = nativeBinding.setxattrSync
 module.exports.stat = nativeBinding.stat
 module.exports.statSync = nativeBinding.statSync
 module.exports.symlink = nativeBinding.symlink
 module.exports.symlinkSync = nativeBinding.symlinkSync
+module.exports.tail = nativeBinding.tail
+module.exports.tailSync = nativeBinding.tailSync
+module.exports.tarCreate = nativeBinding.tarCreate
+module.exports.tarCreateSync = nativeBinding.tarCreateSync
+module.exports.tarExtract = nativeBinding.tarExtract
+module.exports.tarExtractSync = nativeBinding.tarExtractSync
+module.exports.tmpdir = nativeBinding.tmpdir
+module.exports.tmpdirSync = nativeBinding.tmpdirSync
+module.exports.trash = nativeBinding.trash
+module.exports.trashSync = nativeBinding.trashSync
 module.exports.truncate = nativeBinding.truncate
 module.exports.truncateSync = nativeBinding.truncateSync
 module.exports.unlink = nativeBinding.unlink
@@ -628,3 +694,9 @@ module.exports.utimes = nativeBinding.utimes
 module.exports.utimesSync = nativeBinding.utimesSync
 module.exports.writeFile = nativeBinding.writeFile
 module.exports.writeFileSync = nativeBinding.writeFileSync
+module.exports.zipExtract = nativeBinding.zipExtract
+module.exports.zipExtractSync = nativeBinding.zipExtractSync
+module.exports.zipList = nativeBinding.zipList
+module.exports.zipListSync = nativeBinding.zipListSync
+module.exports.zipRead = nativeBinding.zipRead
+module.exports.zipReadSync = nativeBinding.zipReadSync
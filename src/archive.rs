@@ -0,0 +1,545 @@
+//! Extraction plumbing shared by the archive readers (tarExtract, zipExtract): entry path
+//! sanitising, traversal-safe directory creation and the batched parallel file writer.
+
+use crate::utils::uv_error;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rayon::prelude::*;
+use std::collections::{HashSet, VecDeque};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Same limit as Linux's MAXSYMLINKS.
+const MAX_SYMLINKS: usize = 40;
+
+/// Buffered file data above which a batch is flushed to disk; larger entries are streamed.
+const BATCH_BYTES: usize = 32 * 1024 * 1024;
+
+/// Counts returned by the archive functions.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct ArchiveSummary {
+  /// Regular files, including hard links.
+  pub files: u32,
+  pub directories: u32,
+  pub symlinks: u32,
+  /// Total size of the file contents (uncompressed).
+  pub bytes: f64,
+}
+
+/// Where an archive entry may land relative to the destination.
+pub(crate) enum EntryPath {
+  Safe(PathBuf),
+  /// Nothing left after `strip` (or the entry names the archive root itself).
+  Empty,
+  /// The path contains a `..` component.
+  Escapes,
+}
+
+/// Makes an archive path relative: leading `/` and drive prefixes are dropped (like GNU tar),
+/// `.` is ignored and `..` rejects the entry. Then the first `strip` components are removed.
+pub(crate) fn entry_path(raw: &Path, strip: usize) -> EntryPath {
+  let mut parts = Vec::new();
+  for component in raw.components() {
+    match component {
+      Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+      Component::ParentDir => return EntryPath::Escapes,
+      Component::Normal(part) => parts.push(part),
+    }
+  }
+  if parts.len() <= strip {
+    return EntryPath::Empty;
+  }
+  EntryPath::Safe(parts[strip..].iter().collect())
+}
+
+/// Whether a symlink at `entry` (relative to the destination) pointing at `target` resolves
+/// outside the destination. Absolute targets always do.
+pub(crate) fn link_target_escapes(entry: &Path, target: &Path) -> bool {
+  let mut depth = entry.components().count().saturating_sub(1);
+  for component in target.components() {
+    match component {
+      Component::Prefix(_) | Component::RootDir => return true,
+      Component::CurDir => {}
+      Component::ParentDir => {
+        if depth == 0 {
+          return true;
+        }
+        depth -= 1;
+      }
+      Component::Normal(_) => depth += 1,
+    }
+  }
+  false
+}
+
+pub(crate) fn unsafe_path_error(entry: &str, dest: &str, syscall: &str, archive: &str) -> Error {
+  Error::from_reason(format!(
+    "ERR_UNSAFE_ARCHIVE_PATH: Entry '{}' would be extracted outside of '{}', {} '{}'",
+    entry, dest, syscall, archive
+  ))
+}
+
+pub(crate) fn unix_time(secs: u64) -> SystemTime {
+  UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+struct PendingFile {
+  path: PathBuf,
+  data: Vec<u8>,
+  mode: Option<u32>,
+  mtime: Option<SystemTime>,
+}
+
+/// Writes entries below `dest` without ever following a symlink: directories are created
+/// one component at a time and refuse symlinked components, files are created with
+/// `O_EXCL` after removing whatever was there, and symlinks are only created once every file
+/// has been written, so no write can go through a link from the archive. Hard links are made
+/// in archive order, to the target as it is at that point.
+pub(crate) struct Extractor<'a> {
+  dest: PathBuf,
+  dest_str: &'a str,
+  syscall: &'static str,
+  archive: &'a str,
+  pool: Option<rayon::ThreadPool>,
+  /// Directories (relative) already known to be real directories.
+  checked: HashSet<PathBuf>,
+  batch: Vec<PendingFile>,
+  batch_paths: HashSet<PathBuf>,
+  batch_bytes: usize,
+  dirs: Vec<(PathBuf, Option<u32>, Option<SystemTime>)>,
+  symlinks: Vec<(PathBuf, PathBuf)>,
+  summary: ArchiveSummary,
+}
+
+impl<'a> Extractor<'a> {
+  /// Creates `dest` if needed. `concurrency` > 1 writes buffered files in parallel.
+  pub(crate) fn new(
+    dest_str: &'a str,
+    concurrency: usize,
+    syscall: &'static str,
+    archive: &'a str,
+  ) -> Result<Self> {
+    fs::create_dir_all(dest_str).map_err(|e| uv_error(&e, "mkdir", Path::new(dest_str)))?;
+    let dest =
+      fs::canonicalize(dest_str).map_err(|e| uv_error(&e, "realpath", Path::new(dest_str)))?;
+    let pool = if concurrency > 1 {
+      Some(
+        rayon::ThreadPoolBuilder::new()
+          .num_threads(concurrency)
+          .build()
+          .map_err(|e| Error::from_reason(e.to_string()))?,
+      )
+    } else {
+      None
+    };
+    Ok(Extractor {
+      dest,
+      dest_str,
+      syscall,
+      archive,
+      pool,
+      checked: HashSet::new(),
+      batch: Vec::new(),
+      batch_paths: HashSet::new(),
+      batch_bytes: 0,
+      dirs: Vec::new(),
+      symlinks: Vec::new(),
+      summary: ArchiveSummary::default(),
+    })
+  }
+
+  fn escape_error(&self, entry: &Path) -> Error {
+    unsafe_path_error(
+      &entry.to_string_lossy(),
+      self.dest_str,
+      self.syscall,
+      self.archive,
+    )
+  }
+
+  /// Makes sure every component of `rel` is a real directory, creating missing ones.
+  fn ensure_dir(&mut self, rel: &Path) -> Result<()> {
+    let mut current = PathBuf::new();
+    for component in rel.components() {
+      current.push(component);
+      if self.checked.contains(&current) {
+        continue;
+      }
+      let full = self.dest.join(&current);
+      match fs::symlink_metadata(&full) {
+        Ok(meta) if meta.is_dir() => {}
+        Ok(meta) if meta.file_type().is_symlink() => return Err(self.escape_error(rel)),
+        Ok(_) => {
+          return Err(Error::from_reason(format!(
+            "ENOTDIR: not a directory, mkdir '{}'",
+            full.to_string_lossy()
+          )))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => match fs::create_dir(&full) {
+          Ok(()) => {}
+          Err(e) if e.kind() == io::ErrorKind::AlreadyExists && full.is_dir() => {}
+          Err(e) => return Err(uv_error(&e, "mkdir", &full)),
+        },
+        Err(e) => return Err(uv_error(&e, "lstat", &full)),
+      }
+      self.checked.insert(current.clone());
+    }
+    Ok(())
+  }
+
+  fn ensure_parent(&mut self, rel: &Path) -> Result<()> {
+    match rel.parent() {
+      Some(parent) if !parent.as_os_str().is_empty() => self.ensure_dir(parent),
+      _ => Ok(()),
+    }
+  }
+
+  pub(crate) fn dir(
+    &mut self,
+    rel: PathBuf,
+    mode: Option<u32>,
+    mtime: Option<SystemTime>,
+  ) -> Result<()> {
+    self.ensure_dir(&rel)?;
+    self.summary.directories += 1;
+    self.dirs.push((rel, mode, mtime));
+    Ok(())
+  }
+
+  /// Writes a file straight from the archive stream (used for large entries, or when
+  /// extracting sequentially).
+  pub(crate) fn file_from_reader(
+    &mut self,
+    rel: PathBuf,
+    reader: &mut dyn Read,
+    mode: Option<u32>,
+    mtime: Option<SystemTime>,
+  ) -> Result<()> {
+    self.ensure_parent(&rel)?;
+    if self.batch_paths.contains(&rel) {
+      self.flush()?;
+    }
+    let path = self.dest.join(&rel);
+    let mut file = create_exclusive(&path)?;
+    let written = io::copy(reader, &mut file)
+      .map_err(|e| Error::from_reason(format!("{}, {} '{}'", e, self.syscall, self.archive)))?;
+    finish_file(&file, &path, mode, mtime)?;
+    self.summary.files += 1;
+    self.summary.bytes += written as f64;
+    Ok(())
+  }
+
+  /// Buffers a file for the next parallel flush, or writes it right away without a pool.
+  pub(crate) fn file(
+    &mut self,
+    rel: PathBuf,
+    data: Vec<u8>,
+    mode: Option<u32>,
+    mtime: Option<SystemTime>,
+  ) -> Result<()> {
+    if self.pool.is_none() {
+      return self.file_from_reader(rel, &mut data.as_slice(), mode, mtime);
+    }
+    self.ensure_parent(&rel)?;
+    // A later entry for the same path wins, so it must not race the earlier one.
+    if self.batch_paths.contains(&rel) {
+      self.flush()?;
+    }
+    self.summary.files += 1;
+    self.summary.bytes += data.len() as f64;
+    self.batch_bytes += data.len();
+    self.batch_paths.insert(rel.clone());
+    self.batch.push(PendingFile {
+      path: self.dest.join(rel),
+      data,
+      mode,
+      mtime,
+    });
+    if self.batch_bytes >= BATCH_BYTES {
+      self.flush()?;
+    }
+    Ok(())
+  }
+
+  /// Whether an entry of `size` bytes should be buffered with `file` rather than streamed.
+  pub(crate) fn buffers(&self, size: u64) -> bool {
+    self.pool.is_some() && size < BATCH_BYTES as u64
+  }
+
+  /// Writes files whose contents the archive can produce in any order (random-access
+  /// formats such as zip), spread over the pool. `init` builds per-thread state (e.g. a
+  /// cloned archive reader) and `write` copies the contents of `source` into the new file,
+  /// returning the number of bytes written. When a path occurs twice, the last entry wins.
+  pub(crate) fn files_with<T, S, I, W>(
+    &mut self,
+    files: Vec<(PathBuf, T, Option<u32>, Option<SystemTime>)>,
+    init: I,
+    write: W,
+  ) -> Result<()>
+  where
+    T: Send + Sync,
+    I: Fn() -> S + Send + Sync,
+    W: Fn(&mut S, &T, &mut fs::File) -> Result<u64> + Send + Sync,
+  {
+    self.flush()?;
+    let mut last = std::collections::HashMap::new();
+    for (i, (rel, ..)) in files.iter().enumerate() {
+      last.insert(rel.clone(), i);
+    }
+    let mut unique = Vec::with_capacity(last.len());
+    for (i, (rel, source, mode, mtime)) in files.into_iter().enumerate() {
+      if last.get(&rel) == Some(&i) {
+        self.ensure_parent(&rel)?;
+        unique.push((self.dest.join(rel), source, mode, mtime));
+      }
+    }
+
+    let write_one =
+      |state: &mut S,
+       (path, source, mode, mtime): &(PathBuf, T, Option<u32>, Option<SystemTime>)| {
+        let mut file = create_exclusive(path)?;
+        let written = write(state, source, &mut file)?;
+        finish_file(&file, path, *mode, *mtime)?;
+        Ok(written)
+      };
+    let written: Vec<u64> = match &self.pool {
+      Some(pool) => pool.install(|| {
+        unique
+          .par_iter()
+          .map_init(&init, write_one)
+          .collect::<Result<_>>()
+      })?,
+      None => {
+        let mut state = init();
+        unique
+          .iter()
+          .map(|file| write_one(&mut state, file))
+          .collect::<Result<_>>()?
+      }
+    };
+    self.summary.files += written.len() as u32;
+    self.summary.bytes += written.iter().sum::<u64>() as f64;
+    Ok(())
+  }
+
+  fn flush(&mut self) -> Result<()> {
+    let batch = std::mem::take(&mut self.batch);
+    self.batch_paths.clear();
+    self.batch_bytes = 0;
+    if let Some(pool) = &self.pool {
+      pool.install(|| batch.par_iter().try_for_each(write_pending))?;
+    }
+    Ok(())
+  }
+
+  /// Links `rel` to the file `target` as it is at this point of the archive, so a later entry
+  /// replacing `target` does not change it. Pending writes to either path are flushed first.
+  pub(crate) fn hardlink(&mut self, rel: PathBuf, target: PathBuf) -> Result<()> {
+    self.ensure_parent(&rel)?;
+    self.ensure_parent(&target)?;
+    if self.batch_paths.contains(&rel) || self.batch_paths.contains(&target) {
+      self.flush()?;
+    }
+    let path = self.dest.join(&rel);
+    remove_existing(&path)?;
+    fs::hard_link(self.dest.join(&target), &path).map_err(|e| uv_error(&e, "link", &path))?;
+    self.summary.files += 1;
+    if let Ok(meta) = fs::symlink_metadata(&path) {
+      self.summary.bytes += meta.len() as f64;
+    }
+    Ok(())
+  }
+
+  pub(crate) fn symlink(&mut self, rel: PathBuf, target: PathBuf) -> Result<()> {
+    if link_target_escapes(&rel, &target) {
+      return Err(self.escape_error(&rel.join(&target)));
+    }
+    self.symlinks.push((rel, target));
+    Ok(())
+  }
+
+  /// Whether `rel` lands outside the destination once every symlink on the way is followed,
+  /// like `realpath` would but without needing the final location to exist: a dangling link
+  /// pointing outside still escapes as soon as something is created through it.
+  fn resolves_outside(&self, rel: &Path) -> bool {
+    let mut pending: VecDeque<OsString> = rel
+      .components()
+      .map(|c| c.as_os_str().to_os_string())
+      .collect();
+    let mut resolved = PathBuf::new();
+    let mut links = 0;
+    while let Some(part) = pending.pop_front() {
+      match Path::new(&part).components().next() {
+        Some(Component::Normal(name)) => {
+          let path = self.dest.join(&resolved).join(name);
+          let target = match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.file_type().is_symlink() => fs::read_link(&path),
+            _ => {
+              resolved.push(name);
+              continue;
+            }
+          };
+          let Ok(mut target) = target else {
+            resolved.push(name);
+            continue;
+          };
+          links += 1;
+          if links > MAX_SYMLINKS {
+            // Too many levels for the kernel to follow either.
+            return false;
+          }
+          if target.is_absolute() {
+            match target.strip_prefix(&self.dest) {
+              Ok(inside) => target = inside.to_path_buf(),
+              Err(_) => return true,
+            }
+            resolved.clear();
+          }
+          for component in target.components().rev() {
+            pending.push_front(component.as_os_str().to_os_string());
+          }
+        }
+        Some(Component::ParentDir) => {
+          if !resolved.pop() {
+            return true;
+          }
+        }
+        Some(Component::CurDir) | None => {}
+        Some(Component::RootDir | Component::Prefix(_)) => return true,
+      }
+    }
+    false
+  }
+
+  /// Writes the remaining files, then symlinks, and finally applies the directory modes and
+  /// mtimes (deepest first, so read-only directories do not get in the way and child writes
+  /// do not bump the restored mtimes).
+  pub(crate) fn finish(mut self) -> Result<ArchiveSummary> {
+    self.flush()?;
+
+    let symlinks = std::mem::take(&mut self.symlinks);
+    for (rel, target) in &symlinks {
+      self.ensure_parent(rel)?;
+      let path = self.dest.join(rel);
+      remove_existing(&path)?;
+      create_symlink(target, &path).map_err(|e| uv_error(&e, "symlink", &path))?;
+      self.summary.symlinks += 1;
+    }
+    // Each target was checked on its own, but chained links (`a -> .`, `b -> a/a/../..`) can
+    // still add up to an escape; resolve them now that they all exist.
+    for (rel, _) in &symlinks {
+      if self.resolves_outside(rel) {
+        let _ = fs::remove_file(self.dest.join(rel));
+        return Err(self.escape_error(rel));
+      }
+    }
+
+    let mut dirs = std::mem::take(&mut self.dirs);
+    dirs.sort_by_key(|(rel, _, _)| std::cmp::Reverse(rel.components().count()));
+    for (rel, mode, mtime) in dirs {
+      let path = self.dest.join(&rel);
+      if let Some(mtime) = mtime {
+        let dir = open_dir_for_times(&path).map_err(|e| uv_error(&e, "open", &path))?;
+        dir
+          .set_modified(mtime)
+          .map_err(|e| uv_error(&e, "utime", &path))?;
+      }
+      set_mode(&path, mode)?;
+    }
+
+    Ok(self.summary)
+  }
+}
+
+fn write_pending(file: &PendingFile) -> Result<()> {
+  let mut out = create_exclusive(&file.path)?;
+  out
+    .write_all(&file.data)
+    .map_err(|e| uv_error(&e, "write", &file.path))?;
+  finish_file(&out, &file.path, file.mode, file.mtime)
+}
+
+/// Replaces whatever is at `path` (file or symlink) with a new file; `create_new` never
+/// follows a symlink that appears in between.
+fn create_exclusive(path: &Path) -> Result<fs::File> {
+  remove_existing(path)?;
+  fs::OpenOptions::new()
+    .write(true)
+    .create_new(true)
+    .open(path)
+    .map_err(|e| uv_error(&e, "open", path))
+}
+
+fn remove_existing(path: &Path) -> Result<()> {
+  match fs::symlink_metadata(path) {
+    Ok(meta) if meta.is_dir() => Err(Error::from_reason(format!(
+      "EISDIR: illegal operation on a directory, open '{}'",
+      path.to_string_lossy()
+    ))),
+    Ok(_) => fs::remove_file(path).map_err(|e| uv_error(&e, "unlink", path)),
+    Err(_) => Ok(()),
+  }
+}
+
+fn finish_file(
+  file: &fs::File,
+  path: &Path,
+  mode: Option<u32>,
+  mtime: Option<SystemTime>,
+) -> Result<()> {
+  if let Some(mtime) = mtime {
+    file
+      .set_modified(mtime)
+      .map_err(|e| uv_error(&e, "utime", path))?;
+  }
+  set_mode(path, mode)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: Option<u32>) -> Result<()> {
+  use std::os::unix::fs::PermissionsExt;
+  match mode {
+    // setuid / setgid / sticky bits from an archive are not trusted.
+    Some(mode) => fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777))
+      .map_err(|e| uv_error(&e, "chmod", path)),
+    None => Ok(()),
+  }
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: Option<u32>) -> Result<()> {
+  Ok(())
+}
+
+#[cfg(not(windows))]
+fn open_dir_for_times(path: &Path) -> io::Result<fs::File> {
+  fs::File::open(path)
+}
+
+/// Windows only opens directories with `FILE_FLAG_BACKUP_SEMANTICS`, and setting their times
+/// needs `FILE_WRITE_ATTRIBUTES`.
+#[cfg(windows)]
+fn open_dir_for_times(path: &Path) -> io::Result<fs::File> {
+  use std::os::windows::fs::OpenOptionsExt;
+  use windows_sys::Win32::Storage::FileSystem::{
+    FILE_FLAG_BACKUP_SEMANTICS, FILE_WRITE_ATTRIBUTES,
+  };
+  fs::OpenOptions::new()
+    .access_mode(FILE_WRITE_ATTRIBUTES)
+    .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+    .open(path)
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+  std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+  std::os::windows::fs::symlink_file(target, path)
+}
@@ -0,0 +1,107 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+
+// ========= exit cleanup =========
+
+/// A path to remove at exit. With `token` set, it is only removed while that is its content, so
+/// a lockfile taken over by another process is left alone.
+struct ExitEntry {
+  path: PathBuf,
+  token: Option<String>,
+}
+
+/// Paths to remove when the process exits (`removeOnExit`, lockfiles).
+static EXIT_CLEANUP: Mutex<Vec<ExitEntry>> = Mutex::new(Vec::new());
+static EXIT_HOOK: Once = Once::new();
+
+#[cfg(unix)]
+fn at_exit(cb: extern "C" fn()) {
+  unsafe { libc::atexit(cb) };
+}
+
+#[cfg(windows)]
+fn at_exit(cb: extern "C" fn()) {
+  extern "C" {
+    fn atexit(cb: extern "C" fn()) -> i32;
+  }
+  unsafe { atexit(cb) };
+}
+
+#[cfg(not(any(unix, windows)))]
+fn at_exit(_cb: extern "C" fn()) {}
+
+/// Runs from `atexit`, i.e. on a normal exit or `process.exit()` but not when the process is
+/// killed by a signal. Napi is gone by then, so this sticks to `std::fs`.
+extern "C" fn cleanup_on_exit() {
+  let entries = match EXIT_CLEANUP.lock() {
+    Ok(mut entries) => std::mem::take(&mut *entries),
+    Err(poisoned) => std::mem::take(&mut *poisoned.into_inner()),
+  };
+  for entry in entries.iter().rev() {
+    let meta = match fs::symlink_metadata(&entry.path) {
+      Ok(meta) => meta,
+      Err(_) => continue,
+    };
+    if let Some(token) = &entry.token {
+      if fs::read_to_string(&entry.path).ok().as_ref() != Some(token) {
+        continue;
+      }
+    }
+    let _ = if meta.is_dir() {
+      fs::remove_dir_all(&entry.path)
+    } else {
+      fs::remove_file(&entry.path)
+    };
+  }
+}
+
+/// Registers `path` to be removed when the process exits, if it is still there (and still holds
+/// `token`, when given).
+pub(crate) fn remove_on_exit(path: &Path, token: Option<&str>) {
+  EXIT_HOOK.call_once(|| at_exit(cleanup_on_exit));
+  if let Ok(mut entries) = EXIT_CLEANUP.lock() {
+    entries.push(ExitEntry {
+      path: path.to_path_buf(),
+      token: token.map(str::to_string),
+    });
+  }
+}
+
+pub(crate) fn forget_on_exit(path: &Path) {
+  if let Ok(mut entries) = EXIT_CLEANUP.lock() {
+    entries.retain(|entry| entry.path != path);
+  }
+}
+
+// ========= explicit resource management =========
+
+/// Installs `[Symbol.asyncDispose]` and `[Symbol.dispose]` on the prototypes of the handle
+/// classes, on runtimes that have them, so the handles work with `await using` / `using`.
+// Only called through the module registration, which unit test builds leave out.
+#[cfg_attr(test, allow(dead_code))]
+#[napi(module_exports)]
+pub fn init(exports: Object, env: Env) -> Result<()> {
+  // (class, async dispose method, sync dispose method)
+  const DISPOSABLE: &[(&str, &str, &str)] = &[
+    ("TempDir", "dispose", "disposeSync"),
+    ("FileLock", "release", "release"),
+    ("Lockfile", "release", "release"),
+  ];
+  let symbol: Object = env.get_global()?.get_named_property_unchecked("Symbol")?;
+  for (class, async_method, sync_method) in DISPOSABLE {
+    let class: Object = exports.get_named_property_unchecked(class)?;
+    let mut prototype: Object = class.get_named_property("prototype")?;
+    for (key, method) in [("asyncDispose", async_method), ("dispose", sync_method)] {
+      let key: Unknown = symbol.get_named_property(key)?;
+      if key.get_type()? != ValueType::Symbol {
+        continue;
+      }
+      let method: Unknown = prototype.get_named_property(method)?;
+      prototype.set_property(key, method)?;
+    }
+  }
+  Ok(())
+}
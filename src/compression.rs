@@ -0,0 +1,165 @@
+//! gzip / zstd / brotli codecs behind readFile's and writeFile's `compression` option.
+
+use napi::bindgen_prelude::*;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Codec {
+  Gzip,
+  Zstd,
+  Brotli,
+}
+
+/// The parsed `compression` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Compression {
+  Codec(Codec),
+  /// Pick the codec from the file extension (and, when reading, the magic bytes).
+  Auto,
+}
+
+impl Codec {
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Codec::Gzip => "gzip",
+      Codec::Zstd => "zstd",
+      Codec::Brotli => "brotli",
+    }
+  }
+
+  /// Whether two compressed payloads written back to back still decode as one stream, which is
+  /// what appending to a compressed file relies on.
+  pub(crate) fn concatenates(self) -> bool {
+    !matches!(self, Codec::Brotli)
+  }
+
+  fn from_extension(path: &Path) -> Option<Codec> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+      "gz" | "tgz" => Some(Codec::Gzip),
+      "zst" | "zstd" | "tzst" => Some(Codec::Zstd),
+      "br" => Some(Codec::Brotli),
+      _ => None,
+    }
+  }
+
+  /// Checks `level` against the codec's range; `None` picks the codec default (the same as
+  /// Node's zlib for gzip and brotli).
+  pub(crate) fn level(self, level: Option<i32>) -> Result<i32> {
+    let (min, max, default) = match self {
+      Codec::Gzip => (0, 9, 6),
+      Codec::Zstd => (
+        zstd::zstd_safe::min_c_level(),
+        zstd::zstd_safe::max_c_level(),
+        3,
+      ),
+      Codec::Brotli => (0, 11, 11),
+    };
+    match level {
+      None => Ok(default),
+      Some(level) if (min..=max).contains(&level) => Ok(level),
+      Some(level) => Err(Error::from_reason(format!(
+        "ERR_OUT_OF_RANGE: The value of \"options.level\" is out of range. It must be >= {} and <= {} for {}. Received {}",
+        min,
+        max,
+        self.name(),
+        level
+      ))),
+    }
+  }
+}
+
+pub(crate) fn parse_compression(name: Option<&str>) -> Result<Option<Compression>> {
+  match name {
+    None => Ok(None),
+    Some("gzip") => Ok(Some(Compression::Codec(Codec::Gzip))),
+    Some("zstd") => Ok(Some(Compression::Codec(Codec::Zstd))),
+    Some("brotli") => Ok(Some(Compression::Codec(Codec::Brotli))),
+    Some("auto") => Ok(Some(Compression::Auto)),
+    Some(other) => Err(Error::from_reason(format!(
+      "ERR_INVALID_ARG_VALUE: The property 'options.compression' must be one of 'gzip', 'zstd', 'brotli', 'auto'. Received '{}'",
+      other
+    ))),
+  }
+}
+
+/// The codec to write `path` with: `auto` goes by extension and leaves other files uncompressed.
+pub(crate) fn codec_for_write(compression: Option<Compression>, path: &Path) -> Option<Codec> {
+  match compression? {
+    Compression::Codec(codec) => Some(codec),
+    Compression::Auto => Codec::from_extension(path),
+  }
+}
+
+/// The codec to read `path` with. For `auto`, gzip and zstd are recognised by their magic
+/// bytes (peeked from `reader` without consuming them); brotli has none, so `.br` decides.
+pub(crate) fn codec_for_read(
+  compression: Option<Compression>,
+  path: &Path,
+  reader: &mut impl BufRead,
+) -> io::Result<Option<Codec>> {
+  match compression {
+    None => Ok(None),
+    Some(Compression::Codec(codec)) => Ok(Some(codec)),
+    Some(Compression::Auto) => {
+      let head = reader.fill_buf()?;
+      if head.starts_with(GZIP_MAGIC) {
+        Ok(Some(Codec::Gzip))
+      } else if head.starts_with(ZSTD_MAGIC) {
+        Ok(Some(Codec::Zstd))
+      } else {
+        Ok(Codec::from_extension(path).filter(|&codec| codec == Codec::Brotli))
+      }
+    }
+  }
+}
+
+/// Wraps `reader` in a streaming decoder. gzip members and zstd frames written back to back
+/// (e.g. by `appendFile`) decode as one stream.
+pub(crate) fn decoder<'a>(
+  codec: Codec,
+  reader: impl BufRead + 'a,
+) -> io::Result<Box<dyn Read + 'a>> {
+  Ok(match codec {
+    Codec::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(reader)),
+    Codec::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(reader)?),
+    Codec::Brotli => Box::new(brotli::Decompressor::new(reader, 64 * 1024)),
+  })
+}
+
+/// Compresses `data` in one go. `level` must have been checked with `Codec::level`.
+pub(crate) fn compress(codec: Codec, data: &[u8], level: i32) -> io::Result<Vec<u8>> {
+  match codec {
+    Codec::Gzip => {
+      let mut encoder = flate2::write::GzEncoder::new(
+        Vec::with_capacity(data.len() / 2),
+        flate2::Compression::new(level as u32),
+      );
+      encoder.write_all(data)?;
+      encoder.finish()
+    }
+    Codec::Zstd => zstd::bulk::compress(data, level),
+    Codec::Brotli => {
+      let mut out = Vec::with_capacity(data.len() / 2);
+      {
+        let mut encoder = brotli::CompressorWriter::new(&mut out, 64 * 1024, level as u32, 22);
+        encoder.write_all(data)?;
+      }
+      Ok(out)
+    }
+  }
+}
+
+/// Error for a payload the codec could not decode, in the shape of Node's zlib errors.
+pub(crate) fn decompress_error(codec: Codec, e: io::Error, path: &str) -> Error {
+  Error::from_reason(format!(
+    "Z_DATA_ERROR: invalid {} data ({}), read '{}'",
+    codec.name(),
+    e,
+    path
+  ))
+}
@@ -1,9 +1,16 @@
+use crate::dry_run::{DryRunPlan, Planner};
+use crate::on_error::{aggregate_error, ErrorPolicy, PathError};
+use crate::utils::{errno_name, uv_error};
+use crate::xattr;
 use napi::bindgen_prelude::*;
 use napi::Task;
 use napi_derive::napi;
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
 #[napi(object)]
 #[derive(Clone)]
@@ -17,6 +24,152 @@ pub struct CpOptions {
   /// Rush-FS extension: number of parallel threads for recursive copy.
   /// 0 or 1 means sequential; > 1 enables rayon parallel traversal.
   pub concurrency: Option<u32>,
+  /// Rush-FS extension: incremental sync. Existing destination files that are already
+  /// up to date are skipped instead of being overwritten (or skipped wholesale with `force: false`).
+  /// - `'newer'`: copy only when the source mtime is newer than the destination's.
+  /// - `'size-mtime'`: copy unless size and mtime (whole seconds, like rsync) both match.
+  /// - `'checksum'`: copy unless size and content both match.
+  ///
+  /// Copied files always receive the source timestamps so later runs can skip them.
+  pub update: Option<String>,
+  /// Rush-FS extension: delete destination entries that do not exist in the source. Entries of
+  /// another type (a file where the source has a directory, or the other way round) still fail.
+  pub mirror: Option<bool>,
+  /// Rush-FS extension: metadata to carry over from the source. See `CpPreserveOptions`.
+  pub preserve: Option<CpPreserveOptions>,
+  /// Rush-FS extension: recreate hard links between source files as hard links in the
+  /// destination instead of writing one full copy per link (Unix only).
+  pub preserve_hardlinks: Option<bool>,
+  /// Rush-FS extension: write nothing and return the `DryRunPlan` (destination paths that would
+  /// be created, overwritten, skipped or, with `mirror`, removed) instead.
+  pub dry_run: Option<bool>,
+  /// Rush-FS extension: what to do when an entry inside a directory cannot be copied (or, with
+  /// `mirror`, removed). `'throw'` (default) stops at the first error, `'skip'` carries on with
+  /// the other entries, `'collect'` does the same and then throws an `AggregateError` of every
+  /// failure. Errors on `src` / `dest` themselves always throw.
+  #[napi(ts_type = "'throw' | 'skip' | 'collect'")]
+  pub on_error: Option<String>,
+}
+
+/// Metadata preserved by `cp` (all default to false).
+///
+/// - `mode`: copy permission bits, including directories (applied after their children are written).
+/// - `ownership`: copy uid/gid with `lchown`. Silently skipped when the process lacks the privilege.
+/// - `xattrs`: copy extended attributes (Linux and macOS).
+/// - `timestamps`: same as `preserveTimestamps`.
+#[napi(object)]
+#[derive(Clone)]
+pub struct CpPreserveOptions {
+  pub mode: Option<bool>,
+  pub ownership: Option<bool>,
+  pub xattrs: Option<bool>,
+  pub timestamps: Option<bool>,
+}
+
+/// Summary returned by `cp` when `update` or `mirror` is set.
+#[napi(object)]
+pub struct CpSummary {
+  pub copied: u32,
+  pub skipped: u32,
+  pub deleted: u32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum UpdateMode {
+  Newer,
+  SizeMtime,
+  Checksum,
+}
+
+fn parse_update_mode(update: Option<&str>) -> Result<Option<UpdateMode>> {
+  match update {
+    None => Ok(None),
+    Some("newer") => Ok(Some(UpdateMode::Newer)),
+    Some("size-mtime") => Ok(Some(UpdateMode::SizeMtime)),
+    Some("checksum") => Ok(Some(UpdateMode::Checksum)),
+    Some(other) => Err(Error::from_reason(format!(
+      "ERR_INVALID_ARG_VALUE: The property 'options.update' must be one of 'newer', 'size-mtime', 'checksum'. Received '{}'",
+      other
+    ))),
+  }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Preserve {
+  mode: bool,
+  ownership: bool,
+  xattrs: bool,
+  timestamps: bool,
+}
+
+/// First destination written for a source inode, `None` when copying it failed. Threads that
+/// meet the same inode while it is still being copied block in `get_or_init` until the copy has
+/// finished.
+type LinkCell = Arc<OnceLock<Option<PathBuf>>>;
+
+/// Per-call state shared by every level of the (possibly parallel) recursion.
+struct CpContext {
+  update: Option<UpdateMode>,
+  mirror: bool,
+  preserve: Preserve,
+  /// `(dev, ino)` of multiply-linked source files; `None` unless `preserveHardlinks` is set.
+  hardlinks: Option<Mutex<HashMap<(u64, u64), LinkCell>>>,
+  /// Set by `dryRun`: every write is recorded here instead of performed.
+  plan: Option<Planner>,
+  on_error: ErrorPolicy,
+  copied: AtomicU32,
+  skipped: AtomicU32,
+  deleted: AtomicU32,
+}
+
+impl CpContext {
+  fn summary(&self) -> CpSummary {
+    CpSummary {
+      copied: self.copied.load(Ordering::Relaxed),
+      skipped: self.skipped.load(Ordering::Relaxed),
+      deleted: self.deleted.load(Ordering::Relaxed),
+    }
+  }
+}
+
+/// Why copying an entry failed. OS errors stay `io::Error`s on their way up, so `onError` can
+/// tell them apart without parsing messages.
+enum CpError {
+  Io(std::io::Error),
+  /// An error already formatted with its code (`ERR_FS_CP_*`, or `EEXIST` / `ENOENT` naming
+  /// both paths).
+  Coded(&'static str, Error),
+}
+
+impl CpError {
+  fn coded(code: &'static str, message: String) -> Self {
+    CpError::Coded(code, Error::from_reason(format!("{}: {}", code, message)))
+  }
+
+  /// Applies `onError` to the failure of the child `path`.
+  fn check(self, policy: &ErrorPolicy, path: &Path) -> std::result::Result<(), CpError> {
+    match self {
+      CpError::Io(e) => policy.check(e, "cp", path).map_err(CpError::Io),
+      CpError::Coded(code, e) => policy
+        .check_coded(code, e, "cp", path)
+        .map_err(|e| CpError::Coded(code, e)),
+    }
+  }
+}
+
+impl From<std::io::Error> for CpError {
+  fn from(e: std::io::Error) -> Self {
+    CpError::Io(e)
+  }
+}
+
+impl From<CpError> for Error {
+  fn from(e: CpError) -> Self {
+    match e {
+      CpError::Io(e) => Error::from_reason(e.to_string()),
+      CpError::Coded(_, e) => e,
+    }
+  }
 }
 
 #[cfg(unix)]
@@ -51,11 +204,228 @@ fn set_timestamps(_src: &Path, _dest: &Path) -> std::io::Result<()> {
   Ok(())
 }
 
-fn cp_impl(src: &Path, dest: &Path, opts: &CpOptions) -> Result<()> {
+#[cfg(unix)]
+fn set_ownership(src_meta: &fs::Metadata, dest: &Path) -> std::io::Result<()> {
+  use std::os::unix::ffi::OsStrExt;
+  use std::os::unix::fs::MetadataExt;
+  let c_path = std::ffi::CString::new(dest.as_os_str().as_bytes())
+    .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid path"))?;
+  let ret = unsafe { libc::lchown(c_path.as_ptr(), src_meta.uid(), src_meta.gid()) };
+  if ret != 0 {
+    let e = std::io::Error::last_os_error();
+    // Like `cp -p`, unprivileged callers keep their own ownership instead of failing.
+    if e.raw_os_error() == Some(libc::EPERM) && unsafe { libc::geteuid() } != 0 {
+      return Ok(());
+    }
+    return Err(e);
+  }
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_ownership(_src_meta: &fs::Metadata, _dest: &Path) -> std::io::Result<()> {
+  Ok(())
+}
+
+fn copy_xattrs(src: &Path, dest: &Path) -> std::io::Result<()> {
+  let names = match xattr::list_raw(src, false) {
+    Ok(names) => names,
+    Err(e) if xattr::is_unsupported(&e) => return Ok(()),
+    Err(e) => return Err(e),
+  };
+  for name in names {
+    // The attribute may vanish between list and get; nothing to copy then.
+    let Ok(value) = xattr::get_raw(src, &name, false) else {
+      continue;
+    };
+    if let Err(e) = xattr::set_raw(dest, &name, &value, false, false, false) {
+      // Destination filesystem without xattr support, or a privileged namespace
+      // (security.*, trusted.*) we may not write: skip like `cp --preserve=xattr`.
+      #[cfg(unix)]
+      if e.raw_os_error() == Some(libc::EPERM) {
+        continue;
+      }
+      if xattr::is_unsupported(&e) {
+        continue;
+      }
+      return Err(e);
+    }
+  }
+  Ok(())
+}
+
+/// Applies the requested `preserve` attributes to `dest`. Ownership is set before the mode
+/// because `chown` clears setuid/setgid bits; timestamps go last since every other step
+/// bumps ctime and may touch mtime.
+fn apply_preserve(
+  src: &Path,
+  src_meta: &fs::Metadata,
+  dest: &Path,
+  preserve: &Preserve,
+) -> std::io::Result<()> {
+  if preserve.xattrs {
+    copy_xattrs(src, dest)?;
+  }
+  if preserve.ownership {
+    set_ownership(src_meta, dest)?;
+  }
+  if preserve.mode && !src_meta.is_symlink() {
+    fs::set_permissions(dest, src_meta.permissions())?;
+  }
+  if preserve.timestamps && !src_meta.is_symlink() {
+    set_timestamps(src, dest)?;
+  }
+  Ok(())
+}
+
+/// Whether `a` and `b` hold the same bytes. Their sizes must already be known to match.
+pub(crate) fn files_equal(a: &Path, b: &Path) -> std::io::Result<bool> {
+  use std::io::Read;
+  let mut fa = fs::File::open(a)?;
+  let mut fb = fs::File::open(b)?;
+  let mut buf_a = vec![0u8; 64 * 1024];
+  let mut buf_b = vec![0u8; 64 * 1024];
+  loop {
+    let n = fa.read(&mut buf_a)?;
+    if n == 0 {
+      // Sizes were compared beforehand, so both files end here.
+      return Ok(true);
+    }
+    fb.read_exact(&mut buf_b[..n])?;
+    if buf_a[..n] != buf_b[..n] {
+      return Ok(false);
+    }
+  }
+}
+
+/// Whether the regular file at `dest` already matches `src`. A symlink at `dest` never does, even
+/// when it points at an identical file.
+fn is_up_to_date(src: &Path, src_meta: &fs::Metadata, dest: &Path, mode: UpdateMode) -> bool {
+  let dest_meta = match fs::symlink_metadata(dest) {
+    Ok(m) if m.is_file() => m,
+    _ => return false,
+  };
+  let secs = |m: &fs::Metadata| {
+    m.modified()
+      .ok()
+      .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+      .map(|d| d.as_secs())
+  };
+  match mode {
+    UpdateMode::Newer => match (src_meta.modified(), dest_meta.modified()) {
+      (Ok(s), Ok(d)) => d >= s,
+      _ => false,
+    },
+    UpdateMode::SizeMtime => {
+      src_meta.len() == dest_meta.len()
+        && secs(src_meta).is_some()
+        && secs(src_meta) == secs(&dest_meta)
+    }
+    UpdateMode::Checksum => {
+      src_meta.len() == dest_meta.len() && files_equal(src, dest).unwrap_or(false)
+    }
+  }
+}
+
+#[cfg(unix)]
+fn hardlink_key(meta: &fs::Metadata) -> Option<(u64, u64)> {
+  use std::os::unix::fs::MetadataExt;
+  (meta.nlink() > 1).then(|| (meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn hardlink_key(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+  None
+}
+
+/// Deletes the destination entry `path`, which the source does not have, like `rm` does: through
+/// directory fds without following symlinks (see `openat::remove_at_with`), or into the dry-run
+/// plan. Failures below `path` go through `onError`. Returns whether `path` is gone.
+#[cfg(unix)]
+fn remove_extraneous(path: &Path, parallel: bool, ctx: &CpContext) -> std::io::Result<bool> {
+  use crate::openat;
+  use std::os::fd::AsFd;
+
+  let parent = match path.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  };
+  let dir = openat::open_dir(parent)?;
+  let name = openat::cstring(path.file_name().unwrap_or_default())?;
+  let hook = |e: std::io::Error, failed: &Path| {
+    if failed == path {
+      return Err(e);
+    }
+    ctx.on_error.check(e, "rm", failed)
+  };
+  let removal = openat::Removal {
+    parallel,
+    plan: ctx.plan.as_ref(),
+    removed: Some(&ctx.deleted),
+    hook: &hook,
+  };
+  openat::remove_at_with(dir.as_fd(), &name, path, true, &removal)
+}
+
+#[cfg(not(unix))]
+fn remove_extraneous(path: &Path, parallel: bool, ctx: &CpContext) -> std::io::Result<bool> {
+  let meta = fs::symlink_metadata(path)?;
+  let mut emptied = true;
+  if meta.is_dir() {
+    for entry in fs::read_dir(path)? {
+      let child = entry?.path();
+      emptied &= remove_extraneous(&child, parallel, ctx)
+        .or_else(|e| ctx.on_error.check(e, "rm", &child).map(|_| false))?;
+    }
+  }
+  if !emptied {
+    return Ok(false);
+  }
+  match &ctx.plan {
+    Some(plan) => plan.remove(path, 0),
+    None => {
+      if meta.is_dir() {
+        fs::remove_dir(path)?;
+      } else {
+        fs::remove_file(path)?;
+      }
+      ctx.deleted.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+  Ok(true)
+}
+
+fn non_dir_to_dir(src: &Path, dest: &Path) -> CpError {
+  CpError::coded(
+    "ERR_FS_CP_NON_DIR_TO_DIR",
+    format!(
+      "Cannot overwrite non-directory with directory, cp '{}' -> '{}'",
+      src.to_string_lossy(),
+      dest.to_string_lossy()
+    ),
+  )
+}
+
+/// Dry run: records `dir` and its missing ancestors as created, like `create_dir_all` would.
+fn plan_create_dir_all(dir: &Path, plan: &Planner) {
+  plan.create(dir, 0);
+  for ancestor in dir.ancestors().skip(1) {
+    if ancestor.as_os_str().is_empty() || ancestor.symlink_metadata().is_ok() {
+      break;
+    }
+    plan.create(ancestor, 0);
+  }
+}
+
+fn cp_impl(
+  src: &Path,
+  dest: &Path,
+  opts: &CpOptions,
+  ctx: &CpContext,
+) -> std::result::Result<(), CpError> {
   let force = opts.force.unwrap_or(true);
   let error_on_exist = opts.error_on_exist.unwrap_or(false);
   let recursive = opts.recursive.unwrap_or(false);
-  let preserve_timestamps = opts.preserve_timestamps.unwrap_or(false);
   let dereference = opts.dereference.unwrap_or(false);
   let verbatim_symlinks = opts.verbatim_symlinks.unwrap_or(false);
   let concurrency = opts.concurrency.unwrap_or(0);
@@ -68,18 +438,21 @@ fn cp_impl(src: &Path, dest: &Path, opts: &CpOptions) -> Result<()> {
 
   let meta = meta.map_err(|e| {
     if e.kind() == std::io::ErrorKind::NotFound {
-      Error::from_reason(format!(
-        "ENOENT: no such file or directory, cp '{}' -> '{}'",
-        src.to_string_lossy(),
-        dest.to_string_lossy()
-      ))
+      CpError::coded(
+        "ENOENT",
+        format!(
+          "no such file or directory, cp '{}' -> '{}'",
+          src.to_string_lossy(),
+          dest.to_string_lossy()
+        ),
+      )
     } else {
-      Error::from_reason(e.to_string())
+      CpError::Io(e)
     }
   })?;
 
   if meta.is_symlink() && !dereference {
-    let target = fs::read_link(src).map_err(|e| Error::from_reason(e.to_string()))?;
+    let target = fs::read_link(src)?;
 
     let link_target = if verbatim_symlinks {
       target
@@ -94,99 +467,251 @@ fn cp_impl(src: &Path, dest: &Path, opts: &CpOptions) -> Result<()> {
       target.canonicalize().unwrap_or(target)
     };
 
+    let mut replaces = false;
     if dest.exists() || dest.symlink_metadata().is_ok() {
+      if ctx.update.is_some() && fs::read_link(dest).is_ok_and(|t| t == link_target) {
+        ctx.skipped.fetch_add(1, Ordering::Relaxed);
+        if let Some(plan) = &ctx.plan {
+          plan.skip(dest);
+        }
+        return Ok(());
+      }
       if error_on_exist {
-        return Err(Error::from_reason(format!(
-          "EEXIST: file already exists, cp '{}' -> '{}'",
-          src.to_string_lossy(),
-          dest.to_string_lossy()
-        )));
+        return Err(CpError::coded(
+          "EEXIST",
+          format!(
+            "file already exists, cp '{}' -> '{}'",
+            src.to_string_lossy(),
+            dest.to_string_lossy()
+          ),
+        ));
       }
-      if force {
-        let _ = fs::remove_file(dest);
+      if dest.symlink_metadata().is_ok_and(|m| m.is_dir()) {
+        return Err(non_dir_to_dir(src, dest));
+      }
+      if force || ctx.update.is_some() {
+        replaces = true;
+        if ctx.plan.is_none() {
+          let _ = fs::remove_file(dest);
+        }
       } else {
+        ctx.skipped.fetch_add(1, Ordering::Relaxed);
+        if let Some(plan) = &ctx.plan {
+          plan.skip(dest);
+        }
         return Ok(());
       }
     }
 
+    if let Some(plan) = &ctx.plan {
+      if replaces {
+        plan.overwrite(dest, 0);
+      } else {
+        plan.create(dest, 0);
+      }
+      return Ok(());
+    }
+
     #[cfg(unix)]
-    std::os::unix::fs::symlink(&link_target, dest)
-      .map_err(|e| Error::from_reason(e.to_string()))?;
+    std::os::unix::fs::symlink(&link_target, dest)?;
     #[cfg(windows)]
     {
       if link_target.is_dir() {
-        std::os::windows::fs::symlink_dir(&link_target, dest)
-          .map_err(|e| Error::from_reason(e.to_string()))?;
+        std::os::windows::fs::symlink_dir(&link_target, dest)?;
       } else {
-        std::os::windows::fs::symlink_file(&link_target, dest)
-          .map_err(|e| Error::from_reason(e.to_string()))?;
+        std::os::windows::fs::symlink_file(&link_target, dest)?;
       }
     }
+    ctx.copied.fetch_add(1, Ordering::Relaxed);
+    // Only ownership and xattrs apply to the link itself; mode and times would follow it.
+    let link_preserve = Preserve {
+      mode: false,
+      timestamps: false,
+      ..ctx.preserve
+    };
+    apply_preserve(src, &meta, dest, &link_preserve)?;
     return Ok(());
   }
 
   if meta.is_dir() {
     if !recursive {
-      return Err(Error::from_reason(format!(
-        "ERR_FS_EISDIR: Path is a directory. To copy a directory set the 'recursive' option to true, cp '{}' -> '{}'",
-        src.to_string_lossy(),
-        dest.to_string_lossy()
-      )));
+      return Err(CpError::coded(
+        "ERR_FS_EISDIR",
+        format!(
+          "Path is a directory. To copy a directory set the 'recursive' option to true, cp '{}' -> '{}'",
+          src.to_string_lossy(),
+          dest.to_string_lossy()
+        ),
+      ));
+    }
+
+    let exists = dest.exists();
+    if exists && !dest.is_dir() {
+      return Err(CpError::coded(
+        "ERR_FS_CP_DIR_TO_NON_DIR",
+        format!(
+          "Cannot overwrite directory with non-directory, cp '{}' -> '{}'",
+          src.to_string_lossy(),
+          dest.to_string_lossy()
+        ),
+      ));
     }
 
-    if !dest.exists() {
-      fs::create_dir_all(dest).map_err(|e| Error::from_reason(e.to_string()))?;
+    if !exists {
+      match &ctx.plan {
+        Some(plan) => plan_create_dir_all(dest, plan),
+        None => fs::create_dir_all(dest)?,
+      }
     }
 
-    let entries: Vec<_> = fs::read_dir(src)
-      .map_err(|e| Error::from_reason(e.to_string()))?
-      .collect::<std::io::Result<_>>()
-      .map_err(|e| Error::from_reason(e.to_string()))?;
+    let entries: Vec<_> = fs::read_dir(src)?.collect::<std::io::Result<_>>()?;
 
+    let copy_child = |entry: &fs::DirEntry| -> std::result::Result<(), CpError> {
+      let child = entry.path();
+      cp_impl(&child, &dest.join(entry.file_name()), opts, ctx)
+        .or_else(|e| e.check(&ctx.on_error, &child))
+    };
     if concurrency > 1 {
-      entries.par_iter().try_for_each(|entry| -> Result<()> {
-        cp_impl(&entry.path(), &dest.join(entry.file_name()), opts)
-      })?;
+      entries.par_iter().try_for_each(copy_child)?;
     } else {
-      for entry in &entries {
-        cp_impl(&entry.path(), &dest.join(entry.file_name()), opts)?;
+      entries.iter().try_for_each(copy_child)?;
+    }
+
+    // In a dry run the destination may not exist yet (nor be a directory).
+    if ctx.mirror && dest.is_dir() {
+      let keep: HashSet<_> = entries.iter().map(|e| e.file_name()).collect();
+      for entry in fs::read_dir(dest)? {
+        let entry = entry?;
+        if !keep.contains(&entry.file_name()) {
+          let extraneous = entry.path();
+          remove_extraneous(&extraneous, concurrency > 1, ctx)
+            .map(drop)
+            .or_else(|e| ctx.on_error.check(e, "rm", &extraneous))
+            .map_err(|e| {
+              let code = errno_name(&e).map_or("EIO", |(code, _)| code);
+              CpError::Coded(code, uv_error(&e, "rm", &extraneous))
+            })?;
+        }
       }
     }
 
-    if preserve_timestamps {
-      set_timestamps(src, dest).map_err(|e| Error::from_reason(e.to_string()))?;
+    // Applied after the children so a read-only source directory can still be filled.
+    if ctx.plan.is_none() {
+      apply_preserve(src, &meta, dest, &ctx.preserve)?;
     }
   } else {
+    if let Some(mode) = ctx.update {
+      if is_up_to_date(src, &meta, dest, mode) {
+        ctx.skipped.fetch_add(1, Ordering::Relaxed);
+        if let Some(plan) = &ctx.plan {
+          plan.skip(dest);
+        }
+        return Ok(());
+      }
+    }
+
+    let mut replaces = false;
     if dest.exists() {
       if error_on_exist {
-        return Err(Error::from_reason(format!(
-          "EEXIST: file already exists, cp '{}' -> '{}'",
-          src.to_string_lossy(),
-          dest.to_string_lossy()
-        )));
+        return Err(CpError::coded(
+          "EEXIST",
+          format!(
+            "file already exists, cp '{}' -> '{}'",
+            src.to_string_lossy(),
+            dest.to_string_lossy()
+          ),
+        ));
       }
-      if !force {
+      if dest.symlink_metadata().is_ok_and(|m| m.is_dir()) {
+        return Err(non_dir_to_dir(src, dest));
+      }
+      if !force && ctx.update.is_none() {
+        ctx.skipped.fetch_add(1, Ordering::Relaxed);
+        if let Some(plan) = &ctx.plan {
+          plan.skip(dest);
+        }
         return Ok(());
+      } else {
+        replaces = true;
       }
     }
 
+    if let Some(plan) = &ctx.plan {
+      if let Some(parent) = dest.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+          plan_create_dir_all(parent, plan);
+        }
+      }
+      if replaces {
+        plan.overwrite(dest, meta.len());
+      } else {
+        plan.create(dest, meta.len());
+      }
+      return Ok(());
+    }
+
     if let Some(parent) = dest.parent() {
       if !parent.exists() {
-        fs::create_dir_all(parent).map_err(|e| Error::from_reason(e.to_string()))?;
+        fs::create_dir_all(parent)?;
       }
     }
+    // Syncs replace a symlink at `dest` (never up to date, see `is_up_to_date`) with the file
+    // instead of writing through it.
+    if (ctx.update.is_some() || ctx.mirror) && dest.symlink_metadata().is_ok_and(|m| m.is_symlink())
+    {
+      fs::remove_file(dest)?;
+    }
 
-    fs::copy(src, dest).map_err(|e| Error::from_reason(e.to_string()))?;
+    let file_preserve = Preserve {
+      timestamps: ctx.preserve.timestamps || ctx.update.is_some(),
+      ..ctx.preserve
+    };
+    let copy_file = || -> std::io::Result<()> {
+      fs::copy(src, dest)?;
+      apply_preserve(src, &meta, dest, &file_preserve)
+    };
 
-    if preserve_timestamps {
-      set_timestamps(src, dest).map_err(|e| Error::from_reason(e.to_string()))?;
+    let cell = match (&ctx.hardlinks, hardlink_key(&meta)) {
+      (Some(map), Some(key)) => Some(map.lock().unwrap().entry(key).or_default().clone()),
+      _ => None,
+    };
+
+    match cell {
+      Some(cell) => {
+        // `Some` when this thread made the first copy.
+        let mut outcome = None;
+        let first = cell.get_or_init(|| {
+          let result = copy_file();
+          let first = result.is_ok().then(|| dest.to_path_buf());
+          outcome = Some(result);
+          first
+        });
+        match (outcome, first) {
+          (Some(result), _) => result?,
+          (None, Some(first_dest)) => {
+            if dest.symlink_metadata().is_ok() {
+              fs::remove_file(dest)?;
+            }
+            fs::hard_link(first_dest, dest)?;
+          }
+          // The first occurrence failed and reported its error; copy this one on its own.
+          (None, None) => copy_file()?,
+        }
+      }
+      None => copy_file()?,
     }
+    ctx.copied.fetch_add(1, Ordering::Relaxed);
   }
 
   Ok(())
 }
 
-fn cp_entry(src_str: String, dest_str: String, options: Option<CpOptions>) -> Result<()> {
+fn cp_entry(
+  src_str: String,
+  dest_str: String,
+  options: Option<CpOptions>,
+  errors: &mut Vec<PathError>,
+) -> Result<Either3<Undefined, CpSummary, DryRunPlan>> {
   let src = Path::new(&src_str);
   let dest = Path::new(&dest_str);
   let opts = options.unwrap_or(CpOptions {
@@ -197,13 +722,66 @@ fn cp_entry(src_str: String, dest_str: String, options: Option<CpOptions>) -> Re
     dereference: None,
     verbatim_symlinks: None,
     concurrency: None,
+    update: None,
+    mirror: None,
+    preserve: None,
+    preserve_hardlinks: None,
+    dry_run: None,
+    on_error: None,
   });
-  cp_impl(src, dest, &opts)
+  let preserve = match &opts.preserve {
+    Some(p) => Preserve {
+      mode: p.mode.unwrap_or(false),
+      ownership: p.ownership.unwrap_or(false),
+      xattrs: p.xattrs.unwrap_or(false),
+      timestamps: p.timestamps.unwrap_or(false),
+    },
+    None => Preserve::default(),
+  };
+  let ctx = CpContext {
+    update: parse_update_mode(opts.update.as_deref())?,
+    mirror: opts.mirror.unwrap_or(false),
+    preserve: Preserve {
+      timestamps: preserve.timestamps || opts.preserve_timestamps.unwrap_or(false),
+      ..preserve
+    },
+    hardlinks: opts
+      .preserve_hardlinks
+      .unwrap_or(false)
+      .then(|| Mutex::new(HashMap::new())),
+    plan: opts.dry_run.unwrap_or(false).then(Planner::for_copy),
+    on_error: ErrorPolicy::new(opts.on_error.as_deref())?,
+    copied: AtomicU32::new(0),
+    skipped: AtomicU32::new(0),
+    deleted: AtomicU32::new(0),
+  };
+  // Plain `cp` returns undefined like Node.js; sync modes report what they did.
+  let result = cp_impl(src, dest, &opts, &ctx)
+    .map_err(Error::from)
+    .map(|_| {
+      if ctx.update.is_some() || ctx.mirror {
+        Either3::B(ctx.summary())
+      } else {
+        Either3::A(())
+      }
+    });
+  let CpContext { plan, on_error, .. } = ctx;
+  let result = result.map(|summary| match plan {
+    Some(plan) => Either3::C(plan.into_plan()),
+    None => summary,
+  });
+  on_error.finish(result, "cp", &src_str, errors)
 }
 
 #[napi(js_name = "cpSync")]
-pub fn cp_sync(src: String, dest: String, options: Option<CpOptions>) -> Result<()> {
-  cp_entry(src, dest, options)
+pub fn cp_sync(
+  env: Env,
+  src: String,
+  dest: String,
+  options: Option<CpOptions>,
+) -> Result<Either3<Undefined, CpSummary, DryRunPlan>> {
+  let mut errors = Vec::new();
+  cp_entry(src, dest, options, &mut errors).map_err(|e| aggregate_error(&env, e, errors))
 }
 
 // ========= async version =========
@@ -212,22 +790,37 @@ pub struct CpTask {
   pub src: String,
   pub dest: String,
   pub options: Option<CpOptions>,
+  errors: Vec<PathError>,
 }
 
 impl Task for CpTask {
-  type Output = ();
-  type JsValue = ();
+  type Output = Either3<Undefined, CpSummary, DryRunPlan>;
+  type JsValue = Either3<Undefined, CpSummary, DryRunPlan>;
 
   fn compute(&mut self) -> Result<Self::Output> {
-    cp_entry(self.src.clone(), self.dest.clone(), self.options.clone())
+    cp_entry(
+      self.src.clone(),
+      self.dest.clone(),
+      self.options.clone(),
+      &mut self.errors,
+    )
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
   }
 
-  fn resolve(&mut self, _env: Env, _output: Self::Output) -> Result<Self::JsValue> {
-    Ok(())
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    Err(aggregate_error(&env, err, std::mem::take(&mut self.errors)))
   }
 }
 
 #[napi(js_name = "cp")]
 pub fn cp(src: String, dest: String, options: Option<CpOptions>) -> AsyncTask<CpTask> {
-  AsyncTask::new(CpTask { src, dest, options })
+  AsyncTask::new(CpTask {
+    src,
+    dest,
+    options,
+    errors: Vec::new(),
+  })
 }
@@ -0,0 +1,93 @@
+use crate::utils::uv_error;
+use chardetng::EncodingDetector;
+use encoding_rs::UTF_8;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes sampled from the start of the file for heuristic detection.
+const SAMPLE_LEN: u64 = 1024 * 1024;
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct DetectedEncoding {
+  /// WHATWG name of the encoding (e.g. `'UTF-8'`, `'UTF-16LE'`, `'Shift_JIS'`, `'GBK'`), usable as
+  /// `readFile`'s `encoding`.
+  pub encoding: String,
+  /// Whether the file starts with a byte order mark.
+  pub bom: bool,
+  /// False when the guess did not clearly beat other candidates (or there was nothing to go on,
+  /// such as an empty or pure ASCII file).
+  pub confident: bool,
+}
+
+fn detect(sample: &[u8], complete: bool) -> DetectedEncoding {
+  if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(sample) {
+    return DetectedEncoding {
+      encoding: encoding.name().to_string(),
+      bom: true,
+      confident: true,
+    };
+  }
+  if sample.is_ascii() {
+    return DetectedEncoding {
+      encoding: UTF_8.name().to_string(),
+      bom: false,
+      confident: false,
+    };
+  }
+  let mut detector = EncodingDetector::new();
+  detector.feed(sample, complete);
+  let (encoding, confident) = detector.guess_assess(None, true);
+  DetectedEncoding {
+    encoding: encoding.name().to_string(),
+    bom: false,
+    confident,
+  }
+}
+
+fn detect_encoding_impl(path: String) -> Result<DetectedEncoding> {
+  let path = Path::new(&path);
+  let file = fs::File::open(path).map_err(|e| uv_error(&e, "open", path))?;
+  let mut sample = Vec::new();
+  file
+    .take(SAMPLE_LEN + 1)
+    .read_to_end(&mut sample)
+    .map_err(|e| uv_error(&e, "read", path))?;
+  // Reading one byte past the sample tells whether it holds the whole file.
+  let complete = sample.len() as u64 <= SAMPLE_LEN;
+  sample.truncate(SAMPLE_LEN as usize);
+  Ok(detect(&sample, complete))
+}
+
+#[napi(js_name = "detectEncodingSync")]
+pub fn detect_encoding_sync(path: String) -> Result<DetectedEncoding> {
+  detect_encoding_impl(path)
+}
+
+// ========= async version =========
+
+pub struct DetectEncodingTask {
+  pub path: String,
+}
+
+impl Task for DetectEncodingTask {
+  type Output = DetectedEncoding;
+  type JsValue = DetectedEncoding;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    detect_encoding_impl(self.path.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "detectEncoding")]
+pub fn detect_encoding(path: String) -> AsyncTask<DetectEncodingTask> {
+  AsyncTask::new(DetectEncodingTask { path })
+}
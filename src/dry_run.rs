@@ -0,0 +1,104 @@
+use napi_derive::napi;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// What `rm` / `cp` would do with `dryRun: true`. Every list is sorted; `cp` reports destination
+/// paths.
+#[napi(object)]
+pub struct DryRunPlan {
+  /// Paths that would be deleted: files, symlinks and directories (with their contents listed too).
+  pub removed: Vec<String>,
+  /// Paths that would be created, including missing parent directories.
+  pub created: Vec<String>,
+  /// Existing paths that would be replaced in place.
+  pub overwritten: Vec<String>,
+  /// Paths left alone: already up to date (`update`), or existing with `force: false`.
+  pub skipped: Vec<String>,
+  pub totals: DryRunTotals,
+}
+
+#[napi(object)]
+pub struct DryRunTotals {
+  pub removed: u32,
+  pub created: u32,
+  pub overwritten: u32,
+  pub skipped: u32,
+  /// Bytes of regular files that would be freed (`rm`) or written (`cp`).
+  pub bytes: f64,
+}
+
+/// Collects a `DryRunPlan` from (possibly parallel) traversals.
+#[derive(Default)]
+pub(crate) struct Planner {
+  removed: Mutex<Vec<String>>,
+  created: Mutex<Vec<String>>,
+  overwritten: Mutex<Vec<String>>,
+  skipped: Mutex<Vec<String>>,
+  bytes: AtomicU64,
+  /// Set for `cp`, whose `bytes` are the ones it would write: its `mirror` removals add none.
+  copying: bool,
+}
+
+impl Planner {
+  pub(crate) fn for_copy() -> Self {
+    Self {
+      copying: true,
+      ..Self::default()
+    }
+  }
+
+  fn push(list: &Mutex<Vec<String>>, path: &Path) {
+    list
+      .lock()
+      .unwrap()
+      .push(path.to_string_lossy().to_string());
+  }
+
+  pub(crate) fn remove(&self, path: &Path, bytes: u64) {
+    Self::push(&self.removed, path);
+    if !self.copying {
+      self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+  }
+
+  pub(crate) fn create(&self, path: &Path, bytes: u64) {
+    Self::push(&self.created, path);
+    self.bytes.fetch_add(bytes, Ordering::Relaxed);
+  }
+
+  pub(crate) fn overwrite(&self, path: &Path, bytes: u64) {
+    Self::push(&self.overwritten, path);
+    self.bytes.fetch_add(bytes, Ordering::Relaxed);
+  }
+
+  pub(crate) fn skip(&self, path: &Path) {
+    Self::push(&self.skipped, path);
+  }
+
+  pub(crate) fn into_plan(self) -> DryRunPlan {
+    let sorted = |list: Mutex<Vec<String>>| {
+      let mut list = list.into_inner().unwrap();
+      list.sort();
+      list.dedup();
+      list
+    };
+    let removed = sorted(self.removed);
+    let created = sorted(self.created);
+    let overwritten = sorted(self.overwritten);
+    let skipped = sorted(self.skipped);
+    DryRunPlan {
+      totals: DryRunTotals {
+        removed: removed.len() as u32,
+        created: created.len() as u32,
+        overwritten: overwritten.len() as u32,
+        skipped: skipped.len() as u32,
+        bytes: self.bytes.into_inner() as f64,
+      },
+      removed,
+      created,
+      overwritten,
+      skipped,
+    }
+  }
+}
@@ -0,0 +1,251 @@
+use jwalk::{Parallelism, WalkDirGeneric};
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct DuOptions {
+  /// Report apparent sizes (sum of file lengths) instead of allocated disk usage. Defaults to false.
+  pub apparent: Option<bool>,
+  /// Number of threads for the parallel walk. Defaults to the number of CPUs.
+  pub concurrency: Option<u32>,
+  /// Follow symlinks into their targets instead of counting the links. Defaults to false.
+  pub follow_symlinks: Option<bool>,
+  /// Count a hard-linked inode once, like `du`. Defaults to true.
+  pub count_hardlinks_once: Option<bool>,
+  /// Do not descend more than this many levels below `path`; deeper entries are not counted.
+  pub max_depth: Option<u32>,
+  /// Also return a per-child breakdown of the direct children of `path`. Defaults to false.
+  pub children: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct DuEntry {
+  pub name: String,
+  pub bytes: f64,
+  pub blocks: f64,
+  pub files: u32,
+  pub dirs: u32,
+}
+
+#[napi(object)]
+pub struct DuResult {
+  /// Apparent size or allocated bytes (`blocks * 512`), depending on `apparent`.
+  pub bytes: f64,
+  /// 512-byte blocks allocated on disk.
+  pub blocks: f64,
+  /// Number of non-directory entries (files, symlinks, ...).
+  pub files: u32,
+  /// Number of directories below `path` (the root itself is not counted).
+  pub dirs: u32,
+  pub children: Option<Vec<DuEntry>>,
+}
+
+/// The parts of `stat` du needs, fetched on jwalk's worker threads.
+#[derive(Debug, Default, Clone, Copy)]
+struct EntryStat {
+  len: u64,
+  blocks: u64,
+  dev: u64,
+  ino: u64,
+  nlink: u64,
+  is_dir: bool,
+}
+
+impl EntryStat {
+  fn from_metadata(meta: &fs::Metadata) -> Self {
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::MetadataExt;
+      EntryStat {
+        len: meta.len(),
+        blocks: meta.blocks(),
+        dev: meta.dev(),
+        ino: meta.ino(),
+        nlink: meta.nlink(),
+        is_dir: meta.is_dir(),
+      }
+    }
+    #[cfg(not(unix))]
+    {
+      EntryStat {
+        len: meta.len(),
+        blocks: meta.len().div_ceil(512),
+        dev: 0,
+        ino: 0,
+        nlink: 1,
+        is_dir: meta.is_dir(),
+      }
+    }
+  }
+}
+
+fn add_stat(entry: &mut DuEntry, stat: &EntryStat, apparent: bool, is_root: bool) {
+  entry.bytes += if apparent {
+    stat.len as f64
+  } else {
+    (stat.blocks * 512) as f64
+  };
+  entry.blocks += stat.blocks as f64;
+  if stat.is_dir {
+    if !is_root {
+      entry.dirs += 1;
+    }
+  } else {
+    entry.files += 1;
+  }
+}
+
+fn du_impl(path_str: String, options: Option<DuOptions>) -> Result<DuResult> {
+  let opts = options.unwrap_or(DuOptions {
+    apparent: None,
+    concurrency: None,
+    follow_symlinks: None,
+    count_hardlinks_once: None,
+    max_depth: None,
+    children: None,
+  });
+  let apparent = opts.apparent.unwrap_or(false);
+  let follow_symlinks = opts.follow_symlinks.unwrap_or(false);
+  let count_hardlinks_once = opts.count_hardlinks_once.unwrap_or(true);
+  let with_children = opts.children.unwrap_or(false);
+
+  let path = Path::new(&path_str);
+  let root_meta = if follow_symlinks {
+    fs::metadata(path)
+  } else {
+    fs::symlink_metadata(path)
+  }
+  .map_err(|e| {
+    if e.kind() == std::io::ErrorKind::NotFound {
+      Error::from_reason(format!(
+        "ENOENT: no such file or directory, du '{}'",
+        path_str
+      ))
+    } else {
+      Error::from_reason(format!("{}, du '{}'", e, path_str))
+    }
+  })?;
+
+  let mut total = DuEntry::default();
+  add_stat(
+    &mut total,
+    &EntryStat::from_metadata(&root_meta),
+    apparent,
+    true,
+  );
+  if !root_meta.is_dir() {
+    return Ok(DuResult {
+      bytes: total.bytes,
+      blocks: total.blocks,
+      files: total.files,
+      dirs: total.dirs,
+      children: with_children.then(Vec::new),
+    });
+  }
+
+  let mut walk_dir = WalkDirGeneric::<((), Option<EntryStat>)>::new(path)
+    .skip_hidden(false)
+    .follow_links(follow_symlinks)
+    .parallelism(match opts.concurrency {
+      Some(n) => Parallelism::RayonNewPool(n as usize),
+      None => Parallelism::RayonNewPool(0),
+    })
+    .process_read_dir(move |_, _, _, children| {
+      for child in children.iter_mut().flatten() {
+        child.client_state = child.metadata().ok().map(|m| EntryStat::from_metadata(&m));
+      }
+    });
+  if let Some(max_depth) = opts.max_depth {
+    walk_dir = walk_dir.max_depth(max_depth as usize);
+  }
+
+  let mut seen_inodes = HashSet::new();
+  let mut children: HashMap<std::ffi::OsString, DuEntry> = HashMap::new();
+
+  for entry in walk_dir.into_iter().filter_map(|e| e.ok()) {
+    if entry.depth() == 0 {
+      continue;
+    }
+    // Entries that vanished or could not be stat'ed mid-walk are skipped, like readdir.
+    let Some(stat) = entry.client_state else {
+      continue;
+    };
+    if count_hardlinks_once
+      && !stat.is_dir
+      && stat.nlink > 1
+      && !seen_inodes.insert((stat.dev, stat.ino))
+    {
+      continue;
+    }
+    add_stat(&mut total, &stat, apparent, false);
+
+    if with_children {
+      let relative = entry.path();
+      let relative = relative.strip_prefix(path).unwrap_or(&relative);
+      if let Some(first) = relative.components().next() {
+        let name = first.as_os_str().to_os_string();
+        let child = children.entry(name).or_insert_with_key(|name| DuEntry {
+          name: name.to_string_lossy().to_string(),
+          ..DuEntry::default()
+        });
+        // Like the root, a child directory does not count itself in `dirs`.
+        add_stat(child, &stat, apparent, entry.depth() == 1);
+      }
+    }
+  }
+
+  let children = with_children.then(|| {
+    let mut list: Vec<DuEntry> = children.into_values().collect();
+    // Largest first, like `du | sort -rn`.
+    list.sort_by(|a, b| {
+      b.bytes
+        .total_cmp(&a.bytes)
+        .then_with(|| a.name.cmp(&b.name))
+    });
+    list
+  });
+
+  Ok(DuResult {
+    bytes: total.bytes,
+    blocks: total.blocks,
+    files: total.files,
+    dirs: total.dirs,
+    children,
+  })
+}
+
+#[napi(js_name = "duSync")]
+pub fn du_sync(path: String, options: Option<DuOptions>) -> Result<DuResult> {
+  du_impl(path, options)
+}
+
+// ========= async version =========
+
+pub struct DuTask {
+  pub path: String,
+  pub options: Option<DuOptions>,
+}
+
+impl Task for DuTask {
+  type Output = DuResult;
+  type JsValue = DuResult;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    du_impl(self.path.clone(), self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "du")]
+pub fn du(path: String, options: Option<DuOptions>) -> AsyncTask<DuTask> {
+  AsyncTask::new(DuTask { path, options })
+}
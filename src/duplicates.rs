@@ -0,0 +1,313 @@
+use crate::cp::files_equal;
+use crate::glob::build_overrides;
+use crate::hash::{hash_reader, HashAlgorithm};
+use crate::mkdtemp::generate_random_suffix;
+use crate::utils::{errno_name, uv_error};
+use ignore::WalkBuilder;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Bytes read from the start of each same-size candidate before hashing whole files.
+const PARTIAL_HASH_LEN: u64 = 4096;
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FindDuplicatesOptions {
+  /// Ignore files smaller than this many bytes. Defaults to 1 (empty files are skipped).
+  pub min_size: Option<f64>,
+  /// Glob patterns (relative to each root) to skip.
+  pub exclude: Option<Vec<String>>,
+  /// Threads used for walking and hashing. Defaults to 4.
+  pub concurrency: Option<u32>,
+  /// 'report' (default) only returns the groups. 'hardlink' also replaces every duplicate
+  /// with a hard link to the first path of its group (same filesystem only).
+  pub action: Option<String>,
+}
+
+#[napi(object)]
+pub struct DuplicateGroup {
+  /// Size of each file in bytes.
+  pub size: f64,
+  /// Paths with identical content, sorted. The first one is kept by `action: 'hardlink'`.
+  pub paths: Vec<String>,
+  /// With `action: 'hardlink'`: the paths now hard-linked to `paths[0]`.
+  pub linked: Option<Vec<String>>,
+  /// With `action: 'hardlink'`: the duplicates left in place, and why.
+  pub failed: Option<Vec<DuplicateLinkError>>,
+}
+
+/// A duplicate `action: 'hardlink'` did not replace. `code` is `EXDEV` for another filesystem,
+/// `ECHANGED` when either file changed after it was hashed, or the error of the failing call.
+#[napi(object)]
+pub struct DuplicateLinkError {
+  pub path: String,
+  pub code: String,
+  pub message: String,
+}
+
+struct Candidate {
+  path: PathBuf,
+  size: u64,
+  dev: u64,
+  /// mtime seen by the walk, checked again before `action: 'hardlink'` replaces the file.
+  modified: Option<SystemTime>,
+}
+
+fn partial_digest(path: &Path) -> std::io::Result<Vec<u8>> {
+  let file = fs::File::open(path)?;
+  hash_reader(HashAlgorithm::Xxh3, file.take(PARTIAL_HASH_LEN))
+}
+
+fn full_digest(path: &Path) -> std::io::Result<Vec<u8>> {
+  hash_reader(HashAlgorithm::Blake3, fs::File::open(path)?)
+}
+
+/// Splits every group by `key`, dropping files that cannot be read and groups left with a
+/// single member.
+fn refine<K: Eq + std::hash::Hash + Send>(
+  groups: Vec<Vec<Candidate>>,
+  key: impl Fn(&Candidate) -> std::io::Result<K> + Sync,
+) -> Vec<Vec<Candidate>> {
+  groups
+    .into_par_iter()
+    .flat_map_iter(|group| {
+      let mut by_key: HashMap<K, Vec<Candidate>> = HashMap::new();
+      for candidate in group {
+        if let Ok(k) = key(&candidate) {
+          by_key.entry(k).or_default().push(candidate);
+        }
+      }
+      by_key.into_values().filter(|g| g.len() > 1)
+    })
+    .collect()
+}
+
+/// Whether `candidate` is still the regular file of the size and mtime the walk saw.
+fn unchanged(candidate: &Candidate) -> io::Result<bool> {
+  let meta = fs::symlink_metadata(&candidate.path)?;
+  Ok(meta.is_file() && meta.len() == candidate.size && meta.modified().ok() == candidate.modified)
+}
+
+/// Replaces `dup` with a hard link to `keep`, unless either changed since they were hashed or
+/// their bytes no longer match (`Ok(false)`). The link goes to a random temporary name next to
+/// `dup` and is renamed over it, so `dup` never disappears if linking fails.
+fn replace_with_link(keep: &Candidate, dup: &Candidate) -> io::Result<bool> {
+  if !unchanged(keep)? || !unchanged(dup)? || !files_equal(&keep.path, &dup.path)? {
+    return Ok(false);
+  }
+  let file_name = dup.path.file_name().unwrap_or_default().to_string_lossy();
+  let tmp = dup.path.with_file_name(format!(
+    ".{}.{}.rush-fs-link",
+    file_name,
+    generate_random_suffix()
+  ));
+  fs::hard_link(&keep.path, &tmp)?;
+  fs::rename(&tmp, &dup.path).inspect_err(|_| {
+    let _ = fs::remove_file(&tmp);
+  })?;
+  Ok(true)
+}
+
+fn link_error(path: &Path, code: &str, message: String) -> DuplicateLinkError {
+  DuplicateLinkError {
+    path: path.to_string_lossy().to_string(),
+    code: code.to_string(),
+    message,
+  }
+}
+
+fn find_duplicates_impl(
+  roots: Vec<String>,
+  options: Option<FindDuplicatesOptions>,
+) -> Result<Vec<DuplicateGroup>> {
+  let opts = options.unwrap_or(FindDuplicatesOptions {
+    min_size: None,
+    exclude: None,
+    concurrency: None,
+    action: None,
+  });
+  let min_size = opts.min_size.unwrap_or(1.0).max(0.0) as u64;
+  let concurrency = opts.concurrency.unwrap_or(4).max(1) as usize;
+  let hardlink = match opts.action.as_deref() {
+    None | Some("report") => false,
+    Some("hardlink") => true,
+    Some(other) => {
+      return Err(Error::from_reason(format!(
+        "ERR_INVALID_ARG_VALUE: The property 'options.action' must be one of 'report', 'hardlink'. Received '{}'",
+        other
+      )))
+    }
+  };
+
+  // 1. Parallel walk of every root, grouping regular files by size.
+  let by_size: Arc<Mutex<HashMap<u64, Vec<Candidate>>>> = Arc::new(Mutex::new(HashMap::new()));
+  let seen: Arc<Mutex<HashSet<(u64, u64)>>> = Arc::new(Mutex::new(HashSet::new()));
+  for root in &roots {
+    if !Path::new(root).exists() {
+      return Err(Error::from_reason(format!(
+        "ENOENT: no such file or directory, findDuplicates '{}'",
+        root
+      )));
+    }
+    let overrides = build_overrides(root, &[], opts.exclude.as_deref().unwrap_or_default())?;
+    let mut builder = WalkBuilder::new(root);
+    builder
+      .overrides(overrides)
+      .standard_filters(false)
+      .threads(concurrency);
+
+    builder.build_parallel().run(|| {
+      let by_size = by_size.clone();
+      let seen = seen.clone();
+      Box::new(move |entry| {
+        let Ok(entry) = entry else {
+          return ignore::WalkState::Continue;
+        };
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+          return ignore::WalkState::Continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+          return ignore::WalkState::Continue;
+        };
+        if meta.len() < min_size {
+          return ignore::WalkState::Continue;
+        }
+        #[cfg(unix)]
+        let (dev, ino) = {
+          use std::os::unix::fs::MetadataExt;
+          (meta.dev(), meta.ino())
+        };
+        #[cfg(not(unix))]
+        let (dev, ino) = (0u64, 0u64);
+        // Paths that already share an inode (existing hard links, overlapping roots)
+        // take no extra space, so only the first one seen takes part.
+        if cfg!(unix) && !seen.lock().unwrap().insert((dev, ino)) {
+          return ignore::WalkState::Continue;
+        }
+        by_size
+          .lock()
+          .unwrap()
+          .entry(meta.len())
+          .or_default()
+          .push(Candidate {
+            path: entry.into_path(),
+            size: meta.len(),
+            dev,
+            modified: meta.modified().ok(),
+          });
+        ignore::WalkState::Continue
+      })
+    });
+  }
+
+  let by_size = std::mem::take(&mut *by_size.lock().unwrap());
+  let groups: Vec<Vec<Candidate>> = by_size.into_values().filter(|g| g.len() > 1).collect();
+
+  // 2. Cheap partial hash, then 3. full hash, only for groups that still collide.
+  let pool = rayon::ThreadPoolBuilder::new()
+    .num_threads(concurrency)
+    .build()
+    .map_err(|e| Error::from_reason(e.to_string()))?;
+  let groups = pool.install(|| {
+    let groups = refine(groups, |c| partial_digest(&c.path));
+    refine(groups, |c| {
+      if c.size <= PARTIAL_HASH_LEN {
+        // The partial hash already covered the whole file.
+        Ok(Vec::new())
+      } else {
+        full_digest(&c.path)
+      }
+    })
+  });
+
+  let mut result = Vec::with_capacity(groups.len());
+  for mut group in groups {
+    group.sort_by(|a, b| a.path.cmp(&b.path));
+    let (mut linked, mut failed) = (Vec::new(), Vec::new());
+    if hardlink {
+      let keep = &group[0];
+      for dup in &group[1..] {
+        // Hard links cannot cross filesystems; such duplicates stay in place.
+        let replaced = match dup.dev == keep.dev {
+          true => replace_with_link(keep, dup),
+          false => Err(io::Error::from(io::ErrorKind::CrossesDevices)),
+        };
+        match replaced {
+          Ok(true) => linked.push(dup.path.to_string_lossy().to_string()),
+          Ok(false) => failed.push(link_error(
+            &dup.path,
+            "ECHANGED",
+            format!(
+              "ECHANGED: file changed since it was hashed, link '{}'",
+              dup.path.to_string_lossy()
+            ),
+          )),
+          Err(e) => failed.push(link_error(
+            &dup.path,
+            errno_name(&e).map_or("EIO", |(code, _)| code),
+            uv_error(&e, "link", &dup.path).reason,
+          )),
+        }
+      }
+    }
+    result.push(DuplicateGroup {
+      size: group[0].size as f64,
+      paths: group
+        .iter()
+        .map(|c| c.path.to_string_lossy().to_string())
+        .collect(),
+      linked: hardlink.then_some(linked),
+      failed: hardlink.then_some(failed),
+    });
+  }
+  result.sort_by(|a, b| {
+    b.size
+      .total_cmp(&a.size)
+      .then_with(|| a.paths.cmp(&b.paths))
+  });
+  Ok(result)
+}
+
+#[napi(js_name = "findDuplicatesSync")]
+pub fn find_duplicates_sync(
+  roots: Vec<String>,
+  options: Option<FindDuplicatesOptions>,
+) -> Result<Vec<DuplicateGroup>> {
+  find_duplicates_impl(roots, options)
+}
+
+// ========= async version =========
+
+pub struct FindDuplicatesTask {
+  pub roots: Vec<String>,
+  pub options: Option<FindDuplicatesOptions>,
+}
+
+impl Task for FindDuplicatesTask {
+  type Output = Vec<DuplicateGroup>;
+  type JsValue = Vec<DuplicateGroup>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    find_duplicates_impl(self.roots.clone(), self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "findDuplicates")]
+pub fn find_duplicates(
+  roots: Vec<String>,
+  options: Option<FindDuplicatesOptions>,
+) -> AsyncTask<FindDuplicatesTask> {
+  AsyncTask::new(FindDuplicatesTask { roots, options })
+}
@@ -0,0 +1,288 @@
+//! String <-> bytes conversion shared by readFile, writeFile, appendFile, readLines and
+//! friends, following Node's `Buffer#toString(encoding)` / `Buffer.from(string, encoding)`.
+//! Names Node does not know are looked up as WHATWG labels (`gbk`, `shift_jis`, ...) and
+//! transcoded with encoding_rs.
+
+use encoding_rs::{EncoderResult, REPLACEMENT, UTF_16BE, UTF_16LE, UTF_8};
+use napi::bindgen_prelude::*;
+
+/// A Node `BufferEncoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+  Utf8,
+  Utf16le,
+  Latin1,
+  Ascii,
+  Base64,
+  Base64Url,
+  Hex,
+  /// Any other WHATWG encoding.
+  Whatwg(&'static encoding_rs::Encoding),
+}
+
+impl Encoding {
+  /// Parses an encoding name case-insensitively, accepting Node's aliases
+  /// (`utf-8`, `ucs2`, `ucs-2`, `utf-16le`, `binary`) and then any WHATWG label.
+  pub(crate) fn parse(name: &str) -> Result<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "utf8" | "utf-8" => Ok(Encoding::Utf8),
+      "utf16le" | "utf-16le" | "ucs2" | "ucs-2" => Ok(Encoding::Utf16le),
+      "latin1" | "binary" => Ok(Encoding::Latin1),
+      "ascii" => Ok(Encoding::Ascii),
+      "base64" => Ok(Encoding::Base64),
+      "base64url" => Ok(Encoding::Base64Url),
+      "hex" => Ok(Encoding::Hex),
+      _ => match encoding_rs::Encoding::for_label(name.as_bytes()) {
+        // Labels of the "replacement" encoding decode any input to a single U+FFFD.
+        Some(encoding) if encoding != REPLACEMENT => Ok(Encoding::Whatwg(encoding)),
+        _ => Err(Error::from_reason(format!(
+          "ERR_UNKNOWN_ENCODING: Unknown encoding: {}",
+          name
+        ))),
+      },
+    }
+  }
+
+  /// Whether every ASCII byte stands for itself, so text can be split on the byte `\n`.
+  pub(crate) fn is_ascii_compatible(self) -> bool {
+    match self {
+      Encoding::Utf16le => false,
+      Encoding::Whatwg(encoding) => encoding.is_ascii_compatible(),
+      _ => true,
+    }
+  }
+
+  /// The byte order mark written by `writeFile({ bom: true })`, if the encoding has one.
+  pub(crate) fn bom(self) -> Option<&'static [u8]> {
+    match self {
+      Encoding::Utf8 => Some(b"\xEF\xBB\xBF"),
+      Encoding::Utf16le => Some(b"\xFF\xFE"),
+      Encoding::Whatwg(encoding) if encoding == UTF_8 => Some(b"\xEF\xBB\xBF"),
+      Encoding::Whatwg(encoding) if encoding == UTF_16LE => Some(b"\xFF\xFE"),
+      Encoding::Whatwg(encoding) if encoding == UTF_16BE => Some(b"\xFE\xFF"),
+      _ => None,
+    }
+  }
+
+  /// Bytes -> string, never failing: invalid UTF-8 / UTF-16 becomes U+FFFD like in Node.
+  /// WHATWG encodings follow the standard's decode algorithm, so a BOM overrides the label.
+  pub(crate) fn decode(self, data: &[u8]) -> String {
+    match self {
+      Encoding::Utf8 => String::from_utf8_lossy(data).into_owned(),
+      Encoding::Utf16le => {
+        // A trailing odd byte is dropped, as in Node.
+        let units: Vec<u16> = data
+          .chunks_exact(2)
+          .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+          .collect();
+        String::from_utf16_lossy(&units)
+      }
+      Encoding::Latin1 => data.iter().map(|&b| b as char).collect(),
+      // Node clears the high bit, then decodes as latin1.
+      Encoding::Ascii => data.iter().map(|&b| (b & 0x7f) as char).collect(),
+      Encoding::Base64 => base64_encode(data, false),
+      Encoding::Base64Url => base64_encode(data, true),
+      Encoding::Hex => {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut s = String::with_capacity(data.len() * 2);
+        for &b in data {
+          s.push(DIGITS[(b >> 4) as usize] as char);
+          s.push(DIGITS[(b & 0xf) as usize] as char);
+        }
+        s
+      }
+      Encoding::Whatwg(encoding) => encoding.decode(data).0.into_owned(),
+    }
+  }
+
+  /// String -> bytes. Like Node, base64 skips characters outside the alphabet and hex stops at
+  /// the first invalid pair; with `strict`, such input is rejected instead. Characters a WHATWG
+  /// encoding cannot represent become `?`, or an error with `strict`.
+  pub(crate) fn encode(self, s: &str, strict: bool) -> Result<Vec<u8>> {
+    match self {
+      Encoding::Utf8 => Ok(s.as_bytes().to_vec()),
+      Encoding::Utf16le => Ok(s.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+      // Both keep the low byte of each UTF-16 code unit, as Node does.
+      Encoding::Latin1 | Encoding::Ascii => Ok(s.encode_utf16().map(|u| u as u8).collect()),
+      Encoding::Base64 => base64_decode(s, false, strict),
+      Encoding::Base64Url => base64_decode(s, true, strict),
+      Encoding::Hex => hex_decode(s, strict),
+      // encoding_rs encoders only produce UTF-8 for UTF-16 (as the standard requires).
+      Encoding::Whatwg(encoding) if encoding == UTF_16LE => {
+        Ok(s.encode_utf16().flat_map(u16::to_le_bytes).collect())
+      }
+      Encoding::Whatwg(encoding) if encoding == UTF_16BE => {
+        Ok(s.encode_utf16().flat_map(u16::to_be_bytes).collect())
+      }
+      Encoding::Whatwg(encoding) => transcode(encoding, s, strict),
+    }
+  }
+}
+
+fn transcode(encoding: &'static encoding_rs::Encoding, s: &str, strict: bool) -> Result<Vec<u8>> {
+  let mut encoder = encoding.new_encoder();
+  let mut buf = Vec::with_capacity(s.len());
+  let mut rest = s;
+  loop {
+    let (result, read) = encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut buf, true);
+    rest = &rest[read..];
+    match result {
+      EncoderResult::InputEmpty => return Ok(buf),
+      EncoderResult::OutputFull => buf.reserve(rest.len().max(16)),
+      EncoderResult::Unmappable(c) if strict => {
+        return Err(Error::from_reason(format!(
+          "ERR_INVALID_ARG_VALUE: The character '{}' (U+{:04X}) cannot be encoded in {}",
+          c,
+          c as u32,
+          encoding.name()
+        )))
+      }
+      EncoderResult::Unmappable(_) => buf.push(b'?'),
+    }
+  }
+}
+
+/// Parses an optional encoding where `None` or `'buffer'` mean raw bytes.
+pub(crate) fn parse_optional(name: Option<&str>) -> Result<Option<Encoding>> {
+  match name {
+    None => Ok(None),
+    Some(name) if name.eq_ignore_ascii_case("buffer") => Ok(None),
+    Some(name) => Encoding::parse(name).map(Some),
+  }
+}
+
+/// Decodes `data` as a string, or hands it back as a Buffer when `encoding` is `None`.
+pub(crate) fn decode_to_js(data: Vec<u8>, encoding: Option<Encoding>) -> Either<String, Buffer> {
+  match encoding {
+    Some(encoding) => Either::A(encoding.decode(&data)),
+    None => Either::B(Buffer::from(data)),
+  }
+}
+
+pub(crate) fn base64_encode(data: &[u8], url_safe: bool) -> String {
+  const STD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+  const URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+  let table = if url_safe { URL } else { STD };
+
+  let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+  let chunks = data.chunks(3);
+  for chunk in chunks {
+    let b0 = chunk[0] as u32;
+    let b1 = if chunk.len() > 1 { chunk[1] as u32 } else { 0 };
+    let b2 = if chunk.len() > 2 { chunk[2] as u32 } else { 0 };
+    let triple = (b0 << 16) | (b1 << 8) | b2;
+
+    result.push(table[((triple >> 18) & 0x3F) as usize] as char);
+    result.push(table[((triple >> 12) & 0x3F) as usize] as char);
+    if chunk.len() > 1 {
+      result.push(table[((triple >> 6) & 0x3F) as usize] as char);
+    } else if !url_safe {
+      result.push('=');
+    }
+    if chunk.len() > 2 {
+      result.push(table[(triple & 0x3F) as usize] as char);
+    } else if !url_safe {
+      result.push('=');
+    }
+  }
+  result
+}
+
+fn base64_value(c: u8, url_safe: bool, strict: bool) -> Option<u32> {
+  match c {
+    b'A'..=b'Z' => Some((c - b'A') as u32),
+    b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+    b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+    // Lenient decoding accepts both alphabets, like Node.
+    b'+' if !strict || !url_safe => Some(62),
+    b'/' if !strict || !url_safe => Some(63),
+    b'-' if !strict || url_safe => Some(62),
+    b'_' if !strict || url_safe => Some(63),
+    _ => None,
+  }
+}
+
+fn invalid_data(encoding: &str) -> Error {
+  Error::from_reason(format!(
+    "ERR_INVALID_ARG_VALUE: The argument 'data' is not a valid {} string",
+    encoding
+  ))
+}
+
+fn base64_decode(s: &str, url_safe: bool, strict: bool) -> Result<Vec<u8>> {
+  let name = if url_safe { "base64url" } else { "base64" };
+  // Node looks at the low byte of each UTF-16 code unit, so e.g. the low surrogate of an
+  // emoji may read as `=` and end the data. Strict input must be plain ASCII anyway.
+  let units: Vec<u8>;
+  let input = if strict {
+    s.as_bytes()
+  } else {
+    units = s.encode_utf16().map(|u| u as u8).collect();
+    &units
+  };
+  let data_len = input.iter().position(|&c| c == b'=').unwrap_or(input.len());
+  if strict {
+    let padding = &input[data_len..];
+    let padded_ok = padding.iter().all(|&c| c == b'=')
+      && padding.len() <= 2
+      && (padding.is_empty() || input.len().is_multiple_of(4));
+    let unpadded_ok = url_safe || !padding.is_empty() || data_len.is_multiple_of(4);
+    if !padded_ok || !unpadded_ok || data_len % 4 == 1 {
+      return Err(invalid_data(name));
+    }
+  }
+
+  let mut buf = Vec::with_capacity(data_len * 3 / 4);
+  let mut acc: u32 = 0;
+  let mut bits: u32 = 0;
+  for &c in &input[..data_len] {
+    let Some(val) = base64_value(c, url_safe, strict) else {
+      if strict {
+        return Err(invalid_data(name));
+      }
+      // Whitespace and other characters outside the alphabet are skipped.
+      continue;
+    };
+    acc = (acc << 6) | val;
+    bits += 6;
+    if bits >= 8 {
+      bits -= 8;
+      buf.push((acc >> bits) as u8);
+      acc &= (1 << bits) - 1;
+    }
+  }
+  Ok(buf)
+}
+
+fn hex_val(b: u8) -> Option<u8> {
+  match b {
+    b'0'..=b'9' => Some(b - b'0'),
+    b'a'..=b'f' => Some(b - b'a' + 10),
+    b'A'..=b'F' => Some(b - b'A' + 10),
+    _ => None,
+  }
+}
+
+fn hex_decode(s: &str, strict: bool) -> Result<Vec<u8>> {
+  // As with base64, Node only looks at the low byte of each UTF-16 code unit.
+  let units: Vec<u8>;
+  let bytes = if strict {
+    s.as_bytes()
+  } else {
+    units = s.encode_utf16().map(|u| u as u8).collect();
+    &units
+  };
+  if strict && !bytes.len().is_multiple_of(2) {
+    return Err(invalid_data("hex"));
+  }
+  let mut buf = Vec::with_capacity(bytes.len() / 2);
+  for pair in bytes.chunks_exact(2) {
+    match (hex_val(pair[0]), hex_val(pair[1])) {
+      (Some(hi), Some(lo)) => buf.push((hi << 4) | lo),
+      _ if strict => return Err(invalid_data("hex")),
+      // Node truncates at the first pair that is not valid hex.
+      _ => break,
+    }
+  }
+  Ok(buf)
+}
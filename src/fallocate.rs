@@ -0,0 +1,200 @@
+use crate::utils::uv_error;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+/// Modes of `fallocate`, named after the Linux `FALLOC_FL_*` flags they map to.
+#[napi(object)]
+#[derive(Clone)]
+pub struct FallocateOptions {
+  /// Allocate without changing the file size when the range extends past the end
+  /// (`FALLOC_FL_KEEP_SIZE`). Not available on Windows.
+  pub keep_size: Option<bool>,
+  /// Deallocate the range, which then reads as zeros; implies `keepSize`
+  /// (`FALLOC_FL_PUNCH_HOLE`). Linux only.
+  pub punch_hole: Option<bool>,
+  /// Zero the range and keep it allocated (`FALLOC_FL_ZERO_RANGE`). Linux only.
+  pub zero_range: Option<bool>,
+}
+
+#[derive(Clone, Copy)]
+struct Mode {
+  keep_size: bool,
+  punch_hole: bool,
+  zero_range: bool,
+}
+
+/// Linux `fallocate(2)`. Filesystems that cannot preallocate (`EOPNOTSUPP`) fall back to
+/// `posix_fallocate`, which glibc emulates by writing zeros, when no flag is set.
+#[cfg(target_os = "linux")]
+fn allocate(file: &File, offset: i64, length: i64, mode: Mode) -> io::Result<()> {
+  use std::os::fd::AsRawFd;
+  let mut flags = 0;
+  if mode.keep_size {
+    flags |= libc::FALLOC_FL_KEEP_SIZE;
+  }
+  if mode.punch_hole {
+    flags |= libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE;
+  }
+  if mode.zero_range {
+    flags |= libc::FALLOC_FL_ZERO_RANGE;
+  }
+  loop {
+    if unsafe { libc::fallocate(file.as_raw_fd(), flags, offset, length) } == 0 {
+      return Ok(());
+    }
+    let e = io::Error::last_os_error();
+    match e.raw_os_error() {
+      Some(libc::EINTR) => continue,
+      Some(libc::EOPNOTSUPP) if flags == 0 => break,
+      _ => return Err(e),
+    }
+  }
+  // posix_fallocate returns the error number instead of setting errno.
+  match unsafe { libc::posix_fallocate(file.as_raw_fd(), offset, length) } {
+    0 => Ok(()),
+    code => Err(io::Error::from_raw_os_error(code)),
+  }
+}
+
+/// macOS has no `fallocate`: `F_PREALLOCATE` reserves the blocks (contiguous if possible) and
+/// `ftruncate` grows the file unless `keepSize` is set.
+#[cfg(target_os = "macos")]
+fn allocate(file: &File, offset: i64, length: i64, mode: Mode) -> io::Result<()> {
+  use std::os::fd::AsRawFd;
+  if mode.punch_hole || mode.zero_range {
+    return Err(io::Error::from_raw_os_error(libc::ENOTSUP));
+  }
+  let end = offset
+    .checked_add(length)
+    .ok_or_else(|| io::Error::from_raw_os_error(libc::EFBIG))?;
+  let size = file.metadata()?.len() as i64;
+  if end > size {
+    let mut store = libc::fstore_t {
+      fst_flags: libc::F_ALLOCATECONTIG | libc::F_ALLOCATEALL,
+      fst_posmode: libc::F_PEOFPOSMODE,
+      fst_offset: 0,
+      fst_length: end - size,
+      fst_bytesalloc: 0,
+    };
+    let fd = file.as_raw_fd();
+    if unsafe { libc::fcntl(fd, libc::F_PREALLOCATE, &mut store) } == -1 {
+      store.fst_flags = libc::F_ALLOCATEALL;
+      if unsafe { libc::fcntl(fd, libc::F_PREALLOCATE, &mut store) } == -1 {
+        return Err(io::Error::last_os_error());
+      }
+    }
+    if !mode.keep_size {
+      file.set_len(end as u64)?;
+    }
+  }
+  Ok(())
+}
+
+/// Elsewhere only plain preallocation is available, as growing the file with `set_len`.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn allocate(file: &File, offset: i64, length: i64, mode: Mode) -> io::Result<()> {
+  if mode.keep_size || mode.punch_hole || mode.zero_range {
+    return Err(io::Error::from(io::ErrorKind::Unsupported));
+  }
+  let end = offset
+    .checked_add(length)
+    .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+  if end as u64 > file.metadata()?.len() {
+    file.set_len(end as u64)?;
+  }
+  Ok(())
+}
+
+fn fallocate_impl(
+  path_str: String,
+  offset: i64,
+  length: i64,
+  options: Option<FallocateOptions>,
+) -> Result<()> {
+  let path = Path::new(&path_str);
+  let opts = options.unwrap_or(FallocateOptions {
+    keep_size: None,
+    punch_hole: None,
+    zero_range: None,
+  });
+  let mode = Mode {
+    keep_size: opts.keep_size.unwrap_or(false),
+    punch_hole: opts.punch_hole.unwrap_or(false),
+    zero_range: opts.zero_range.unwrap_or(false),
+  };
+  if mode.punch_hole && mode.zero_range {
+    return Err(Error::from_reason(
+      "ERR_INVALID_ARG_VALUE: The properties 'options.punchHole' and 'options.zeroRange' cannot both be true",
+    ));
+  }
+  if offset < 0 || length <= 0 {
+    return Err(Error::from_reason(format!(
+      "EINVAL: invalid argument, fallocate '{}'",
+      path_str
+    )));
+  }
+
+  let file = OpenOptions::new()
+    .write(true)
+    .open(path)
+    .map_err(|e| uv_error(&e, "open", path))?;
+  allocate(&file, offset, length, mode).map_err(|e| uv_error(&e, "fallocate", path))
+}
+
+/// Allocates (or with `punchHole` / `zeroRange`, deallocates or zeroes) `length` bytes of `path`
+/// starting at `offset`, like Linux `fallocate(2)`.
+#[napi(js_name = "fallocateSync")]
+pub fn fallocate_sync(
+  path: String,
+  offset: i64,
+  length: i64,
+  options: Option<FallocateOptions>,
+) -> Result<()> {
+  fallocate_impl(path, offset, length, options)
+}
+
+// ========= async version =========
+
+pub struct FallocateTask {
+  pub path: String,
+  pub offset: i64,
+  pub length: i64,
+  pub options: Option<FallocateOptions>,
+}
+
+impl Task for FallocateTask {
+  type Output = ();
+  type JsValue = ();
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    fallocate_impl(
+      self.path.clone(),
+      self.offset,
+      self.length,
+      self.options.clone(),
+    )
+  }
+
+  fn resolve(&mut self, _env: Env, _output: Self::Output) -> Result<Self::JsValue> {
+    Ok(())
+  }
+}
+
+#[napi(js_name = "fallocate")]
+pub fn fallocate(
+  path: String,
+  offset: i64,
+  length: i64,
+  options: Option<FallocateOptions>,
+) -> AsyncTask<FallocateTask> {
+  AsyncTask::new(FallocateTask {
+    path,
+    offset,
+    length,
+    options,
+  })
+}
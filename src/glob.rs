@@ -1,9 +1,16 @@
+use crate::glob_pattern::{expand_braces, GlobMatcher};
+use crate::on_error::{aggregate_error, ErrorPolicy, PathError};
 use crate::types::Dirent;
-use crate::utils::get_file_type_id;
-use ignore::{overrides::OverrideBuilder, WalkBuilder};
+use crate::utils::{get_file_type_id, uv_error};
+use ignore::{
+  overrides::{Override, OverrideBuilder},
+  WalkBuilder,
+};
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use std::path::{Path, PathBuf};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Component, Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 /// Extract leading path prefix from pattern so we can walk from that directory.
@@ -12,8 +19,13 @@ use std::sync::{Arc, Mutex};
 /// This aligns with Node.js: pattern with path prefix uses that path as the search root.
 /// We scan for the first of * ? [ so that patterns like "dir?/sub/**/*.ts" use "dir?" as prefix
 /// (no literal dir? on disk) rather than "dir?/sub", which would wrongly be used as walk root.
+/// Extglob groups (`@(a|b)`, `+(a)`, `!(a)`) end the prefix too.
 fn extract_path_prefix(pattern: &str) -> Option<(String, String)> {
-  let first_glob = pattern.find(['*', '?', '['])?;
+  let bytes = pattern.as_bytes();
+  let first_glob = bytes.iter().enumerate().position(|(i, b)| {
+    matches!(b, b'*' | b'?' | b'[')
+      || (matches!(b, b'@' | b'+' | b'!') && bytes.get(i + 1) == Some(&b'('))
+  })?;
   let prefix = pattern[..first_glob]
     .trim_end_matches('/')
     .trim_end_matches(std::path::MAIN_SEPARATOR);
@@ -23,10 +35,34 @@ fn extract_path_prefix(pattern: &str) -> Option<(String, String)> {
   Some((prefix.to_string(), pattern[first_glob..].to_string()))
 }
 
-// The ignore crate filters files by override whitelist; directories are always traversed
-// (so we can recurse to find matching children). We use dir_matcher to test whether a
-// directory path itself matches the pattern; only matching directories are included in
-// results — aligned with Node.js fs.globSync:
+/// Builds the override matcher used by glob and the other `ignore`-based walkers
+/// (hashTree, ...): `include` patterns whitelist paths, `exclude` patterns are added as
+/// negations. With no include patterns, everything that is not excluded passes.
+pub(crate) fn build_overrides(
+  root: &str,
+  include: &[String],
+  exclude: &[String],
+) -> Result<Override> {
+  let mut override_builder = OverrideBuilder::new(root);
+  for pattern in include {
+    override_builder
+      .add(pattern)
+      .map_err(|e| Error::from_reason(e.to_string()))?;
+  }
+  for ex in exclude {
+    override_builder
+      .add(&format!("!{}", ex))
+      .map_err(|e| Error::from_reason(e.to_string()))?;
+  }
+  override_builder
+    .build()
+    .map_err(|e| Error::from_reason(e.to_string()))
+}
+
+// Directories are always traversed (so we can recurse to find matching children), except
+// those excluded by `exclude` or a negated `dir/**`. Every entry, directory or not, is tested
+// against the compiled patterns (see glob_pattern.rs); only matching directories are included
+// in results — aligned with Node.js fs.globSync:
 //   - "src/*"   → returns files AND subdirs under src/
 //   - "**/*.rs" → returns only .rs files (dirs don't match)
 //   - "**"      → returns all files and dirs (excluding the cwd root itself)
@@ -39,12 +75,165 @@ pub struct GlobOptions {
   pub exclude: Option<Vec<String>>,
   pub concurrency: Option<u32>,
   pub git_ignore: Option<bool>,
+  /// Rush-FS extension: what to do with directories that cannot be read. `'skip'` (default,
+  /// like Node.js) leaves them out, `'throw'` fails on the first one, and `'collect'` finishes
+  /// the walk, then throws an `AggregateError` of every failure.
+  #[napi(ts_type = "'throw' | 'skip' | 'collect'")]
+  pub on_error: Option<String>,
+}
+
+/// The path an `ignore` walk error is about, if any.
+pub(crate) fn walk_error_path(e: &ignore::Error) -> Option<&Path> {
+  match e {
+    ignore::Error::WithPath { path, .. } => Some(path),
+    ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+      walk_error_path(err)
+    }
+    ignore::Error::Loop { child, .. } => Some(child),
+    _ => None,
+  }
 }
 
 #[napi(js_name = "globSync")]
 pub fn glob_sync(
-  pattern: String,
+  env: Env,
+  pattern: Either<String, Vec<String>>,
   options: Option<GlobOptions>,
+) -> Result<Either<Vec<String>, Vec<Dirent>>> {
+  let mut errors = Vec::new();
+  glob_impl(pattern_list(pattern), options, &mut errors)
+    .map_err(|e| aggregate_error(&env, e, errors))
+}
+
+fn pattern_list(pattern: Either<String, Vec<String>>) -> Vec<String> {
+  match pattern {
+    Either::A(pattern) => vec![pattern],
+    Either::B(patterns) => patterns,
+  }
+}
+
+/// The patterns of one glob call, brace-expanded and compiled against cwd-relative paths.
+struct Patterns {
+  include: Vec<GlobMatcher>,
+  /// `!`-negated patterns: matching entries are left out of the results.
+  exclude: Vec<GlobMatcher>,
+  /// The negations ending in `/**`: directories whose contents they exclude are not descended
+  /// into at all.
+  prune: Vec<GlobMatcher>,
+  /// Where to walk from. Roots inside another root are merged into it, so that no entry is
+  /// visited (and returned) twice; with `gitIgnore`, whose walks skip hidden and ignored
+  /// directories, only identical roots are.
+  roots: Vec<Root>,
+}
+
+/// The literal prefix of an include pattern, walked from instead of the cwd.
+struct Root {
+  /// Normalized prefix (`None` for the cwd itself): "./src" and "src" are the same root.
+  prefix: Option<String>,
+  /// The prefix as written in the pattern, which results start with.
+  display: Option<String>,
+}
+
+/// Splits a brace-expanded pattern into its literal prefix and the rest.
+fn split_prefix(pattern: &str) -> (Root, String) {
+  match extract_path_prefix(pattern) {
+    Some((display, rest)) => {
+      let prefix: PathBuf = Path::new(&display)
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect();
+      let prefix = prefix.to_string_lossy().to_string();
+      let root = Root {
+        prefix: (!prefix.is_empty()).then_some(prefix),
+        display: Some(display),
+      };
+      (root, rest)
+    }
+    None => {
+      let root = Root {
+        prefix: None,
+        display: None,
+      };
+      (root, pattern.to_string())
+    }
+  }
+}
+
+/// Whether walking `root` also visits everything below `other`.
+fn covers(root: &Option<String>, other: &Option<String>) -> bool {
+  let other = match other {
+    Some(other) => Path::new(other),
+    None => return root.is_none(),
+  };
+  let rest = match root {
+    None if other.is_absolute() => return false,
+    None => other,
+    Some(root) => match other.strip_prefix(root) {
+      Ok(rest) => rest,
+      Err(_) => return false,
+    },
+  };
+  !rest.components().any(|c| {
+    matches!(
+      c,
+      Component::ParentDir | Component::RootDir | Component::Prefix(_)
+    )
+  })
+}
+
+fn compile_patterns(patterns: &[String], git_ignore: bool) -> Result<Patterns> {
+  let mut compiled = Patterns {
+    include: Vec::new(),
+    exclude: Vec::new(),
+    prune: Vec::new(),
+    roots: Vec::new(),
+  };
+  let mut roots = Vec::new();
+  for pattern in patterns {
+    let (negated, pattern) = match pattern.strip_prefix('!') {
+      Some(rest) if !rest.starts_with('(') => (true, rest),
+      _ => (false, pattern.as_str()),
+    };
+    for expanded in expand_braces(pattern)? {
+      let (root, rest) = split_prefix(&expanded);
+      let prefix = root.prefix.clone();
+      if !negated {
+        compiled
+          .include
+          .push(GlobMatcher::new(prefix.as_deref(), &rest)?);
+        roots.push(root);
+        continue;
+      }
+      let matcher = GlobMatcher::new(prefix.as_deref(), &rest)?;
+      // Test the whole pattern: for `node_modules/**`, the prefix took everything but `**`.
+      if expanded.ends_with("/**") {
+        compiled.prune.push(matcher.clone());
+      }
+      compiled.exclude.push(matcher);
+    }
+  }
+  // Shortest first, so that every root is checked against the roots that may contain it.
+  roots.sort_by_key(|root| {
+    root
+      .prefix
+      .as_ref()
+      .map(|prefix| Path::new(prefix).components().count())
+  });
+  for root in roots {
+    if !compiled.roots.iter().any(|kept| match git_ignore {
+      true => kept.prefix == root.prefix,
+      false => covers(&kept.prefix, &root.prefix),
+    }) {
+      compiled.roots.push(root);
+    }
+  }
+  Ok(compiled)
+}
+
+fn glob_impl(
+  patterns: Vec<String>,
+  options: Option<GlobOptions>,
+  errors: &mut Vec<PathError>,
 ) -> Result<Either<Vec<String>, Vec<Dirent>>> {
   let opts = options.unwrap_or(GlobOptions {
     cwd: None,
@@ -52,72 +241,123 @@ pub fn glob_sync(
     exclude: None,
     concurrency: None,
     git_ignore: None,
+    on_error: None,
   });
+  let policy = ErrorPolicy::new(Some(opts.on_error.as_deref().unwrap_or("skip")))?;
+  let policy = Arc::new(policy);
+  let result = compile_patterns(&patterns, opts.git_ignore.unwrap_or(false))
+    .and_then(|compiled| walk(compiled, opts, policy.clone()));
+  let policy = Arc::try_unwrap(policy).map_err(|_| Error::from_reason("Lock error"))?;
+  policy.finish(result, "glob", &patterns.join(", "), errors)
+}
 
-  let cwd = opts.cwd.unwrap_or_else(|| ".".to_string());
-  let cwd_path = Path::new(&cwd).to_path_buf();
-  let with_file_types = opts.with_file_types.unwrap_or(false);
-  let concurrency = opts.concurrency.unwrap_or(4) as usize;
+/// A match: its path relative to the cwd and its dirent.
+type Found = (String, Dirent);
 
-  // When pattern has a path prefix (e.g. "dir/**/*.txt" or ".hidden/**/*.txt"), use that as the
-  // walk root so we descend into it (fixes hidden dirs and matches Node.js behavior).
-  let (walk_root, pattern_for_override, result_prefix) = match extract_path_prefix(&pattern) {
-    Some((prefix, rest)) => {
-      let root = cwd_path.join(&prefix);
-      (
-        root.to_string_lossy().to_string(),
-        rest,
-        Some(PathBuf::from(prefix)),
-      )
-    }
-    None => (cwd.clone(), pattern.clone(), None),
-  };
+fn walk(
+  patterns: Patterns,
+  opts: GlobOptions,
+  policy: Arc<ErrorPolicy>,
+) -> Result<Either<Vec<String>, Vec<Dirent>>> {
+  let cwd = opts.cwd.clone().unwrap_or_else(|| ".".to_string());
+  let with_file_types = opts.with_file_types.unwrap_or(false);
+  let patterns = Arc::new(patterns);
+  let found: Arc<Mutex<Vec<Found>>> = Arc::new(Mutex::new(Vec::new()));
 
-  // Build override (whitelist) relative to walk_root
-  let mut override_builder = OverrideBuilder::new(&walk_root);
-  override_builder
-    .add(&pattern_for_override)
-    .map_err(|e| Error::from_reason(e.to_string()))?;
-
-  if let Some(ref excludes) = opts.exclude {
-    for ex in excludes {
-      override_builder
-        .add(&format!("!{}", ex))
-        .map_err(|e| Error::from_reason(e.to_string()))?;
-    }
+  // When a pattern has a path prefix (e.g. "dir/**/*.txt" or ".hidden/**/*.txt"), that is its
+  // walk root, so we descend into it (fixes hidden dirs and matches Node.js behavior).
+  for root in &patterns.roots {
+    let walk_root = match &root.display {
+      Some(prefix) => Path::new(&cwd).join(prefix).to_string_lossy().to_string(),
+      None => cwd.clone(),
+    };
+    walk_root_dir(
+      &walk_root,
+      root,
+      &opts,
+      patterns.clone(),
+      policy.clone(),
+      found.clone(),
+    )?;
   }
 
-  let overrides = override_builder
-    .build()
-    .map_err(|e| Error::from_reason(e.to_string()))?;
+  let mut found = Arc::try_unwrap(found)
+    .map_err(|_| Error::from_reason("Lock error"))?
+    .into_inner()
+    .map_err(|_| Error::from_reason("Mutex error"))?;
+  // Unmerged roots (see `Patterns::roots`) can overlap.
+  if patterns.roots.len() > 1 {
+    let mut seen = HashSet::new();
+    found.retain(|(path, _)| {
+      let key: PathBuf = Path::new(path)
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect();
+      seen.insert(key)
+    });
+  }
+  if with_file_types {
+    Ok(Either::B(found.into_iter().map(|(_, d)| d).collect()))
+  } else {
+    Ok(Either::A(found.into_iter().map(|(p, _)| p).collect()))
+  }
+}
 
-  let dir_matcher = Arc::new(overrides.clone());
+/// Walks one root, adding the entries `patterns` match to `found` with paths relative to the cwd
+/// (the root's prefix joined with the path inside the root).
+fn walk_root_dir(
+  walk_root: &str,
+  root: &Root,
+  opts: &GlobOptions,
+  patterns: Arc<Patterns>,
+  policy: Arc<ErrorPolicy>,
+  found: Arc<Mutex<Vec<Found>>>,
+) -> Result<()> {
+  let concurrency = opts.concurrency.unwrap_or(4) as usize;
+  // `exclude` patterns are relative to each walk root and prune whole directories.
+  let overrides = build_overrides(walk_root, &[], opts.exclude.as_deref().unwrap_or_default())?;
 
-  let mut builder = WalkBuilder::new(&walk_root);
+  let mut builder = WalkBuilder::new(walk_root);
   builder
     .overrides(overrides)
     .standard_filters(opts.git_ignore.unwrap_or(false))
     .threads(concurrency);
 
-  // We use two vectors to avoid enum overhead in the lock if possible, but Mutex<Vec<T>> is easier
-  let result_strings = Arc::new(Mutex::new(Vec::new()));
-  let result_dirents = Arc::new(Mutex::new(Vec::new()));
+  // With `onError: 'throw'`, the first error stops the walk and is returned.
+  let first_error: Arc<Mutex<Option<Error>>> = Arc::new(Mutex::new(None));
+  let first_error_clone = first_error.clone();
 
-  let result_strings_clone = result_strings.clone();
-  let result_dirents_clone = result_dirents.clone();
-
-  let root_path = Path::new(&walk_root).to_path_buf();
+  let root_path = Path::new(walk_root).to_path_buf();
+  let prefix = root.prefix.as_ref().map(PathBuf::from);
+  let display = root.display.as_ref().map(PathBuf::from);
 
   builder.build_parallel().run(move || {
-    let result_strings = result_strings_clone.clone();
-    let result_dirents = result_dirents_clone.clone();
+    let found = found.clone();
     let root = root_path.clone();
-    let dir_matcher = dir_matcher.clone();
+    let prefix = prefix.clone();
+    let display = display.clone();
+    let patterns = patterns.clone();
+    let policy = policy.clone();
+    let first_error = first_error_clone.clone();
 
     Box::new(move |entry| {
       let entry = match entry {
         Ok(e) => e,
-        Err(_) => return ignore::WalkState::Continue,
+        Err(e) => {
+          let failed = walk_error_path(&e).unwrap_or(&root).to_path_buf();
+          let message = e.to_string();
+          let e = e
+            .into_io_error()
+            .unwrap_or_else(|| io::Error::other(message));
+          if let Err(e) = policy.check(e, "scandir", &failed) {
+            first_error
+              .lock()
+              .unwrap()
+              .get_or_insert_with(|| uv_error(&e, "scandir", &failed));
+            return ignore::WalkState::Quit;
+          }
+          return ignore::WalkState::Continue;
+        }
       };
 
       // 跳过 cwd 根节点自身（depth 0）
@@ -127,82 +367,64 @@ pub fn glob_sync(
 
       let path = entry.path();
       let relative_path = path.strip_prefix(&root).unwrap_or(path);
-      let relative_path_str = relative_path.to_string_lossy().to_string();
+      let mut match_path = match &prefix {
+        Some(prefix) => prefix.join(relative_path).to_string_lossy().to_string(),
+        None => relative_path.to_string_lossy().to_string(),
+      };
+      if cfg!(windows) {
+        match_path = match_path.replace('\\', "/");
+      }
 
-      let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
+      let file_type = entry.file_type();
+      let is_dir = file_type.map(|ft| ft.is_dir()).unwrap_or(false);
 
-      if is_dir {
-        // 目录：ignore crate 只为遍历而产出，需单独测试路径是否符合模式。
-        // 与 Node.js 行为一致：模式 "src/*" 会同时返回 src/ 下的文件和子目录。
-        let matched = dir_matcher.matched(relative_path, true);
-        if !matched.is_whitelist() {
-          // 目录本身不匹配模式，但仍继续遍历以便找到匹配的子条目
-          return ignore::WalkState::Continue;
-        }
-        // 目录匹配模式，加入结果后继续遍历
-      }
-      // 非目录条目：ignore crate 的 override 白名单已确保它们匹配模式
-
-      if with_file_types {
-        let mut lock = result_dirents.lock().unwrap();
-        let parent_path = relative_path
-          .parent()
-          .unwrap_or(Path::new(""))
-          .to_string_lossy()
-          .to_string();
-        let name = relative_path
-          .file_name()
-          .unwrap_or_default()
-          .to_string_lossy()
-          .to_string();
-        let file_type = if let Some(ft) = entry.file_type() {
-          get_file_type_id(&ft)
-        } else {
-          0
+      // 与 Node.js 行为一致：模式 "src/*" 会同时返回 src/ 下的文件和子目录。
+      if patterns
+        .include
+        .iter()
+        .any(|p| p.is_match(&match_path, is_dir))
+        && !patterns
+          .exclude
+          .iter()
+          .any(|p| p.is_match(&match_path, is_dir))
+      {
+        let shown = |path: &Path| match &display {
+          Some(display) => display.join(path).to_string_lossy().to_string(),
+          None => path.to_string_lossy().to_string(),
         };
-        lock.push(Dirent {
-          name,
-          parent_path,
-          file_type,
-        });
-      } else {
-        let mut lock = result_strings.lock().unwrap();
-        lock.push(relative_path_str);
+        let dirent = Dirent {
+          name: relative_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+          parent_path: shown(relative_path.parent().unwrap_or(Path::new(""))),
+          file_type: file_type.map(|ft| get_file_type_id(&ft)).unwrap_or(0),
+        };
+        found.lock().unwrap().push((shown(relative_path), dirent));
       }
 
+      // `dir/**` matching "dir/" means it excludes everything below `dir`.
+      let inside = format!("{}/", match_path);
+      if is_dir && patterns.prune.iter().any(|p| p.is_match(&inside, true)) {
+        return ignore::WalkState::Skip;
+      }
       ignore::WalkState::Continue
     })
   });
 
-  if with_file_types {
-    let mut final_results = Arc::try_unwrap(result_dirents)
-      .map_err(|_| Error::from_reason("Lock error"))?
-      .into_inner()
-      .map_err(|_| Error::from_reason("Mutex error"))?;
-    if let Some(ref prefix) = result_prefix {
-      for d in final_results.iter_mut() {
-        d.parent_path = prefix.join(&d.parent_path).to_string_lossy().to_string();
-      }
-    }
-    Ok(Either::B(final_results))
-  } else {
-    let mut final_results = Arc::try_unwrap(result_strings)
-      .map_err(|_| Error::from_reason("Lock error"))?
-      .into_inner()
-      .map_err(|_| Error::from_reason("Mutex error"))?;
-    if let Some(ref prefix) = result_prefix {
-      for r in final_results.iter_mut() {
-        *r = prefix.join(r.as_str()).to_string_lossy().to_string();
-      }
-    }
-    Ok(Either::A(final_results))
+  let first_error = first_error.lock().unwrap().take();
+  match first_error {
+    Some(e) => Err(e),
+    None => Ok(()),
   }
 }
 
 // ===== Async version =====
 pub struct GlobTask {
-  pub pattern: String,
+  pub patterns: Vec<String>,
   pub options: Option<GlobOptions>,
+  errors: Vec<PathError>,
 }
 
 impl Task for GlobTask {
@@ -210,15 +432,30 @@ impl Task for GlobTask {
   type JsValue = Either<Vec<String>, Vec<Dirent>>;
 
   fn compute(&mut self) -> Result<Self::Output> {
-    glob_sync(self.pattern.clone(), self.options.clone())
+    glob_impl(
+      self.patterns.clone(),
+      self.options.clone(),
+      &mut self.errors,
+    )
   }
 
   fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
     Ok(output)
   }
+
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    Err(aggregate_error(&env, err, std::mem::take(&mut self.errors)))
+  }
 }
 
 #[napi(js_name = "glob")]
-pub fn glob(pattern: String, options: Option<GlobOptions>) -> AsyncTask<GlobTask> {
-  AsyncTask::new(GlobTask { pattern, options })
+pub fn glob(
+  pattern: Either<String, Vec<String>>,
+  options: Option<GlobOptions>,
+) -> AsyncTask<GlobTask> {
+  AsyncTask::new(GlobTask {
+    patterns: pattern_list(pattern),
+    options,
+    errors: Vec::new(),
+  })
 }
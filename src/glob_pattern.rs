@@ -0,0 +1,415 @@
+use napi::bindgen_prelude::*;
+use regex::Regex;
+use std::ops::Range;
+
+/// Upper bound on the patterns one brace expansion may produce (`{1..100000}` and the like).
+const MAX_EXPANSIONS: usize = 10_000;
+
+/// Position of the `}` closing the `{` at `open`, and whether the group is an expansion (has a
+/// top-level `,` or is a `x..y` range). Returns `None` for an unterminated group.
+fn find_brace_group(chars: &[char], open: usize) -> Option<(usize, bool)> {
+  let mut depth = 0;
+  let mut has_comma = false;
+  let mut i = open;
+  while i < chars.len() {
+    match chars[i] {
+      '\\' => i += 1,
+      '{' => depth += 1,
+      '}' => {
+        depth -= 1;
+        if depth == 0 {
+          let body: String = chars[open + 1..i].iter().collect();
+          return Some((i, has_comma || parse_range(&body).is_some()));
+        }
+      }
+      ',' if depth == 1 => has_comma = true,
+      _ => {}
+    }
+    i += 1;
+  }
+  None
+}
+
+/// Splits a brace body at its top-level commas.
+fn split_alternatives(body: &[char]) -> Vec<String> {
+  let mut parts = Vec::new();
+  let mut current = String::new();
+  let mut depth = 0;
+  let mut i = 0;
+  while i < body.len() {
+    let c = body[i];
+    match c {
+      '\\' if i + 1 < body.len() => {
+        current.push(c);
+        current.push(body[i + 1]);
+        i += 2;
+        continue;
+      }
+      '{' => depth += 1,
+      '}' => depth -= 1,
+      ',' if depth == 0 => {
+        parts.push(std::mem::take(&mut current));
+        i += 1;
+        continue;
+      }
+      _ => {}
+    }
+    current.push(c);
+    i += 1;
+  }
+  parts.push(current);
+  parts
+}
+
+/// `{1..10}`, `{01..10..2}` (zero-padded, with a step) and `{a..e}` ranges, like bash.
+fn parse_range(body: &str) -> Option<Vec<String>> {
+  let parts: Vec<&str> = body.split("..").collect();
+  if parts.len() != 2 && parts.len() != 3 {
+    return None;
+  }
+  let step = match parts.get(2) {
+    Some(step) => step.parse::<i64>().ok()?.unsigned_abs().max(1) as usize,
+    None => 1,
+  };
+  if let (Ok(start), Ok(end)) = (parts[0].parse::<i64>(), parts[1].parse::<i64>()) {
+    let padded =
+      |s: &str| s.trim_start_matches('-').len() > 1 && s.trim_start_matches('-').starts_with('0');
+    let width = if padded(parts[0]) || padded(parts[1]) {
+      parts[0].len().max(parts[1].len())
+    } else {
+      0
+    };
+    let count = start.abs_diff(end) / step as u64 + 1;
+    if count > MAX_EXPANSIONS as u64 {
+      return None;
+    }
+    let values = (0..count as usize).map(|i| {
+      let offset = (i * step) as i64;
+      let n = if start <= end {
+        start + offset
+      } else {
+        start - offset
+      };
+      format!("{:0width$}", n, width = width)
+    });
+    return Some(values.collect());
+  }
+  let (mut a, mut b) = (parts[0].chars(), parts[1].chars());
+  match (a.next(), a.next(), b.next(), b.next()) {
+    (Some(start), None, Some(end), None)
+      if start.is_ascii_alphabetic() && end.is_ascii_alphabetic() =>
+    {
+      let (lo, hi) = (start.min(end) as u8, start.max(end) as u8);
+      let mut values: Vec<String> = (lo..=hi)
+        .step_by(step)
+        .map(|c| (c as char).to_string())
+        .collect();
+      if start > end {
+        values.reverse();
+      }
+      Some(values)
+    }
+    _ => None,
+  }
+}
+
+fn expand_into(pattern: &str, out: &mut Vec<String>) -> Result<()> {
+  let chars: Vec<char> = pattern.chars().collect();
+  let mut i = 0;
+  while i < chars.len() {
+    match chars[i] {
+      '\\' => i += 2,
+      '{' => match find_brace_group(&chars, i) {
+        Some((close, true)) => {
+          let head: String = chars[..i].iter().collect();
+          let tail: String = chars[close + 1..].iter().collect();
+          let body: String = chars[i + 1..close].iter().collect();
+          let alternatives =
+            parse_range(&body).unwrap_or_else(|| split_alternatives(&chars[i + 1..close]));
+          for alternative in alternatives {
+            expand_into(&format!("{}{}{}", head, alternative, tail), out)?;
+            if out.len() > MAX_EXPANSIONS {
+              return Err(Error::from_reason(format!(
+                "ERR_INVALID_ARG_VALUE: The brace expansion of pattern '{}' produces more than {} patterns",
+                pattern, MAX_EXPANSIONS
+              )));
+            }
+          }
+          return Ok(());
+        }
+        // `{a}` and unterminated groups stay literal; expand what comes after.
+        _ => i += 1,
+      },
+      _ => i += 1,
+    }
+  }
+  out.push(pattern.to_string());
+  Ok(())
+}
+
+/// Bash-style brace expansion: `src/**/*.{ts,tsx}` becomes `src/**/*.ts` and `src/**/*.tsx`.
+/// Groups nest, `{1..3}` / `{a..c}` ranges are expanded, and `\{` stays literal.
+pub(crate) fn expand_braces(pattern: &str) -> Result<Vec<String>> {
+  let mut out = Vec::new();
+  expand_into(pattern, &mut out)?;
+  Ok(out)
+}
+
+/// Index of the `)` closing the extglob group opened at `open` (the `(`).
+fn find_group_end(chars: &[char], open: usize) -> Option<usize> {
+  let mut depth = 0;
+  let mut i = open;
+  while i < chars.len() {
+    match chars[i] {
+      '\\' => i += 1,
+      '(' => depth += 1,
+      ')' => {
+        depth -= 1;
+        if depth == 0 {
+          return Some(i);
+        }
+      }
+      _ => {}
+    }
+    i += 1;
+  }
+  None
+}
+
+fn split_group(chars: &[char]) -> Vec<Vec<char>> {
+  let mut parts = vec![Vec::new()];
+  let mut depth = 0;
+  let mut i = 0;
+  while i < chars.len() {
+    let c = chars[i];
+    match c {
+      '\\' if i + 1 < chars.len() => {
+        parts.last_mut().unwrap().extend([c, chars[i + 1]]);
+        i += 2;
+        continue;
+      }
+      '(' => depth += 1,
+      ')' => depth -= 1,
+      '|' if depth == 0 => {
+        parts.push(Vec::new());
+        i += 1;
+        continue;
+      }
+      _ => {}
+    }
+    parts.last_mut().unwrap().push(c);
+    i += 1;
+  }
+  parts
+}
+
+/// Translates a glob into a regex. The regex crate has no lookahead, so a `!(...)` group is
+/// compiled as `[^/]*` and recorded in `negations` (where it stands in the output, and its
+/// alternatives as a regex), for `GlobMatcher` to check separately.
+#[derive(Default)]
+struct Translator {
+  negations: Vec<(Range<usize>, String)>,
+  /// Set for a `!(...)` inside another group, which `GlobMatcher` cannot split the path at.
+  nested_negation: bool,
+}
+
+impl Translator {
+  fn translate(&mut self, chars: &[char], out: &mut String, nested: bool) {
+    let mut i = 0;
+    while i < chars.len() {
+      let c = chars[i];
+      let next = chars.get(i + 1).copied();
+      if matches!(c, '@' | '+' | '!' | '*' | '?') && next == Some('(') {
+        if let Some(close) = find_group_end(chars, i + 1) {
+          let alternatives = split_group(&chars[i + 2..close]);
+          let mut group = String::from("(?:");
+          for (n, alternative) in alternatives.iter().enumerate() {
+            if n > 0 {
+              group.push('|');
+            }
+            self.translate(alternative, &mut group, true);
+          }
+          group.push(')');
+          match c {
+            '@' => out.push_str(&group),
+            '?' => out.push_str(&format!("{}?", group)),
+            '+' => out.push_str(&format!("{}+", group)),
+            '*' => out.push_str(&format!("{}*", group)),
+            _ => {
+              self.nested_negation |= nested;
+              let start = out.len();
+              out.push_str("[^/]*");
+              self.negations.push((start..out.len(), group));
+            }
+          }
+          i = close + 1;
+          continue;
+        }
+      }
+      match c {
+        '\\' if next.is_some() => {
+          out.push_str(&regex::escape(&next.unwrap().to_string()));
+          i += 2;
+          continue;
+        }
+        '*' if next == Some('*') => {
+          let at_start = i == 0 || chars[i - 1] == '/';
+          let after = chars.get(i + 2).copied();
+          if at_start && after == Some('/') {
+            out.push_str("(?:.*/)?");
+            i += 3;
+          } else if at_start && after.is_none() {
+            out.push_str(".*");
+            i += 2;
+          } else {
+            out.push_str("[^/]*");
+            i += 2;
+          }
+          continue;
+        }
+        '*' => out.push_str("[^/]*"),
+        '?' => out.push_str("[^/]"),
+        '[' => match translate_class(chars, i) {
+          Some((class, end)) => {
+            out.push_str(&class);
+            i = end + 1;
+            continue;
+          }
+          None => out.push_str("\\["),
+        },
+        _ => out.push_str(&regex::escape(&c.to_string())),
+      }
+      i += 1;
+    }
+  }
+}
+
+/// `[abc]`, `[a-z]`, `[!x]` / `[^x]` starting at `open`; returns the regex class and the index
+/// of the closing `]`.
+fn translate_class(chars: &[char], open: usize) -> Option<(String, usize)> {
+  let mut i = open + 1;
+  let mut class = String::from("[");
+  if matches!(chars.get(i), Some('!') | Some('^')) {
+    class.push('^');
+    i += 1;
+  }
+  let first = i;
+  while i < chars.len() {
+    let c = chars[i];
+    match c {
+      ']' if i > first => {
+        class.push(']');
+        return Some((class, i));
+      }
+      '\\' if i + 1 < chars.len() => {
+        class.push('\\');
+        class.push(chars[i + 1]);
+        i += 1;
+      }
+      '[' | ']' | '&' | '~' | '^' => {
+        class.push('\\');
+        class.push(c);
+      }
+      _ => class.push(c),
+    }
+    i += 1;
+  }
+  None
+}
+
+/// One glob, compiled against paths relative to the glob's cwd (with `/` separators).
+#[derive(Clone)]
+pub(crate) struct GlobMatcher {
+  /// The whole pattern, with `!(...)` groups matching any segment part.
+  regex: Regex,
+  /// With `!(...)` groups: the parts of the pattern around them, each matching a whole
+  /// substring. One more than `negations`.
+  pieces: Vec<Regex>,
+  /// The `!(...)` groups, each matching a whole substring.
+  negations: Vec<Regex>,
+  dir_only: bool,
+}
+
+impl GlobMatcher {
+  /// Compiles `rest`, a pattern relative to the literal directory `prefix`. Like the override
+  /// globs of the `ignore` crate, a pattern without `/` matches at any depth below `prefix`, a
+  /// leading `/` anchors it and a trailing `/` only matches directories.
+  pub(crate) fn new(prefix: Option<&str>, rest: &str) -> Result<Self> {
+    let mut rest = rest;
+    let dir_only = rest.len() > 1 && rest.ends_with('/');
+    if dir_only {
+      rest = rest.trim_end_matches('/');
+    }
+    let body = match rest.strip_prefix('/') {
+      Some(anchored) => anchored.to_string(),
+      None if rest.contains('/') => rest.to_string(),
+      None => format!("**/{}", rest),
+    };
+    let chars: Vec<char> = body.chars().collect();
+    let mut head = String::from("^");
+    if let Some(prefix) = prefix {
+      head.push_str(&regex::escape(&prefix.replace('\\', "/")));
+      head.push('/');
+    }
+
+    let mut translator = Translator::default();
+    let mut source = head;
+    translator.translate(&chars, &mut source, false);
+    source.push('$');
+    if translator.nested_negation {
+      return Err(Error::from_reason(format!(
+        "ERR_INVALID_ARG_VALUE: Invalid glob pattern '{}': !(...) is not supported inside another group",
+        rest
+      )));
+    }
+    let compile = |source: &str| {
+      Regex::new(source).map_err(|e| {
+        Error::from_reason(format!(
+          "ERR_INVALID_ARG_VALUE: Invalid glob pattern '{}': {}",
+          rest, e
+        ))
+      })
+    };
+    let regex = compile(&source)?;
+    let mut pieces = Vec::new();
+    let mut negations = Vec::new();
+    if !translator.negations.is_empty() {
+      let mut start = 0;
+      for (range, group) in &translator.negations {
+        pieces.push(compile(&format!("^(?:{})$", &source[start..range.start]))?);
+        negations.push(compile(&format!("^{}$", group))?);
+        start = range.end;
+      }
+      pieces.push(compile(&format!("^(?:{})$", &source[start..]))?);
+    }
+    Ok(Self {
+      regex,
+      pieces,
+      negations,
+      dir_only,
+    })
+  }
+
+  /// Whether `path[start..]` matches piece `k` and everything after it: the piece ends somewhere,
+  /// then the `!(...)` group `k` takes a run of the same segment that it does not match, like
+  /// bash's extglob, where `!(a)*` matches `abc` with the group taking the empty string.
+  fn matches_from(&self, path: &str, k: usize, start: usize) -> bool {
+    let Some(group) = self.negations.get(k) else {
+      return self.pieces[k].is_match(&path[start..]);
+    };
+    (start..=path.len())
+      .filter(|&end| path.is_char_boundary(end) && self.pieces[k].is_match(&path[start..end]))
+      .any(|end| {
+        let segment_end = path[end..].find('/').map_or(path.len(), |i| end + i);
+        (end..=segment_end)
+          .filter(|&next| path.is_char_boundary(next))
+          .any(|next| !group.is_match(&path[end..next]) && self.matches_from(path, k + 1, next))
+      })
+  }
+
+  pub(crate) fn is_match(&self, path: &str, is_dir: bool) -> bool {
+    (is_dir || !self.dir_only)
+      && self.regex.is_match(path)
+      && (self.negations.is_empty() || self.matches_from(path, 0, 0))
+  }
+}
@@ -0,0 +1,450 @@
+use crate::encoding::base64_encode;
+use crate::glob::{build_overrides, walk_error_path};
+use crate::utils::uv_error;
+use ignore::WalkBuilder;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy)]
+pub(crate) enum HashAlgorithm {
+  Sha256,
+  Blake3,
+  Xxh3,
+}
+
+pub(crate) fn parse_algorithm(algorithm: Option<&str>) -> Result<HashAlgorithm> {
+  match algorithm {
+    None | Some("sha256") => Ok(HashAlgorithm::Sha256),
+    Some("blake3") => Ok(HashAlgorithm::Blake3),
+    Some("xxh3") => Ok(HashAlgorithm::Xxh3),
+    Some(other) => Err(Error::from_reason(format!(
+      "ERR_INVALID_ARG_VALUE: The property 'options.algorithm' must be one of 'sha256', 'blake3', 'xxh3'. Received '{}'",
+      other
+    ))),
+  }
+}
+
+/// Streaming hasher over the supported algorithms. `xxh3` produces the 128-bit variant.
+pub(crate) enum Hasher {
+  Sha256(sha2::Sha256),
+  Blake3(Box<blake3::Hasher>),
+  Xxh3(Box<xxhash_rust::xxh3::Xxh3>),
+}
+
+impl Hasher {
+  pub(crate) fn new(algorithm: HashAlgorithm) -> Self {
+    match algorithm {
+      HashAlgorithm::Sha256 => Hasher::Sha256(<sha2::Sha256 as sha2::Digest>::new()),
+      HashAlgorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+      HashAlgorithm::Xxh3 => Hasher::Xxh3(Box::new(xxhash_rust::xxh3::Xxh3::new())),
+    }
+  }
+
+  pub(crate) fn update(&mut self, data: &[u8]) {
+    match self {
+      Hasher::Sha256(h) => sha2::Digest::update(h, data),
+      Hasher::Blake3(h) => {
+        h.update(data);
+      }
+      Hasher::Xxh3(h) => h.update(data),
+    }
+  }
+
+  pub(crate) fn finalize(self) -> Vec<u8> {
+    match self {
+      Hasher::Sha256(h) => sha2::Digest::finalize(h).to_vec(),
+      Hasher::Blake3(h) => h.finalize().as_bytes().to_vec(),
+      Hasher::Xxh3(h) => h.digest128().to_be_bytes().to_vec(),
+    }
+  }
+}
+
+/// Hashes everything `reader` yields in 64 KiB chunks, so memory stays flat for large files.
+pub(crate) fn hash_reader(
+  algorithm: HashAlgorithm,
+  mut reader: impl Read,
+) -> std::io::Result<Vec<u8>> {
+  let mut hasher = Hasher::new(algorithm);
+  let mut buf = vec![0u8; 64 * 1024];
+  loop {
+    let n = reader.read(&mut buf)?;
+    if n == 0 {
+      break;
+    }
+    hasher.update(&buf[..n]);
+  }
+  Ok(hasher.finalize())
+}
+
+fn encode_digest(digest: Vec<u8>, encoding: Option<&str>) -> Result<Either<String, Buffer>> {
+  match encoding {
+    None | Some("hex") => Ok(Either::A(
+      digest.iter().map(|b| format!("{:02x}", b)).collect(),
+    )),
+    Some("base64") => Ok(Either::A(base64_encode(&digest, false))),
+    Some("base64url") => Ok(Either::A(base64_encode(&digest, true))),
+    Some("buffer") => Ok(Either::B(Buffer::from(digest))),
+    Some(enc) => Err(Error::from_reason(format!("Unknown encoding: {}", enc))),
+  }
+}
+
+fn hash_file_digest(path: &Path, algorithm: HashAlgorithm) -> Result<Vec<u8>> {
+  let file = fs::File::open(path).map_err(|e| uv_error(&e, "open", path))?;
+  hash_reader(algorithm, file).map_err(|e| uv_error(&e, "read", path))
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct HashFileOptions {
+  /// 'sha256' (default), 'blake3' or 'xxh3' (128-bit).
+  pub algorithm: Option<String>,
+  /// 'hex' (default), 'base64', 'base64url' or 'buffer'.
+  pub encoding: Option<String>,
+}
+
+fn hash_file_impl(
+  path_str: String,
+  options: Option<HashFileOptions>,
+) -> Result<Either<String, Buffer>> {
+  let opts = options.unwrap_or(HashFileOptions {
+    algorithm: None,
+    encoding: None,
+  });
+  let algorithm = parse_algorithm(opts.algorithm.as_deref())?;
+  let digest = hash_file_digest(Path::new(&path_str), algorithm)?;
+  encode_digest(digest, opts.encoding.as_deref())
+}
+
+#[napi(js_name = "hashFileSync")]
+pub fn hash_file_sync(
+  path: String,
+  options: Option<HashFileOptions>,
+) -> Result<Either<String, Buffer>> {
+  hash_file_impl(path, options)
+}
+
+pub struct HashFileTask {
+  pub path: String,
+  pub options: Option<HashFileOptions>,
+}
+
+impl Task for HashFileTask {
+  type Output = Either<String, Buffer>;
+  type JsValue = Either<String, Buffer>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    hash_file_impl(self.path.clone(), self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "hashFile")]
+pub fn hash_file(path: String, options: Option<HashFileOptions>) -> AsyncTask<HashFileTask> {
+  AsyncTask::new(HashFileTask { path, options })
+}
+
+// ===== hashTree =====
+//
+// The tree digest is Merkle-style: every directory hashes its children sorted by name as
+// `tag | name length | name | digest length | digest`, where tag is 'f' (file), 'l' (symlink,
+// digest of the link target) or 'd' (directory, recursive digest). The result depends only on
+// names, contents and shape — not on walk order, thread count, mtimes or permissions.
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct HashTreeOptions {
+  /// 'sha256' (default), 'blake3' or 'xxh3' (128-bit).
+  pub algorithm: Option<String>,
+  /// 'hex' (default), 'base64', 'base64url' or 'buffer'.
+  pub encoding: Option<String>,
+  /// Glob patterns (relative to the root) to leave out of the digest.
+  pub exclude: Option<Vec<String>>,
+  /// Respect .gitignore / .ignore files. Defaults to false.
+  pub git_ignore: Option<bool>,
+  /// Threads used for walking and hashing. Defaults to 4.
+  pub concurrency: Option<u32>,
+  /// Hash symlink targets' contents instead of the link paths. Dangling and looping links are
+  /// still hashed by their target path. Defaults to false.
+  pub follow_symlinks: Option<bool>,
+  /// Also return the digest of every file. Defaults to false.
+  pub per_file: Option<bool>,
+}
+
+#[napi(object)]
+pub struct FileHash {
+  /// Path relative to the root, always '/'-separated.
+  pub path: String,
+  pub hash: Either<String, Buffer>,
+}
+
+#[napi(object)]
+pub struct HashTreeResult {
+  pub hash: Either<String, Buffer>,
+  pub files: Option<Vec<FileHash>>,
+}
+
+enum TreeNode {
+  Leaf(u8, Vec<u8>),
+  Dir(BTreeMap<OsString, TreeNode>),
+}
+
+fn insert_node(dir: &mut BTreeMap<OsString, TreeNode>, components: &[OsString], node: TreeNode) {
+  match components {
+    [] => {}
+    [name] => {
+      // A directory may already exist because one of its children was inserted first.
+      if !matches!(
+        (dir.get(name), &node),
+        (Some(TreeNode::Dir(_)), TreeNode::Dir(_))
+      ) {
+        dir.insert(name.clone(), node);
+      }
+    }
+    [name, rest @ ..] => {
+      let entry = dir
+        .entry(name.clone())
+        .or_insert_with(|| TreeNode::Dir(BTreeMap::new()));
+      if let TreeNode::Dir(children) = entry {
+        insert_node(children, rest, node);
+      }
+    }
+  }
+}
+
+/// Names go in as their raw bytes (`as_encoded_bytes`: the OS bytes on unix, WTF-8 on Windows),
+/// so names that differ only in bytes that are not valid UTF-8 hash differently.
+fn dir_digest(algorithm: HashAlgorithm, dir: &BTreeMap<OsString, TreeNode>) -> Vec<u8> {
+  let mut hasher = Hasher::new(algorithm);
+  for (name, node) in dir {
+    let (tag, digest) = match node {
+      TreeNode::Leaf(tag, digest) => (*tag, digest.clone()),
+      TreeNode::Dir(children) => (b'd', dir_digest(algorithm, children)),
+    };
+    hasher.update(&[tag]);
+    let name = name.as_encoded_bytes();
+    hasher.update(&(name.len() as u64).to_le_bytes());
+    hasher.update(name);
+    hasher.update(&(digest.len() as u64).to_le_bytes());
+    hasher.update(&digest);
+  }
+  hasher.finalize()
+}
+
+enum WalkedKind {
+  File,
+  Symlink,
+  Dir,
+}
+
+/// Path components relative to the root, absolute path, and entry kind.
+type WalkedEntry = (Vec<OsString>, PathBuf, WalkedKind);
+
+/// Whether following a symlink failed only because its target is missing or leads into a loop,
+/// rather than because something could not be read.
+fn unfollowable_link(e: &ignore::Error) -> bool {
+  match e {
+    ignore::Error::Loop { .. } => true,
+    ignore::Error::WithPath { err, .. }
+    | ignore::Error::WithDepth { err, .. }
+    | ignore::Error::WithLineNumber { err, .. } => unfollowable_link(err),
+    ignore::Error::Io(e) => {
+      #[cfg(unix)]
+      if e.raw_os_error() == Some(libc::ELOOP) {
+        return true;
+      }
+      e.kind() == io::ErrorKind::NotFound
+    }
+    _ => false,
+  }
+}
+
+fn hash_tree_impl(root_str: String, options: Option<HashTreeOptions>) -> Result<HashTreeResult> {
+  let opts = options.unwrap_or(HashTreeOptions {
+    algorithm: None,
+    encoding: None,
+    exclude: None,
+    git_ignore: None,
+    concurrency: None,
+    follow_symlinks: None,
+    per_file: None,
+  });
+  let algorithm = parse_algorithm(opts.algorithm.as_deref())?;
+  let encoding = opts.encoding.as_deref();
+  let concurrency = opts.concurrency.unwrap_or(4).max(1) as usize;
+  let follow_symlinks = opts.follow_symlinks.unwrap_or(false);
+
+  let root = Path::new(&root_str);
+  match fs::metadata(root) {
+    Ok(m) if m.is_dir() => {}
+    Ok(_) => {
+      return Err(Error::from_reason(format!(
+        "ENOTDIR: not a directory, hashTree '{}'",
+        root_str
+      )))
+    }
+    Err(_) => {
+      return Err(Error::from_reason(format!(
+        "ENOENT: no such file or directory, hashTree '{}'",
+        root_str
+      )))
+    }
+  }
+
+  let overrides = build_overrides(&root_str, &[], opts.exclude.as_deref().unwrap_or_default())?;
+  let mut builder = WalkBuilder::new(root);
+  builder
+    .overrides(overrides)
+    .standard_filters(opts.git_ignore.unwrap_or(false))
+    .follow_links(follow_symlinks)
+    .threads(concurrency);
+
+  let walked: Arc<Mutex<Vec<WalkedEntry>>> = Arc::new(Mutex::new(Vec::new()));
+  let first_error: Arc<Mutex<Option<Error>>> = Arc::new(Mutex::new(None));
+  let root_path = root.to_path_buf();
+
+  builder.build_parallel().run(|| {
+    let walked = walked.clone();
+    let first_error = first_error.clone();
+    let root = root_path.clone();
+    Box::new(move |entry| {
+      let (path, kind) = match entry {
+        Ok(entry) => {
+          if entry.depth() == 0 {
+            return ignore::WalkState::Continue;
+          }
+          let kind = match entry.file_type() {
+            Some(ft) if ft.is_dir() => WalkedKind::Dir,
+            Some(ft) if ft.is_file() => WalkedKind::File,
+            Some(ft) if ft.is_symlink() => WalkedKind::Symlink,
+            // FIFOs, sockets and devices have no stable content to hash.
+            _ => return ignore::WalkState::Continue,
+          };
+          (entry.into_path(), kind)
+        }
+        Err(e) => {
+          let failed = walk_error_path(&e).unwrap_or(&root).to_path_buf();
+          // With `followSymlinks`, a dangling or looping link is hashed as the link itself,
+          // the same way it is without.
+          if unfollowable_link(&e)
+            && fs::symlink_metadata(&failed).is_ok_and(|m| m.file_type().is_symlink())
+          {
+            (failed, WalkedKind::Symlink)
+          } else {
+            let message = e.to_string();
+            let e = e
+              .into_io_error()
+              .unwrap_or_else(|| io::Error::other(message));
+            first_error
+              .lock()
+              .unwrap()
+              .get_or_insert_with(|| uv_error(&e, "scandir", &failed));
+            return ignore::WalkState::Quit;
+          }
+        }
+      };
+      let relative = path.strip_prefix(&root).unwrap_or(&path);
+      let components = relative
+        .components()
+        .map(|c| c.as_os_str().to_os_string())
+        .collect();
+      walked.lock().unwrap().push((components, path, kind));
+      ignore::WalkState::Continue
+    })
+  });
+
+  if let Some(e) = first_error.lock().unwrap().take() {
+    return Err(e);
+  }
+  let walked = std::mem::take(&mut *walked.lock().unwrap());
+
+  let pool = rayon::ThreadPoolBuilder::new()
+    .num_threads(concurrency)
+    .build()
+    .map_err(|e| Error::from_reason(e.to_string()))?;
+  let nodes: Vec<(Vec<OsString>, TreeNode)> = pool.install(|| {
+    walked
+      .into_par_iter()
+      .map(|(components, path, kind)| {
+        let node = match kind {
+          WalkedKind::Dir => TreeNode::Dir(BTreeMap::new()),
+          WalkedKind::File => TreeNode::Leaf(b'f', hash_file_digest(&path, algorithm)?),
+          WalkedKind::Symlink => {
+            let target = fs::read_link(&path).map_err(|e| uv_error(&e, "readlink", &path))?;
+            let mut hasher = Hasher::new(algorithm);
+            hasher.update(target.as_os_str().as_encoded_bytes());
+            TreeNode::Leaf(b'l', hasher.finalize())
+          }
+        };
+        Ok((components, node))
+      })
+      .collect::<Result<_>>()
+  })?;
+
+  let mut tree = BTreeMap::new();
+  let mut files = Vec::new();
+  for (components, node) in nodes {
+    if let TreeNode::Leaf(_, digest) = &node {
+      let path = components.join(OsStr::new("/"));
+      files.push((path.to_string_lossy().to_string(), digest.clone()));
+    }
+    insert_node(&mut tree, &components, node);
+  }
+
+  let hash = encode_digest(dir_digest(algorithm, &tree), encoding)?;
+  let files = if opts.per_file.unwrap_or(false) {
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Some(
+      files
+        .into_iter()
+        .map(|(path, digest)| {
+          Ok(FileHash {
+            path,
+            hash: encode_digest(digest, encoding)?,
+          })
+        })
+        .collect::<Result<_>>()?,
+    )
+  } else {
+    None
+  };
+  Ok(HashTreeResult { hash, files })
+}
+
+#[napi(js_name = "hashTreeSync")]
+pub fn hash_tree_sync(dir: String, options: Option<HashTreeOptions>) -> Result<HashTreeResult> {
+  hash_tree_impl(dir, options)
+}
+
+pub struct HashTreeTask {
+  pub dir: String,
+  pub options: Option<HashTreeOptions>,
+}
+
+impl Task for HashTreeTask {
+  type Output = HashTreeResult;
+  type JsValue = HashTreeResult;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    hash_tree_impl(self.dir.clone(), self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "hashTree")]
+pub fn hash_tree(dir: String, options: Option<HashTreeOptions>) -> AsyncTask<HashTreeTask> {
+  AsyncTask::new(HashTreeTask { dir, options })
+}
@@ -1,52 +1,95 @@
 #![deny(clippy::all)]
 
 pub mod access;
+pub mod archive;
 pub mod chmod;
 pub mod chown;
+pub(crate) mod cleanup;
+pub mod compression;
 pub mod copy_file;
 pub mod cp;
+pub mod detect_encoding;
+pub mod dry_run;
+pub mod du;
+pub mod duplicates;
+pub mod encoding;
 pub mod exists;
+pub mod fallocate;
 pub mod glob;
+pub(crate) mod glob_pattern;
+pub mod hash;
+pub mod line_index;
 pub mod link;
+pub mod lock;
 pub mod mkdir;
 pub mod mkdtemp;
+pub mod mmap;
+pub(crate) mod on_error;
+#[cfg(unix)]
+pub(crate) mod openat;
 pub mod read_file;
+pub mod read_lines;
 pub mod readdir;
 pub mod readlink;
 pub mod realpath;
 pub mod rename;
 pub mod rm;
 pub mod rmdir;
+pub mod root;
+pub mod search;
 pub mod stat;
 pub mod symlink;
+pub mod tar_archive;
+pub mod tmpdir;
+pub mod trash;
 pub mod truncate;
 pub mod types;
 pub mod unlink;
 pub mod utils;
 pub mod utimes;
 pub mod write_file;
+pub mod xattr;
+pub mod zip_archive;
 
 pub use access::*;
+pub use archive::*;
 pub use chmod::*;
 pub use chown::*;
 pub use copy_file::*;
 pub use cp::*;
+pub use detect_encoding::*;
+pub use dry_run::*;
+pub use du::*;
+pub use duplicates::*;
 pub use exists::*;
+pub use fallocate::*;
 pub use glob::*;
+pub use hash::*;
+pub use line_index::*;
 pub use link::*;
+pub use lock::*;
 pub use mkdir::*;
 pub use mkdtemp::*;
+pub use mmap::*;
 pub use read_file::*;
+pub use read_lines::*;
 pub use readdir::*;
 pub use readlink::*;
 pub use realpath::*;
 pub use rename::*;
 pub use rm::*;
 pub use rmdir::*;
+pub use root::*;
+pub use search::*;
 pub use stat::*;
 pub use symlink::*;
+pub use tar_archive::*;
+pub use tmpdir::*;
+pub use trash::*;
 pub use truncate::*;
 pub use types::*;
 pub use unlink::*;
 pub use utimes::*;
 pub use write_file::*;
+pub use xattr::*;
+pub use zip_archive::*;
@@ -0,0 +1,306 @@
+use crate::hash::{hash_reader, HashAlgorithm};
+use crate::read_lines::{encode_lines, parse_line_encoding};
+use crate::utils::uv_error;
+use memchr::memchr_iter;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Chunk size used when scanning for newlines.
+const SCAN_CHUNK: usize = 1024 * 1024;
+/// Leading bytes fingerprinted to notice a file rewritten in place (or a reused inode).
+const FINGERPRINT_LEN: u64 = 4096;
+
+#[cfg(unix)]
+fn file_identity(meta: &fs::Metadata) -> (u64, u64) {
+  use std::os::unix::fs::MetadataExt;
+  (meta.dev(), meta.ino())
+}
+
+#[cfg(not(unix))]
+fn file_identity(_meta: &fs::Metadata) -> (u64, u64) {
+  (0, 0)
+}
+
+struct IndexState {
+  /// `starts[n]` is the byte offset of line `n + 1`. Always begins with 0.
+  starts: Vec<u64>,
+  /// Number of bytes scanned so far.
+  scanned: u64,
+  /// `(dev, ino)` of the indexed file, to notice when it is replaced (log rotation).
+  identity: (u64, u64),
+  /// xxh3 of the first `min(scanned, FINGERPRINT_LEN)` bytes.
+  fingerprint: Vec<u8>,
+}
+
+impl IndexState {
+  fn count(&self) -> usize {
+    // The last entry of `starts` is a real line only if some bytes follow the final newline.
+    if self.starts.last() == Some(&self.scanned) {
+      self.starts.len() - 1
+    } else {
+      self.starts.len()
+    }
+  }
+}
+
+/// Shared between the `LineIndex` and its tasks on the libuv thread pool. The state lock is only
+/// held to read or apply offsets, never across I/O, so `lineCount` does not wait for a scan;
+/// `scanning` keeps two refreshes from appending the same bytes twice.
+struct Shared {
+  path: String,
+  state: RwLock<IndexState>,
+  scanning: Mutex<()>,
+}
+
+fn fingerprint(file: &mut fs::File, scanned: u64) -> std::io::Result<Vec<u8>> {
+  file.seek(SeekFrom::Start(0))?;
+  hash_reader(
+    HashAlgorithm::Xxh3,
+    file.by_ref().take(scanned.min(FINGERPRINT_LEN)),
+  )
+}
+
+/// Scans the bytes appended since the last scan, or the whole file again if it shrank, was
+/// replaced, or its first bytes changed. Returns the new line count.
+fn refresh_impl(shared: &Shared) -> Result<u32> {
+  let _scanning = shared.scanning.lock().unwrap();
+  let path = Path::new(&shared.path);
+  let (mut scanned, identity, old_fingerprint) = {
+    let state = shared.state.read().unwrap();
+    (state.scanned, state.identity, state.fingerprint.clone())
+  };
+
+  let mut file = fs::File::open(path).map_err(|e| uv_error(&e, "open", path))?;
+  let meta = file.metadata().map_err(|e| uv_error(&e, "fstat", path))?;
+  if meta.is_dir() {
+    return Err(uv_error(
+      &std::io::Error::from(std::io::ErrorKind::IsADirectory),
+      "read",
+      path,
+    ));
+  }
+  let new_identity = file_identity(&meta);
+  let rewritten = meta.len() < scanned
+    || new_identity != identity
+    || fingerprint(&mut file, scanned).map_err(|e| uv_error(&e, "read", path))? != old_fingerprint;
+  if rewritten {
+    scanned = 0;
+  }
+
+  file
+    .seek(SeekFrom::Start(scanned))
+    .map_err(|e| uv_error(&e, "read", path))?;
+  let mut starts = Vec::new();
+  let mut chunk = vec![0u8; SCAN_CHUNK];
+  loop {
+    let n = file
+      .read(&mut chunk)
+      .map_err(|e| uv_error(&e, "read", path))?;
+    if n == 0 {
+      break;
+    }
+    let base = scanned;
+    starts.extend(memchr_iter(b'\n', &chunk[..n]).map(|i| base + i as u64 + 1));
+    scanned += n as u64;
+  }
+  let new_fingerprint = fingerprint(&mut file, scanned).map_err(|e| uv_error(&e, "read", path))?;
+
+  let mut state = shared.state.write().unwrap();
+  if rewritten {
+    state.starts = vec![0];
+  }
+  state.starts.extend(starts);
+  state.scanned = scanned;
+  state.identity = new_identity;
+  state.fingerprint = new_fingerprint;
+  Ok(state.count() as u32)
+}
+
+fn read_lines_impl(
+  shared: &Shared,
+  from: u32,
+  to: u32,
+  encoding: Option<String>,
+) -> Result<Either<Vec<String>, Vec<Buffer>>> {
+  let encoding = parse_line_encoding(encoding.as_deref())?;
+  let path = Path::new(&shared.path);
+  let (start, end, scanned, identity) = {
+    let state = shared.state.read().unwrap();
+    let to = (to as usize).min(state.count());
+    if from < 1 || to < from as usize {
+      return encode_lines(Vec::new(), encoding);
+    }
+    (
+      state.starts[from as usize - 1],
+      state.starts.get(to).copied().unwrap_or(state.scanned),
+      state.scanned,
+      state.identity,
+    )
+  };
+
+  let mut file = fs::File::open(path).map_err(|e| uv_error(&e, "open", path))?;
+  // The offsets only hold for the file they were taken from, and only while it keeps the bytes
+  // that were scanned.
+  let meta = file.metadata().map_err(|e| uv_error(&e, "fstat", path))?;
+  if file_identity(&meta) != identity || meta.len() < scanned {
+    return Err(Error::from_reason(format!(
+      "ESTALE: file was replaced or truncated since it was indexed, readLines '{}'",
+      shared.path
+    )));
+  }
+  let mut data = vec![0u8; (end - start) as usize];
+  file
+    .seek(SeekFrom::Start(start))
+    .and_then(|_| file.read_exact(&mut data))
+    .map_err(|e| uv_error(&e, "read", path))?;
+
+  if data.last() == Some(&b'\n') {
+    data.pop();
+  }
+  let lines = data
+    .split(|&b| b == b'\n')
+    .map(|l| l.strip_suffix(b"\r").unwrap_or(l).to_vec())
+    .collect();
+  encode_lines(lines, encoding)
+}
+
+/// Byte offsets of every line start in a file, for O(1) seeks to any line.
+/// Lines follow `readLines`: `\n` or `\r\n` separated, and a trailing newline does not start
+/// an empty last line.
+#[napi]
+pub struct LineIndex {
+  shared: Arc<Shared>,
+}
+
+/// Runs one `LineIndex` operation on the libuv thread pool.
+pub struct LineIndexTask<T> {
+  op: Option<Box<dyn FnOnce() -> Result<T> + Send>>,
+}
+
+impl<T: ToNapiValue + TypeName + Send + 'static> Task for LineIndexTask<T> {
+  type Output = T;
+  type JsValue = T;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let op = self.op.take().expect("compute runs once");
+    op()
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+impl LineIndex {
+  fn task<T, F>(&self, op: F) -> AsyncTask<LineIndexTask<T>>
+  where
+    T: ToNapiValue + TypeName + Send + 'static,
+    F: FnOnce(&Shared) -> Result<T> + Send + 'static,
+  {
+    let shared = Arc::clone(&self.shared);
+    AsyncTask::new(LineIndexTask {
+      op: Some(Box::new(move || op(&shared))),
+    })
+  }
+}
+
+#[napi]
+impl LineIndex {
+  /// Number of lines indexed.
+  #[napi(getter, js_name = "lineCount")]
+  pub fn line_count(&self) -> u32 {
+    self.shared.state.read().unwrap().count() as u32
+  }
+
+  /// Number of bytes indexed.
+  #[napi(getter)]
+  pub fn size(&self) -> f64 {
+    self.shared.state.read().unwrap().scanned as f64
+  }
+
+  /// Reads lines `from..=to` (1-based, like `readFile`'s `lines` option) by seeking straight to
+  /// their offset. `to` is clamped to `lineCount`; lines appended after the last `refresh()`
+  /// are not visible. Throws `ESTALE` if the file was replaced or shrank since then.
+  #[napi(js_name = "readLinesSync")]
+  pub fn read_lines_sync(
+    &self,
+    from: u32,
+    to: u32,
+    encoding: Option<String>,
+  ) -> Result<Either<Vec<String>, Vec<Buffer>>> {
+    read_lines_impl(&self.shared, from, to, encoding)
+  }
+
+  #[napi(js_name = "readLines")]
+  pub fn read_lines(
+    &self,
+    from: u32,
+    to: u32,
+    encoding: Option<String>,
+  ) -> AsyncTask<LineIndexTask<Either<Vec<String>, Vec<Buffer>>>> {
+    self.task(move |shared| read_lines_impl(shared, from, to, encoding))
+  }
+
+  /// Extends the index with lines appended since it was built, and returns the new line count.
+  /// A file that shrank, was replaced or whose first bytes changed is indexed again from the start.
+  #[napi(js_name = "refreshSync")]
+  pub fn refresh_sync(&self) -> Result<u32> {
+    refresh_impl(&self.shared)
+  }
+
+  #[napi]
+  pub fn refresh(&self) -> AsyncTask<LineIndexTask<u32>> {
+    self.task(refresh_impl)
+  }
+}
+
+fn build_line_index_impl(path: String) -> Result<LineIndex> {
+  let shared = Shared {
+    path,
+    state: RwLock::new(IndexState {
+      starts: vec![0],
+      scanned: 0,
+      identity: (0, 0),
+      fingerprint: Vec::new(),
+    }),
+    scanning: Mutex::new(()),
+  };
+  refresh_impl(&shared)?;
+  Ok(LineIndex {
+    shared: Arc::new(shared),
+  })
+}
+
+#[napi(js_name = "buildLineIndexSync")]
+pub fn build_line_index_sync(path: String) -> Result<LineIndex> {
+  build_line_index_impl(path)
+}
+
+// ========= async version =========
+
+pub struct BuildLineIndexTask {
+  pub path: String,
+}
+
+impl Task for BuildLineIndexTask {
+  type Output = LineIndex;
+  type JsValue = LineIndex;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    build_line_index_impl(self.path.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "buildLineIndex")]
+pub fn build_line_index(path: String) -> AsyncTask<BuildLineIndexTask> {
+  AsyncTask::new(BuildLineIndexTask { path })
+}
@@ -4,7 +4,7 @@ use napi_derive::napi;
 use std::fs;
 use std::path::Path;
 
-fn link_impl(existing_path: String, new_path: String) -> Result<()> {
+pub(crate) fn link_impl(existing_path: String, new_path: String) -> Result<()> {
   let existing = Path::new(&existing_path);
   let new = Path::new(&new_path);
 
@@ -0,0 +1,478 @@
+use crate::cleanup::{forget_on_exit, remove_on_exit};
+use crate::mkdtemp::generate_random_suffix;
+use crate::utils::uv_error;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+// ========= lock =========
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct LockOptions {
+  /// Take an exclusive (write) lock. Default: true, unless `shared` is set.
+  pub exclusive: Option<bool>,
+  /// Take a shared (read) lock; shorthand for `exclusive: false`.
+  pub shared: Option<bool>,
+  /// Wait for a conflicting lock to go away. With `false`, throw `EAGAIN` right away.
+  /// Default: true.
+  pub wait: Option<bool>,
+  /// Give up with `ETIMEDOUT` after this many milliseconds of waiting. Default: wait forever.
+  pub timeout: Option<u32>,
+  /// `'flock'` (default) locks the whole file with `flock` (`LockFileEx` on Windows). `'fcntl'`
+  /// takes an open file description lock (`F_OFD_SETLK`, Linux only), which also works over NFS
+  /// and needs the file to be writable for exclusive locks.
+  #[napi(ts_type = "'flock' | 'fcntl'")]
+  pub method: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Method {
+  Flock,
+  Fcntl,
+}
+
+/// An advisory lock on a file or directory, held until `release()` is called, the handle is
+/// garbage collected or the process exits. `using` / `await using` release it too.
+#[napi]
+pub struct FileLock {
+  #[napi(readonly)]
+  pub path: String,
+  #[napi(readonly)]
+  pub exclusive: bool,
+  file: Option<fs::File>,
+}
+
+#[napi]
+impl FileLock {
+  /// Releases the lock (closing its descriptor). Releasing twice is a no-op.
+  #[napi]
+  pub fn release(&mut self) {
+    self.file = None;
+  }
+
+  #[napi(getter)]
+  pub fn released(&self) -> bool {
+    self.file.is_none()
+  }
+}
+
+/// An OFD lock covering the whole file. Returns `Ok(false)` when a conflicting lock is held and
+/// `block` is not set.
+#[cfg(target_os = "linux")]
+fn ofd_lock(file: &fs::File, exclusive: bool, block: bool) -> io::Result<bool> {
+  use std::os::fd::AsRawFd;
+  let mut flock: libc::flock = unsafe { std::mem::zeroed() };
+  flock.l_type = if exclusive {
+    libc::F_WRLCK
+  } else {
+    libc::F_RDLCK
+  } as libc::c_short;
+  flock.l_whence = libc::SEEK_SET as libc::c_short;
+  let cmd = if block {
+    libc::F_OFD_SETLKW
+  } else {
+    libc::F_OFD_SETLK
+  };
+  loop {
+    if unsafe { libc::fcntl(file.as_raw_fd(), cmd, &flock) } == 0 {
+      return Ok(true);
+    }
+    let e = io::Error::last_os_error();
+    match e.raw_os_error() {
+      Some(libc::EINTR) => continue,
+      Some(libc::EAGAIN) | Some(libc::EACCES) if !block => return Ok(false),
+      _ => return Err(e),
+    }
+  }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn ofd_lock(_file: &fs::File, _exclusive: bool, _block: bool) -> io::Result<bool> {
+  Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+fn try_lock(file: &fs::File, method: Method, exclusive: bool, block: bool) -> io::Result<bool> {
+  if method == Method::Fcntl {
+    return ofd_lock(file, exclusive, block);
+  }
+  if block {
+    if exclusive {
+      file.lock()?;
+    } else {
+      file.lock_shared()?;
+    }
+    return Ok(true);
+  }
+  let result = if exclusive {
+    file.try_lock()
+  } else {
+    file.try_lock_shared()
+  };
+  match result {
+    Ok(()) => Ok(true),
+    Err(fs::TryLockError::WouldBlock) => Ok(false),
+    Err(fs::TryLockError::Error(e)) => Err(e),
+  }
+}
+
+/// An opened lock target with its parsed options, ready for lock attempts.
+struct LockRequest {
+  path: String,
+  file: fs::File,
+  method: Method,
+  exclusive: bool,
+  wait: bool,
+  timeout: Option<u32>,
+}
+
+impl LockRequest {
+  fn new(path: String, options: Option<LockOptions>) -> Result<Self> {
+    let opts = options.unwrap_or(LockOptions {
+      exclusive: None,
+      shared: None,
+      wait: None,
+      timeout: None,
+      method: None,
+    });
+    let method = match opts.method.as_deref() {
+      None | Some("flock") => Method::Flock,
+      Some("fcntl") => Method::Fcntl,
+      Some(other) => {
+        return Err(Error::from_reason(format!(
+          "ERR_INVALID_ARG_VALUE: The property 'options.method' must be one of 'flock', 'fcntl'. Received '{}'",
+          other
+        )))
+      }
+    };
+    let shared = opts.shared.unwrap_or(false);
+    if shared && opts.exclusive == Some(true) {
+      return Err(Error::from_reason(
+        "ERR_INVALID_ARG_VALUE: The properties 'options.exclusive' and 'options.shared' cannot both be true",
+      ));
+    }
+    let exclusive = opts.exclusive.unwrap_or(!shared);
+
+    // Locks go on an existing file or directory; fcntl write locks need a writable descriptor.
+    let file = fs::OpenOptions::new()
+      .read(true)
+      .write(method == Method::Fcntl && exclusive)
+      .open(&path)
+      .map_err(|e| uv_error(&e, "lock", Path::new(&path)))?;
+    Ok(LockRequest {
+      path,
+      file,
+      method,
+      exclusive,
+      wait: opts.wait.unwrap_or(true),
+      timeout: opts.timeout,
+    })
+  }
+
+  fn error(&self, e: io::Error) -> Error {
+    uv_error(&e, "lock", Path::new(&self.path))
+  }
+
+  /// One attempt that never blocks. A conflicting lock is `Ok(false)` while waiting and `EAGAIN`
+  /// otherwise.
+  fn try_acquire(&self) -> Result<bool> {
+    let acquired =
+      try_lock(&self.file, self.method, self.exclusive, false).map_err(|e| self.error(e))?;
+    if !acquired && !self.wait {
+      return Err(self.error(io::Error::from(io::ErrorKind::WouldBlock)));
+    }
+    Ok(acquired)
+  }
+
+  fn into_lock(self) -> FileLock {
+    FileLock {
+      path: self.path,
+      exclusive: self.exclusive,
+      file: Some(self.file),
+    }
+  }
+}
+
+/// Delays between lock attempts: no portable timed lock exists, so waiters poll with a backoff
+/// capped at 100ms until `timeout` runs out.
+struct Backoff {
+  deadline: Option<Instant>,
+  timeout: Option<u32>,
+  delay: Duration,
+}
+
+impl Backoff {
+  fn new(timeout: Option<u32>) -> Self {
+    Backoff {
+      deadline: timeout.map(|ms| Instant::now() + Duration::from_millis(ms as u64)),
+      timeout,
+      delay: Duration::from_millis(5),
+    }
+  }
+
+  /// How long to sleep before the next attempt, or `ETIMEDOUT` once the deadline has passed.
+  fn next(&mut self, path: &str) -> Result<Duration> {
+    let mut delay = self.delay;
+    if let (Some(deadline), Some(timeout)) = (self.deadline, self.timeout) {
+      let now = Instant::now();
+      if now >= deadline {
+        return Err(Error::from_reason(format!(
+          "ETIMEDOUT: lock not acquired within {}ms, lock '{}'",
+          timeout, path
+        )));
+      }
+      delay = delay.min(deadline - now);
+    }
+    self.delay = (self.delay * 2).min(Duration::from_millis(100));
+    Ok(delay)
+  }
+}
+
+#[napi(js_name = "lockSync")]
+pub fn lock_sync(path: String, options: Option<LockOptions>) -> Result<FileLock> {
+  let request = LockRequest::new(path, options)?;
+  if request.wait && request.timeout.is_none() {
+    try_lock(&request.file, request.method, request.exclusive, true)
+      .map_err(|e| request.error(e))?;
+    return Ok(request.into_lock());
+  }
+  let mut backoff = Backoff::new(request.timeout);
+  while !request.try_acquire()? {
+    std::thread::sleep(backoff.next(&request.path)?);
+  }
+  Ok(request.into_lock())
+}
+
+/// Waiting always polls here: a blocking lock would hold a threadpool worker until the holder
+/// lets go, and a few waiters would stall every other async call, the holder's included.
+#[napi(js_name = "lock")]
+pub async fn lock(path: String, options: Option<LockOptions>) -> Result<FileLock> {
+  let request = LockRequest::new(path, options)?;
+  let mut backoff = Backoff::new(request.timeout);
+  while !request.try_acquire()? {
+    tokio::time::sleep(backoff.next(&request.path)?).await;
+  }
+  Ok(request.into_lock())
+}
+
+// ========= lockfile =========
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct LockfileOptions {
+  /// Treat an existing lockfile whose mtime is older than this many milliseconds as abandoned
+  /// and take it over. Default: never.
+  pub stale: Option<f64>,
+  /// How many more times to try while the lockfile exists. Default: 0.
+  pub retries: Option<u32>,
+  /// Milliseconds between retries. Default: 100.
+  pub retry_wait: Option<u32>,
+}
+
+/// A lockfile created by `lockfile`: the file exists while the lock is held. `release()`,
+/// garbage collection of the handle, process exit and `using` / `await using` remove it, unless
+/// another process has taken it over as stale in the meantime.
+#[napi]
+pub struct Lockfile {
+  #[napi(readonly)]
+  pub path: String,
+  /// Content written on creation (`<pid>\n<random>\n`), which tells our lockfile apart from a
+  /// new one at the same path even when the filesystem reuses the inode.
+  token: String,
+  held: bool,
+}
+
+impl Lockfile {
+  fn is_ours(&self) -> bool {
+    self.held && fs::read_to_string(&self.path).is_ok_and(|content| content == self.token)
+  }
+}
+
+#[napi]
+impl Lockfile {
+  /// Removes the lockfile if it is still ours. Releasing twice is a no-op.
+  #[napi]
+  pub fn release(&mut self) -> Result<()> {
+    let ours = self.is_ours();
+    self.held = false;
+    forget_on_exit(Path::new(&self.path));
+    if ours {
+      fs::remove_file(&self.path).map_err(|e| uv_error(&e, "unlink", Path::new(&self.path)))?;
+    }
+    Ok(())
+  }
+
+  /// Sets the lockfile's mtime to now, so that holding it for longer than `stale` does not let
+  /// others take it over. Throws `ECOMPROMISED` when it was released, removed or taken over.
+  #[napi]
+  pub fn touch(&self) -> Result<()> {
+    if !self.is_ours() {
+      return Err(Error::from_reason(format!(
+        "ECOMPROMISED: lock was released, removed or taken over, lockfile '{}'",
+        self.path
+      )));
+    }
+    fs::OpenOptions::new()
+      .write(true)
+      .open(&self.path)
+      .and_then(|file| file.set_modified(SystemTime::now()))
+      .map_err(|e| uv_error(&e, "utime", Path::new(&self.path)))
+  }
+
+  #[napi(getter)]
+  pub fn released(&self) -> bool {
+    !self.held
+  }
+}
+
+impl Drop for Lockfile {
+  fn drop(&mut self) {
+    let _ = self.release();
+  }
+}
+
+#[cfg(unix)]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+  use std::os::unix::fs::MetadataExt;
+  (a.dev(), a.ino()) == (b.dev(), b.ino()) && a.modified().ok() == b.modified().ok()
+}
+
+#[cfg(not(unix))]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+  a.modified().ok() == b.modified().ok() && a.len() == b.len()
+}
+
+/// Removes `path` if it is still older than `stale`. The lockfile is renamed away first and
+/// put back when it turns out to be a fresh lock another process created in between, so two
+/// processes breaking the same stale lock cannot delete each other's new one. Returns whether
+/// the stale lockfile is gone.
+fn break_stale(path: &Path, stale: Duration) -> io::Result<bool> {
+  let meta = match fs::symlink_metadata(path) {
+    Ok(meta) => meta,
+    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(true),
+    Err(e) => return Err(e),
+  };
+  let age = SystemTime::now()
+    .duration_since(meta.modified()?)
+    .unwrap_or_default();
+  if age < stale {
+    return Ok(false);
+  }
+  let mut aside = path.as_os_str().to_owned();
+  aside.push(format!(
+    ".{}.{}.stale",
+    std::process::id(),
+    generate_random_suffix()
+  ));
+  match fs::rename(path, &aside) {
+    Ok(()) => {}
+    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(true),
+    Err(e) => return Err(e),
+  }
+  if !same_file(&fs::symlink_metadata(&aside)?, &meta) {
+    // When `path` was created again meanwhile the link fails; the aside copy is left in place
+    // rather than deleting a lock its owner still holds.
+    fs::hard_link(&aside, path)?;
+    fs::remove_file(&aside)?;
+    return Ok(false);
+  }
+  fs::remove_file(&aside)?;
+  Ok(true)
+}
+
+struct LockfileRequest {
+  path: String,
+  stale: Option<Duration>,
+  retries: u32,
+  retry_wait: Duration,
+}
+
+impl LockfileRequest {
+  fn new(path: String, options: Option<LockfileOptions>) -> Self {
+    let opts = options.unwrap_or(LockfileOptions {
+      stale: None,
+      retries: None,
+      retry_wait: None,
+    });
+    LockfileRequest {
+      path,
+      stale: opts
+        .stale
+        .map(|stale| Duration::from_millis(stale.max(0.0) as u64)),
+      retries: opts.retries.unwrap_or(0),
+      retry_wait: Duration::from_millis(opts.retry_wait.unwrap_or(100) as u64),
+    }
+  }
+
+  fn error(&self, e: io::Error) -> Error {
+    uv_error(&e, "lockfile", Path::new(&self.path))
+  }
+
+  /// Creates the lockfile, breaking a stale one first. Fails with `AlreadyExists` while it is
+  /// held by someone else.
+  fn try_create(&self) -> io::Result<Lockfile> {
+    let path = Path::new(&self.path);
+    loop {
+      match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+      {
+        Ok(mut file) => {
+          let token = format!("{}\n{}\n", std::process::id(), generate_random_suffix());
+          if let Err(e) = file.write_all(token.as_bytes()) {
+            // An empty lockfile would block everyone else until it goes stale, if ever.
+            drop(file);
+            let _ = fs::remove_file(path);
+            return Err(e);
+          }
+          remove_on_exit(path, Some(&token));
+          return Ok(Lockfile {
+            path: self.path.clone(),
+            token,
+            held: true,
+          });
+        }
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => match self.stale {
+          Some(stale) if break_stale(path, stale)? => continue,
+          _ => return Err(e),
+        },
+        Err(e) => return Err(e),
+      }
+    }
+  }
+}
+
+#[napi(js_name = "lockfileSync")]
+pub fn lockfile_sync(path: String, options: Option<LockfileOptions>) -> Result<Lockfile> {
+  let request = LockfileRequest::new(path, options);
+  let mut attempt = 0;
+  loop {
+    match request.try_create() {
+      Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < request.retries => {
+        attempt += 1;
+        std::thread::sleep(request.retry_wait);
+      }
+      result => return result.map_err(|e| request.error(e)),
+    }
+  }
+}
+
+/// Retries sleep on the runtime's timer instead of holding a threadpool worker, like `lock`.
+#[napi(js_name = "lockfile")]
+pub async fn lockfile(path: String, options: Option<LockfileOptions>) -> Result<Lockfile> {
+  let request = LockfileRequest::new(path, options);
+  let mut attempt = 0;
+  loop {
+    match request.try_create() {
+      Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < request.retries => {
+        attempt += 1;
+        tokio::time::sleep(request.retry_wait).await;
+      }
+      result => return result.map_err(|e| request.error(e)),
+    }
+  }
+}
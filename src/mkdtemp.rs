@@ -1,12 +1,28 @@
+use crate::cleanup::remove_on_exit;
+use crate::utils::uv_error;
 use napi::bindgen_prelude::*;
 use napi::Task;
 use napi_derive::napi;
 use std::fs;
 use std::path::Path;
 
+#[napi(object)]
+#[derive(Clone)]
+pub struct MkstempOptions {
+  /// Remove the file when the process exits, if it is still there. Default: false.
+  pub remove_on_exit: Option<bool>,
+}
+
+#[napi(object)]
+pub struct MkstempResult {
+  pub path: String,
+  /// Open read/write descriptor of the new file, for `fs.write` / `fs.close`.
+  pub fd: i32,
+}
+
 /// Generate a cryptographically seeded random 6-char suffix using OS random bytes.
 /// Falls back to time-based entropy if the OS call fails.
-fn generate_random_suffix() -> String {
+pub(crate) fn generate_random_suffix() -> String {
   const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
   let mut bytes = [0u8; 6];
 
@@ -76,7 +92,7 @@ fn fill_fallback(bytes: &mut [u8]) {
   }
 }
 
-fn mkdtemp_impl(prefix: String) -> Result<String> {
+pub(crate) fn mkdtemp_impl(prefix: String) -> Result<String> {
   if let Some(parent) = Path::new(&prefix).parent() {
     if !parent.as_os_str().is_empty() && !parent.exists() {
       return Err(Error::from_reason(format!(
@@ -133,3 +149,99 @@ impl Task for MkdtempTask {
 pub fn mkdtemp(prefix: String) -> AsyncTask<MkdtempTask> {
   AsyncTask::new(MkdtempTask { prefix })
 }
+
+// ========= mkstemp =========
+
+/// Hands the descriptor of `file` over to JS.
+#[cfg(unix)]
+fn into_fd(file: fs::File, _path: &str) -> Result<i32> {
+  use std::os::fd::IntoRawFd;
+  Ok(file.into_raw_fd())
+}
+
+#[cfg(windows)]
+fn into_fd(file: fs::File, path: &str) -> Result<i32> {
+  use std::os::windows::io::IntoRawHandle;
+  extern "C" {
+    fn _open_osfhandle(osfhandle: isize, flags: i32) -> i32;
+  }
+  const O_RDWR: i32 = 2;
+  let fd = unsafe { _open_osfhandle(file.into_raw_handle() as isize, O_RDWR) };
+  if fd < 0 {
+    return Err(Error::from_reason(format!(
+      "EMFILE: too many open files, mkstemp '{}'",
+      path
+    )));
+  }
+  Ok(fd)
+}
+
+fn mkstemp_impl(prefix: String, options: Option<MkstempOptions>) -> Result<MkstempResult> {
+  if let Some(parent) = Path::new(&prefix).parent() {
+    if !parent.as_os_str().is_empty() && !parent.exists() {
+      return Err(Error::from_reason(format!(
+        "ENOENT: no such file or directory, mkstemp '{}'",
+        prefix
+      )));
+    }
+  }
+
+  // create_new is O_CREAT | O_EXCL, and std opens everything with O_CLOEXEC.
+  let mut open = fs::OpenOptions::new();
+  open.read(true).write(true).create_new(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    open.mode(0o600);
+  }
+
+  for _ in 0..10 {
+    let path = format!("{}{}", prefix, generate_random_suffix());
+    match open.open(&path) {
+      Ok(file) => {
+        let fd = into_fd(file, &path)?;
+        if options.and_then(|o| o.remove_on_exit).unwrap_or(false) {
+          remove_on_exit(Path::new(&path), None);
+        }
+        return Ok(MkstempResult { path, fd });
+      }
+      Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+      Err(e) => return Err(uv_error(&e, "mkstemp", Path::new(&path))),
+    }
+  }
+
+  Err(Error::from_reason(format!(
+    "EEXIST: could not create unique temporary file after 10 attempts, mkstemp '{}'",
+    prefix
+  )))
+}
+
+/// Creates `prefix` + 6 random characters exclusively (`O_CREAT | O_EXCL | O_CLOEXEC`, mode
+/// `0o600`) and returns its path with an open descriptor; closing it is up to the caller.
+#[napi(js_name = "mkstempSync")]
+pub fn mkstemp_sync(prefix: String, options: Option<MkstempOptions>) -> Result<MkstempResult> {
+  mkstemp_impl(prefix, options)
+}
+
+pub struct MkstempTask {
+  pub prefix: String,
+  pub options: Option<MkstempOptions>,
+}
+
+impl Task for MkstempTask {
+  type Output = MkstempResult;
+  type JsValue = MkstempResult;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    mkstemp_impl(self.prefix.clone(), self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "mkstemp")]
+pub fn mkstemp(prefix: String, options: Option<MkstempOptions>) -> AsyncTask<MkstempTask> {
+  AsyncTask::new(MkstempTask { prefix, options })
+}
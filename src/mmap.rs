@@ -0,0 +1,162 @@
+use crate::utils::uv_error;
+use memmap2::{MmapMut, MmapOptions as MapOptions};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::ffi::c_void;
+use std::fs;
+use std::path::Path;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+/// The mapping behind one Buffer. Taken (and so unmapped) by `close()` or the GC finalizer,
+/// whichever comes first.
+type SharedMapping = Arc<Mutex<Option<MmapMut>>>;
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct MmapFileOptions {
+  /// Byte offset in the file where the mapping starts. Defaults to 0.
+  pub offset: Option<f64>,
+  /// Number of bytes to map. Defaults to (and is clamped to) the rest of the file.
+  pub length: Option<f64>,
+}
+
+unsafe extern "C" fn finalize_mapping(_env: sys::napi_env, _data: *mut c_void, hint: *mut c_void) {
+  let mapping = unsafe { Box::from_raw(hint.cast::<SharedMapping>()) };
+  mapping.lock().unwrap().take();
+}
+
+/// `buffer.close()`: detaches the Buffer's ArrayBuffer so JS can no longer reach the memory
+/// (its length becomes 0), then unmaps it. Calling it again is a no-op.
+fn close_mapping(ctx: FunctionCallContext, mapping: &SharedMapping, data: usize) -> Result<()> {
+  if mapping.lock().unwrap().is_none() {
+    return Ok(());
+  }
+  let env = ctx.env.raw();
+  let this: Unknown = ctx.this()?;
+  let mut this_data = ptr::null_mut();
+  let mut array_buffer = ptr::null_mut();
+  let status = unsafe {
+    sys::napi_get_typedarray_info(
+      env,
+      this.raw(),
+      ptr::null_mut(),
+      ptr::null_mut(),
+      &mut this_data,
+      &mut array_buffer,
+      ptr::null_mut(),
+    )
+  };
+  if status != sys::Status::napi_ok || this_data as usize != data {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "close() must be called on the Buffer returned by mmapFile".to_owned(),
+    ));
+  }
+  check_status!(
+    unsafe { sys::napi_detach_arraybuffer(env, array_buffer) },
+    "Failed to detach mapped Buffer"
+  )?;
+  // Detaching may already have run the finalizer; the lock is not held across it.
+  mapping.lock().unwrap().take();
+  Ok(())
+}
+
+/// Maps `length` bytes at `offset` and wraps them in an external Buffer. Also returns the
+/// mapping and its address for `close()`, unless the runtime forced a copy.
+fn map_into_buffer<'env>(
+  env: &'env Env,
+  file: &fs::File,
+  offset: u64,
+  length: usize,
+  path: &Path,
+) -> Result<(BufferSlice<'env>, Option<(SharedMapping, usize)>)> {
+  // A private copy-on-write mapping: JS may write to the Buffer without touching the file.
+  let mut map = unsafe { MapOptions::new().offset(offset).len(length).map_copy(file) }
+    .map_err(|e| uv_error(&e, "mmap", path))?;
+  let data = map.as_mut_ptr();
+  let mapping: SharedMapping = Arc::new(Mutex::new(Some(map)));
+  let hint = Box::into_raw(Box::new(mapping.clone()));
+
+  let mut raw = ptr::null_mut();
+  let status = unsafe {
+    sys::napi_create_external_buffer(
+      env.raw(),
+      length,
+      data.cast(),
+      Some(finalize_mapping),
+      hint.cast(),
+      &mut raw,
+    )
+  };
+  if status != sys::Status::napi_ok {
+    // The finalizer will never run.
+    drop(unsafe { Box::from_raw(hint) });
+  }
+  if status == sys::Status::napi_no_external_buffers_allowed {
+    // Runtimes with a V8 sandbox (Electron) reject external memory: fall back to a copy.
+    let map = mapping.lock().unwrap().take();
+    let buffer = BufferSlice::copy_from(env, map.as_deref().unwrap_or_default())?;
+    return Ok((buffer, None));
+  }
+  check_status!(status, "Failed to create external Buffer for mapping")?;
+  let buffer = unsafe { BufferSlice::from_napi_value(env.raw(), raw)? };
+  Ok((buffer, Some((mapping, data as usize))))
+}
+
+#[napi(js_name = "mmapFile", ts_return_type = "Buffer & { close(): void }")]
+pub fn mmap_file<'env>(
+  env: &'env Env,
+  path: String,
+  options: Option<MmapFileOptions>,
+) -> Result<BufferSlice<'env>> {
+  let opts = options.unwrap_or(MmapFileOptions {
+    offset: None,
+    length: None,
+  });
+  let path = Path::new(&path);
+  let file = fs::File::open(path).map_err(|e| uv_error(&e, "open", path))?;
+  let meta = file.metadata().map_err(|e| uv_error(&e, "fstat", path))?;
+  if meta.is_dir() {
+    return Err(uv_error(
+      &std::io::Error::from(std::io::ErrorKind::IsADirectory),
+      "mmap",
+      path,
+    ));
+  }
+
+  let size = meta.len();
+  let offset = opts.offset.unwrap_or(0.0);
+  if !(offset >= 0.0 && offset.fract() == 0.0) || offset as u64 > size {
+    return Err(Error::from_reason(format!(
+      "ERR_OUT_OF_RANGE: The value of \"offset\" is out of range. It must be an integer >= 0 and <= {}. Received {}",
+      size, offset
+    )));
+  }
+  let offset = offset as u64;
+  // Mapping past EOF would SIGBUS on access, so never map more than the file holds.
+  let length = match opts.length {
+    Some(length) if length < 0.0 || length.fract() != 0.0 => {
+      return Err(Error::from_reason(format!(
+        "ERR_OUT_OF_RANGE: The value of \"length\" is out of range. It must be an integer >= 0. Received {}",
+        length
+      )))
+    }
+    Some(length) => (length as u64).min(size - offset),
+    None => size - offset,
+  } as usize;
+
+  let (mut buffer, mapping) = if length == 0 {
+    // Zero-length mappings are invalid; an empty Buffer behaves the same.
+    (BufferSlice::copy_from(env, [])?, None)
+  } else {
+    map_into_buffer(env, &file, offset, length, path)?
+  };
+  let close =
+    env.create_function_from_closure::<(), (), _>("close", move |ctx| match &mapping {
+      Some((mapping, data)) => close_mapping(ctx, mapping, *data),
+      None => Ok(()),
+    })?;
+  buffer.set_named_property("close", close)?;
+  Ok(buffer)
+}
@@ -0,0 +1,134 @@
+use crate::utils::{errno_name, uv_error};
+use napi::bindgen_prelude::*;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// `onError` of the traversing APIs (readdir, glob, cp, rm).
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+  Throw,
+  Skip,
+  Collect,
+}
+
+/// One failure recorded under `onError: 'collect'`, shaped like a Node.js fs error.
+pub(crate) struct PathError {
+  code: String,
+  message: String,
+  syscall: String,
+  path: String,
+}
+
+/// Decides what happens to an error met on one entry of a traversal: `'throw'` (default) aborts
+/// with it, `'skip'` drops it and carries on, `'collect'` carries on and reports every error as
+/// an `AggregateError` once the traversal is over.
+pub(crate) struct ErrorPolicy {
+  mode: Mode,
+  collected: Mutex<Vec<PathError>>,
+}
+
+impl ErrorPolicy {
+  pub(crate) fn new(on_error: Option<&str>) -> Result<Self> {
+    let mode = match on_error {
+      None | Some("throw") => Mode::Throw,
+      Some("skip") => Mode::Skip,
+      Some("collect") => Mode::Collect,
+      Some(other) => {
+        return Err(Error::from_reason(format!(
+          "ERR_INVALID_ARG_VALUE: The property 'options.onError' must be one of 'throw', 'skip', 'collect'. Received '{}'",
+          other
+        )))
+      }
+    };
+    Ok(Self {
+      mode,
+      collected: Mutex::new(Vec::new()),
+    })
+  }
+
+  pub(crate) fn throws(&self) -> bool {
+    self.mode == Mode::Throw
+  }
+
+  fn record(&self, code: &str, message: String, syscall: &str, path: &Path) {
+    if self.mode == Mode::Collect {
+      self.collected.lock().unwrap().push(PathError {
+        code: code.to_string(),
+        message,
+        syscall: syscall.to_string(),
+        path: path.to_string_lossy().to_string(),
+      });
+    }
+  }
+
+  /// Applies the policy to an OS error at `path`: hands it back under `'throw'`, otherwise
+  /// records it (for `'collect'`) and returns `Ok` so the caller moves on.
+  pub(crate) fn check(&self, e: io::Error, syscall: &str, path: &Path) -> io::Result<()> {
+    if self.throws() {
+      return Err(e);
+    }
+    let code = errno_name(&e).map_or("EIO", |(code, _)| code);
+    self.record(code, uv_error(&e, syscall, path).reason, syscall, path);
+    Ok(())
+  }
+
+  /// `check` for errors that are not OS errors and come formatted with their own `code`
+  /// (`ERR_FS_CP_*` and the like).
+  pub(crate) fn check_coded(&self, code: &str, e: Error, syscall: &str, path: &Path) -> Result<()> {
+    if self.throws() {
+      return Err(e);
+    }
+    self.record(code, e.reason, syscall, path);
+    Ok(())
+  }
+
+  /// Ends the traversal of `path`: with errors collected, fails with a summary and moves them to
+  /// `errors` for `aggregate_error` to turn into an `AggregateError`.
+  pub(crate) fn finish<T>(
+    self,
+    result: Result<T>,
+    syscall: &str,
+    path: &str,
+    errors: &mut Vec<PathError>,
+  ) -> Result<T> {
+    let collected = self.collected.into_inner().unwrap();
+    if result.is_err() || collected.is_empty() {
+      return result;
+    }
+    let count = collected.len();
+    *errors = collected;
+    Err(Error::from_reason(format!(
+      "{} error{} occurred, {} '{}'",
+      count,
+      if count == 1 { "" } else { "s" },
+      syscall,
+      path
+    )))
+  }
+}
+
+fn build_aggregate(env: &Env, message: &str, errors: Vec<PathError>) -> Result<Error> {
+  let mut list = Vec::with_capacity(errors.len());
+  for error in errors {
+    let mut object = env.create_error(Error::from_reason(error.message))?;
+    object.set_named_property("code", error.code)?;
+    object.set_named_property("syscall", error.syscall)?;
+    object.set_named_property("path", error.path)?;
+    list.push(object);
+  }
+  let constructor: Function<FnArgs<(Vec<Object>, &str)>, Unknown> =
+    env.get_global()?.get_named_property("AggregateError")?;
+  let aggregate = constructor.new_instance((list, message).into())?;
+  Ok(Error::from(aggregate))
+}
+
+/// Turns the error of an `onError: 'collect'` run (see `ErrorPolicy::finish`) into a JS
+/// `AggregateError` whose `errors` carry `code`, `syscall` and `path`. Other errors pass through.
+pub(crate) fn aggregate_error(env: &Env, err: Error, errors: Vec<PathError>) -> Error {
+  if errors.is_empty() {
+    return err;
+  }
+  let message = err.reason.clone();
+  build_aggregate(env, &message, errors).unwrap_or(err)
+}
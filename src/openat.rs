@@ -0,0 +1,603 @@
+//! Path resolution confined beneath a directory fd.
+//!
+//! On Linux 5.6+ every lookup goes through `openat2(RESOLVE_BENEATH | RESOLVE_NO_MAGICLINKS)`, so
+//! the kernel itself refuses `..`, absolute symlinks and `/proc` magic links that would leave the
+//! directory. Elsewhere (and on older kernels) the same rules are enforced by walking the path one
+//! component at a time with `openat(O_NOFOLLOW)` and expanding symlinks by hand: every directory
+//! we step into is held open, so swapping a component for a symlink mid-walk cannot redirect us.
+
+use crate::dry_run::Planner;
+use std::collections::VecDeque;
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::io;
+use std::os::fd::{AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Component, Path};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Same limit as Linux's MAXSYMLINKS.
+const MAX_SYMLINKS: usize = 40;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) const DIR_FLAGS: libc::c_int = libc::O_PATH | libc::O_DIRECTORY | libc::O_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) const DIR_FLAGS: libc::c_int = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC;
+
+fn cvt(ret: libc::c_int) -> io::Result<libc::c_int> {
+  if ret < 0 {
+    Err(io::Error::last_os_error())
+  } else {
+    Ok(ret)
+  }
+}
+
+fn errno(code: i32) -> io::Error {
+  io::Error::from_raw_os_error(code)
+}
+
+pub(crate) fn cstring(name: &OsStr) -> io::Result<CString> {
+  CString::new(name.as_bytes()).map_err(|_| errno(libc::EINVAL))
+}
+
+/// Opens `dir` itself as a directory fd to resolve paths against.
+pub(crate) fn open_dir(dir: &Path) -> io::Result<OwnedFd> {
+  let path = cstring(dir.as_os_str())?;
+  let fd = cvt(unsafe {
+    libc::open(
+      path.as_ptr(),
+      libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+    )
+  })?;
+  Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+pub(crate) fn openat(
+  dir: BorrowedFd,
+  name: &CStr,
+  flags: libc::c_int,
+  mode: u32,
+) -> io::Result<OwnedFd> {
+  let fd = cvt(unsafe {
+    libc::openat(
+      dir.as_raw_fd(),
+      name.as_ptr(),
+      flags | libc::O_CLOEXEC,
+      mode as libc::c_uint,
+    )
+  })?;
+  Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// `fstatat(AT_SYMLINK_NOFOLLOW)`: describes `name` itself, never what it points to.
+pub(crate) fn lstatat(dir: BorrowedFd, name: &CStr) -> io::Result<libc::stat> {
+  let mut st = std::mem::MaybeUninit::<libc::stat>::uninit();
+  cvt(unsafe {
+    libc::fstatat(
+      dir.as_raw_fd(),
+      name.as_ptr(),
+      st.as_mut_ptr(),
+      libc::AT_SYMLINK_NOFOLLOW,
+    )
+  })?;
+  Ok(unsafe { st.assume_init() })
+}
+
+fn readlinkat(dir: BorrowedFd, name: &CStr) -> io::Result<OsString> {
+  let mut buf = vec![0u8; 256];
+  loop {
+    let n = unsafe {
+      libc::readlinkat(
+        dir.as_raw_fd(),
+        name.as_ptr(),
+        buf.as_mut_ptr().cast(),
+        buf.len(),
+      )
+    };
+    if n < 0 {
+      return Err(io::Error::last_os_error());
+    }
+    let n = n as usize;
+    if n < buf.len() {
+      buf.truncate(n);
+      return Ok(OsString::from_vec(buf));
+    }
+    buf.resize(buf.len() * 2, 0);
+  }
+}
+
+pub(crate) fn mkdirat(dir: BorrowedFd, name: &CStr, mode: u32) -> io::Result<()> {
+  cvt(unsafe { libc::mkdirat(dir.as_raw_fd(), name.as_ptr(), mode as libc::mode_t) }).map(drop)
+}
+
+pub(crate) fn unlinkat(dir: BorrowedFd, name: &CStr, remove_dir: bool) -> io::Result<()> {
+  let flags = if remove_dir { libc::AT_REMOVEDIR } else { 0 };
+  cvt(unsafe { libc::unlinkat(dir.as_raw_fd(), name.as_ptr(), flags) }).map(drop)
+}
+
+/// Maps `st_mode` to the Dirent file type ids (see `types::Dirent`).
+pub(crate) fn mode_type_id(mode: libc::mode_t) -> u8 {
+  match mode & libc::S_IFMT {
+    libc::S_IFREG => 1,
+    libc::S_IFDIR => 2,
+    libc::S_IFLNK => 3,
+    libc::S_IFBLK => 4,
+    libc::S_IFCHR => 5,
+    libc::S_IFIFO => 6,
+    libc::S_IFSOCK => 7,
+    _ => 0,
+  }
+}
+
+fn dirent_type_id(d_type: u8) -> u8 {
+  match d_type {
+    libc::DT_REG => 1,
+    libc::DT_DIR => 2,
+    libc::DT_LNK => 3,
+    libc::DT_BLK => 4,
+    libc::DT_CHR => 5,
+    libc::DT_FIFO => 6,
+    libc::DT_SOCK => 7,
+    _ => 0,
+  }
+}
+
+/// `readdir` only signals errors through errno, so it has to be cleared first.
+fn clear_errno() {
+  #[cfg(any(target_os = "linux", target_os = "emscripten"))]
+  unsafe {
+    *libc::__errno_location() = 0
+  };
+  #[cfg(target_os = "android")]
+  unsafe {
+    *libc::__errno() = 0
+  };
+  #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+  unsafe {
+    *libc::__error() = 0
+  };
+}
+
+/// Lists the directory `dir` (consumed), returning each entry's name and Dirent file type id.
+/// Entries the filesystem does not type are resolved with `fstatat`, without following symlinks.
+pub(crate) fn read_dir(dir: OwnedFd) -> io::Result<Vec<(OsString, u8)>> {
+  let raw = dir.into_raw_fd();
+  let dirp = unsafe { libc::fdopendir(raw) };
+  if dirp.is_null() {
+    let err = io::Error::last_os_error();
+    unsafe { libc::close(raw) };
+    return Err(err);
+  }
+  let fd = unsafe { BorrowedFd::borrow_raw(raw) };
+  let mut entries = Vec::new();
+  let result = loop {
+    clear_errno();
+    let entry = unsafe { libc::readdir(dirp) };
+    if entry.is_null() {
+      let err = io::Error::last_os_error();
+      break match err.raw_os_error() {
+        Some(0) | None => Ok(()),
+        Some(_) => Err(err),
+      };
+    }
+    let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+    let bytes = name.to_bytes();
+    if bytes == b"." || bytes == b".." {
+      continue;
+    }
+    let mut type_id = dirent_type_id(unsafe { (*entry).d_type });
+    if type_id == 0 {
+      type_id = match lstatat(fd, name) {
+        Ok(st) => mode_type_id(st.st_mode),
+        Err(_) => 0,
+      };
+    }
+    entries.push((OsString::from_vec(bytes.to_vec()), type_id));
+  };
+  unsafe { libc::closedir(dirp) };
+  result.map(|_| entries)
+}
+
+/// Decides what happens to an error met at a path while removing a tree: `Err` aborts the
+/// removal, `Ok` moves on to the next entry.
+pub(crate) type ErrorHook<'a> = &'a (dyn Fn(io::Error, &Path) -> io::Result<()> + Sync);
+
+/// Removes `name` inside `dir` without ever following a symlink: directories are descended
+/// through an `O_NOFOLLOW` fd, so a directory swapped for a symlink mid-removal fails instead of
+/// deleting whatever the link points at. `parallel` removes each directory's children on the
+/// current rayon pool.
+pub(crate) fn remove_at(
+  dir: BorrowedFd,
+  name: &CStr,
+  recursive: bool,
+  parallel: bool,
+) -> io::Result<()> {
+  let removal = Removal {
+    parallel,
+    plan: None,
+    removed: None,
+    hook: &|e, _| Err(e),
+  };
+  remove_at_with(dir, name, Path::new(""), recursive, &removal).map(|_| ())
+}
+
+/// How `remove_at_with` removes a tree.
+pub(crate) struct Removal<'a> {
+  /// Remove each directory's children on the current rayon pool.
+  pub(crate) parallel: bool,
+  /// Unlink nothing: record every entry that would be instead, after the same lookups, so
+  /// unreadable directories fail (and reach `hook`) just like a real removal.
+  pub(crate) plan: Option<&'a Planner>,
+  /// Counts the entries unlinked.
+  pub(crate) removed: Option<&'a AtomicU32>,
+  /// Gets each failure along with its path.
+  pub(crate) hook: ErrorHook<'a>,
+}
+
+/// `remove_at` with the options of `removal` (`path` names `name`). Returns whether `name` is
+/// gone: when the hook lets an error through, the entries above it stay.
+pub(crate) fn remove_at_with(
+  dir: BorrowedFd,
+  name: &CStr,
+  path: &Path,
+  recursive: bool,
+  removal: &Removal,
+) -> io::Result<bool> {
+  remove_entry(dir, name, path, recursive, 0, removal)
+}
+
+/// Directories this many levels below the removed one still have their children removed in
+/// parallel (with `parallel`); deeper ones are emptied one entry at a time, see `remove_children`.
+const PARALLEL_DEPTH: usize = 4;
+
+const REMOVE_DIR_FLAGS: libc::c_int = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_NOFOLLOW;
+
+impl Removal<'_> {
+  fn failed(&self, e: io::Error, path: &Path) -> io::Result<bool> {
+    (self.hook)(e, path).map(|_| false)
+  }
+
+  /// Unlinks `name` inside `dir`, or records it in the plan.
+  fn unlink(&self, dir: BorrowedFd, name: &CStr, path: &Path, st: &libc::stat) -> io::Result<bool> {
+    let is_dir = st.st_mode & libc::S_IFMT == libc::S_IFDIR;
+    if let Some(plan) = self.plan {
+      let bytes = if st.st_mode & libc::S_IFMT == libc::S_IFREG {
+        st.st_size as u64
+      } else {
+        0
+      };
+      plan.remove(path, bytes);
+      return Ok(true);
+    }
+    if let Err(e) = unlinkat(dir, name, is_dir) {
+      return self.failed(e, path);
+    }
+    if let Some(removed) = self.removed {
+      removed.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(true)
+  }
+}
+
+/// Removes `name` inside `dir`, `depth` levels below the top of the removal.
+fn remove_entry(
+  dir: BorrowedFd,
+  name: &CStr,
+  path: &Path,
+  recursive: bool,
+  depth: usize,
+  removal: &Removal,
+) -> io::Result<bool> {
+  let st = match lstatat(dir, name) {
+    Ok(st) => st,
+    Err(e) => return removal.failed(e, path),
+  };
+  if st.st_mode & libc::S_IFMT != libc::S_IFDIR {
+    return removal.unlink(dir, name, path, &st);
+  }
+  if recursive {
+    let child = match openat(dir, name, REMOVE_DIR_FLAGS, 0) {
+      Ok(child) => child,
+      Err(e) => return removal.failed(e, path),
+    };
+    if !remove_children(child, path, depth + 1, removal)?.0 {
+      return Ok(false);
+    }
+  } else if removal.plan.is_some() {
+    // What rmdir would say about a non-empty directory.
+    match openat(dir, name, REMOVE_DIR_FLAGS, 0).and_then(read_dir) {
+      Ok(entries) if entries.is_empty() => {}
+      Ok(_) => return removal.failed(errno(libc::ENOTEMPTY), path),
+      Err(e) => return removal.failed(e, path),
+    }
+  }
+  removal.unlink(dir, name, path, &st)
+}
+
+/// `(st_dev, st_ino)` of the directory `dir`.
+fn dir_id(dir: BorrowedFd) -> io::Result<(u64, u64)> {
+  let st = lstatat(dir, c".")?;
+  Ok((st.st_dev as u64, st.st_ino as u64))
+}
+
+/// Empties the directory `dir` (named `path`, `depth` levels down), see `remove_at_with`. Returns
+/// whether it is now empty, along with an fd of it.
+///
+/// Near the top of a `parallel` removal, `dir` stays open while its children are removed on the
+/// rayon pool. Below that, children are removed one at a time and `dir` is closed while a
+/// subdirectory is emptied, then reopened through the subdirectory's `..` (which must still be
+/// the same directory): only the directories on the current path hold an fd, so neither depth
+/// nor width can run the process out of them.
+fn remove_children(
+  dir: OwnedFd,
+  path: &Path,
+  depth: usize,
+  removal: &Removal,
+) -> io::Result<(bool, OwnedFd)> {
+  use rayon::prelude::*;
+  use std::os::fd::AsFd;
+
+  let entries = match dir.try_clone().and_then(read_dir) {
+    Ok(entries) => entries,
+    Err(e) => return removal.failed(e, path).map(|removed| (removed, dir)),
+  };
+  if removal.parallel && depth < PARALLEL_DEPTH {
+    let removed = entries
+      .par_iter()
+      .map(|(entry, _)| {
+        let child = path.join(entry);
+        match cstring(entry) {
+          Ok(name) => remove_entry(dir.as_fd(), &name, &child, true, depth, removal),
+          Err(e) => removal.failed(e, &child),
+        }
+      })
+      .try_reduce(|| true, |a, b| Ok(a && b))?;
+    return Ok((removed, dir));
+  }
+
+  let id = dir_id(dir.as_fd())?;
+  let mut dir = dir;
+  let mut removed = true;
+  for (entry, _) in &entries {
+    let child = path.join(entry);
+    let name = match cstring(entry) {
+      Ok(name) => name,
+      Err(e) => {
+        removed &= removal.failed(e, &child)?;
+        continue;
+      }
+    };
+    let st = match lstatat(dir.as_fd(), &name) {
+      Ok(st) => st,
+      Err(e) => {
+        removed &= removal.failed(e, &child)?;
+        continue;
+      }
+    };
+    if st.st_mode & libc::S_IFMT != libc::S_IFDIR {
+      removed &= removal.unlink(dir.as_fd(), &name, &child, &st)?;
+      continue;
+    }
+    let sub = match openat(dir.as_fd(), &name, REMOVE_DIR_FLAGS, 0) {
+      Ok(sub) => sub,
+      Err(e) => {
+        removed &= removal.failed(e, &child)?;
+        continue;
+      }
+    };
+    drop(dir);
+    let (emptied, sub) = remove_children(sub, &child, depth + 1, removal)?;
+    dir = openat(sub.as_fd(), c"..", REMOVE_DIR_FLAGS, 0)?;
+    if dir_id(dir.as_fd())? != id {
+      // Moved while we were below it: the names left to remove no longer live here.
+      return Err(errno(libc::ENOENT));
+    }
+    drop(sub);
+    removed &= emptied && removal.unlink(dir.as_fd(), &name, &child, &st)?;
+  }
+  Ok((removed, dir))
+}
+
+// ========= openat2 =========
+
+#[cfg(target_os = "linux")]
+mod openat2 {
+  use super::*;
+  use std::sync::atomic::{AtomicBool, Ordering};
+
+  /// `struct open_how`; libc's is `#[non_exhaustive]`, so it cannot be built from here.
+  #[repr(C)]
+  struct OpenHow {
+    flags: u64,
+    mode: u64,
+    resolve: u64,
+  }
+
+  static UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+
+  /// Returns `None` when the kernel lacks openat2 (or seccomp hides it), so callers fall back.
+  pub(super) fn open(
+    dir: BorrowedFd,
+    path: &CStr,
+    flags: libc::c_int,
+    mode: u32,
+  ) -> Option<io::Result<OwnedFd>> {
+    if UNSUPPORTED.load(Ordering::Relaxed) {
+      return None;
+    }
+    let how = OpenHow {
+      flags: (flags | libc::O_CLOEXEC) as u64,
+      mode: if flags & (libc::O_CREAT | libc::O_TMPFILE) != 0 {
+        mode as u64
+      } else {
+        0
+      },
+      resolve: libc::RESOLVE_BENEATH | libc::RESOLVE_NO_MAGICLINKS,
+    };
+    loop {
+      let fd = unsafe {
+        libc::syscall(
+          libc::SYS_openat2,
+          dir.as_raw_fd(),
+          path.as_ptr(),
+          &how as *const OpenHow,
+          std::mem::size_of::<OpenHow>(),
+        )
+      };
+      if fd >= 0 {
+        return Some(Ok(unsafe { OwnedFd::from_raw_fd(fd as libc::c_int) }));
+      }
+      let err = io::Error::last_os_error();
+      match err.raw_os_error() {
+        // Raced with a rename elsewhere on the system; the kernel asks us to retry.
+        Some(libc::EAGAIN) | Some(libc::EINTR) => continue,
+        Some(libc::ENOSYS) => {
+          UNSUPPORTED.store(true, Ordering::Relaxed);
+          return None;
+        }
+        _ => return Some(Err(err)),
+      }
+    }
+  }
+}
+
+// ========= component-wise fallback =========
+
+/// Walks `path` beneath `root`, returning the directory holding its final component and that
+/// component's name. Intermediate symlinks are always expanded (relative to the directory they
+/// live in); the final one only when `follow` is set. `..` past the root and absolute symlinks
+/// fail with EXDEV, like RESOLVE_BENEATH. A path naming a directory (`.`, `a/..`) resolves to
+/// that directory and `.`.
+fn resolve(root: BorrowedFd, path: &Path, follow: bool) -> io::Result<(OwnedFd, CString)> {
+  let mut pending: VecDeque<OsString> = VecDeque::new();
+  push_components(&mut pending, path, false)?;
+
+  let mut stack: Vec<OwnedFd> = vec![root.try_clone_to_owned()?];
+  let mut links = 0;
+  while let Some(component) = pending.pop_front() {
+    if component == ".." {
+      if stack.len() == 1 {
+        return Err(errno(libc::EXDEV));
+      }
+      stack.pop();
+      continue;
+    }
+    let name = cstring(&component)?;
+    let top = stack.last().expect("root stays on the stack");
+    use std::os::fd::AsFd;
+    let last = pending.is_empty();
+    let st = match lstatat(top.as_fd(), &name) {
+      Ok(st) => st,
+      // The final component may not exist yet: the caller decides whether to create it.
+      Err(e) if last && e.raw_os_error() == Some(libc::ENOENT) => {
+        return Ok((stack.pop().expect("non-empty"), name));
+      }
+      Err(e) => return Err(e),
+    };
+    let kind = st.st_mode & libc::S_IFMT;
+    if kind == libc::S_IFLNK && (follow || !last) {
+      links += 1;
+      if links > MAX_SYMLINKS {
+        return Err(errno(libc::ELOOP));
+      }
+      let target = readlinkat(top.as_fd(), &name)?;
+      push_components(&mut pending, Path::new(&target), true)?;
+      continue;
+    }
+    if last {
+      return Ok((stack.pop().expect("non-empty"), name));
+    }
+    if kind != libc::S_IFDIR {
+      return Err(errno(libc::ENOTDIR));
+    }
+    match openat(top.as_fd(), &name, DIR_FLAGS | libc::O_NOFOLLOW, 0) {
+      Ok(fd) => stack.push(fd),
+      // Swapped for a symlink (or a file) since the fstatat: look at it again.
+      Err(e) if matches!(e.raw_os_error(), Some(libc::ELOOP) | Some(libc::ENOTDIR)) => {
+        links += 1;
+        if links > MAX_SYMLINKS {
+          return Err(e);
+        }
+        pending.push_front(component);
+      }
+      Err(e) => return Err(e),
+    }
+  }
+  Ok((stack.pop().expect("non-empty"), c".".to_owned()))
+}
+
+fn push_components(pending: &mut VecDeque<OsString>, path: &Path, front: bool) -> io::Result<()> {
+  let mut parts = Vec::new();
+  for component in path.components() {
+    match component {
+      Component::Prefix(_) | Component::RootDir => return Err(errno(libc::EXDEV)),
+      Component::CurDir => {}
+      Component::ParentDir => parts.push(OsString::from("..")),
+      Component::Normal(name) => parts.push(name.to_os_string()),
+    }
+  }
+  if front {
+    for part in parts.into_iter().rev() {
+      pending.push_front(part);
+    }
+  } else {
+    pending.extend(parts);
+  }
+  Ok(())
+}
+
+fn check_relative(path: &Path) -> io::Result<CString> {
+  if path.as_os_str().is_empty() {
+    return Err(errno(libc::ENOENT));
+  }
+  if path.is_absolute() {
+    return Err(errno(libc::EXDEV));
+  }
+  cstring(path.as_os_str())
+}
+
+/// Opens `path` beneath `root` with `open(2)` flags. Symlinks are followed as long as they stay
+/// inside the root; add `O_NOFOLLOW` to refuse a symlink as the final component.
+pub(crate) fn open_beneath(
+  root: BorrowedFd,
+  path: &Path,
+  flags: libc::c_int,
+  mode: u32,
+) -> io::Result<OwnedFd> {
+  let c_path = check_relative(path)?;
+  #[cfg(target_os = "linux")]
+  if let Some(result) = openat2::open(root, &c_path, flags, mode) {
+    return result;
+  }
+  #[cfg(not(target_os = "linux"))]
+  let _ = c_path;
+
+  use std::os::fd::AsFd;
+  let follow = flags & libc::O_NOFOLLOW == 0;
+  let mut attempts = 0;
+  loop {
+    let (dir, name) = resolve(root, path, follow)?;
+    match openat(dir.as_fd(), &name, flags | libc::O_NOFOLLOW, mode) {
+      // The final component became a symlink after we resolved it: resolve it again.
+      Err(e) if follow && e.raw_os_error() == Some(libc::ELOOP) && attempts < MAX_SYMLINKS => {
+        attempts += 1;
+      }
+      result => return result,
+    }
+  }
+}
+
+/// Opens the directory that will hold `path`'s final component, for operations that act on a name
+/// (mkdirat, unlinkat). The final component itself is never followed and must be a plain name.
+pub(crate) fn parent_beneath(root: BorrowedFd, path: &Path) -> io::Result<(OwnedFd, CString)> {
+  check_relative(path)?;
+  let name = match path.components().next_back() {
+    Some(Component::Normal(name)) => cstring(name)?,
+    _ => return Err(errno(libc::EINVAL)),
+  };
+  let parent = path.parent().unwrap_or(Path::new(""));
+  if parent.as_os_str().is_empty() {
+    return Ok((root.try_clone_to_owned()?, name));
+  }
+  let dir = open_beneath(root, parent, DIR_FLAGS, 0)?;
+  Ok((dir, name))
+}
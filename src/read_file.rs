@@ -1,64 +1,12 @@
+use crate::compression::{codec_for_read, decoder, decompress_error, parse_compression};
+use crate::encoding::{decode_to_js, parse_optional};
 use napi::bindgen_prelude::*;
 use napi::Task;
 use napi_derive::napi;
 use std::fs;
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 
-fn decode_data(data: Vec<u8>, encoding: Option<&str>) -> Result<Either<String, Buffer>> {
-  match encoding {
-    Some("utf8" | "utf-8") => {
-      let s =
-        String::from_utf8(data).map_err(|e| Error::from_reason(format!("Invalid UTF-8: {}", e)))?;
-      Ok(Either::A(s))
-    }
-    Some("ascii") => {
-      let s: String = data.iter().map(|&b| (b & 0x7f) as char).collect();
-      Ok(Either::A(s))
-    }
-    Some("latin1" | "binary") => {
-      let s: String = data.iter().map(|&b| b as char).collect();
-      Ok(Either::A(s))
-    }
-    Some("base64") => Ok(Either::A(base64_encode(&data, false))),
-    Some("base64url") => Ok(Either::A(base64_encode(&data, true))),
-    Some("hex") => {
-      let s: String = data.iter().map(|b| format!("{:02x}", b)).collect();
-      Ok(Either::A(s))
-    }
-    Some(enc) => Err(Error::from_reason(format!("Unknown encoding: {}", enc))),
-    None => Ok(Either::B(Buffer::from(data))),
-  }
-}
-
-fn base64_encode(data: &[u8], url_safe: bool) -> String {
-  const STD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-  const URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
-  let table = if url_safe { URL } else { STD };
-
-  let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
-  let chunks = data.chunks(3);
-  for chunk in chunks {
-    let b0 = chunk[0] as u32;
-    let b1 = if chunk.len() > 1 { chunk[1] as u32 } else { 0 };
-    let b2 = if chunk.len() > 2 { chunk[2] as u32 } else { 0 };
-    let triple = (b0 << 16) | (b1 << 8) | b2;
-
-    result.push(table[((triple >> 18) & 0x3F) as usize] as char);
-    result.push(table[((triple >> 12) & 0x3F) as usize] as char);
-    if chunk.len() > 1 {
-      result.push(table[((triple >> 6) & 0x3F) as usize] as char);
-    } else if !url_safe {
-      result.push('=');
-    }
-    if chunk.len() > 2 {
-      result.push(table[(triple & 0x3F) as usize] as char);
-    } else if !url_safe {
-      result.push('=');
-    }
-  }
-  result
-}
-
 #[napi(object)]
 #[derive(Clone)]
 pub struct LineRange {
@@ -72,9 +20,12 @@ pub struct ReadFileOptions {
   pub encoding: Option<String>,
   pub flag: Option<String>,
   pub lines: Option<LineRange>,
+  /// Decompress the file: 'gzip', 'zstd', 'brotli', or 'auto' to detect gzip / zstd by their
+  /// magic bytes and brotli by a `.br` extension (anything else is read as is).
+  pub compression: Option<String>,
 }
 
-fn normalize_read_file_options(
+pub(crate) fn normalize_read_file_options(
   options: Option<Either<String, ReadFileOptions>>,
 ) -> ReadFileOptions {
   match options {
@@ -82,80 +33,64 @@ fn normalize_read_file_options(
       encoding: Some(encoding),
       flag: None,
       lines: None,
+      compression: None,
     },
     Some(Either::B(opts)) => opts,
     None => ReadFileOptions {
       encoding: None,
       flag: None,
       lines: None,
+      compression: None,
     },
   }
 }
 
-fn read_file_with_lines(
-  path: &Path,
-  open_opts: &mut fs::OpenOptions,
-  range: LineRange,
-  encoding: Option<&str>,
-) -> Result<String> {
-  use std::io::{BufRead, BufReader};
+/// Checks the options before the file is opened, so bad ones never create or truncate anything.
+pub(crate) fn validate_read_file_options(opts: &ReadFileOptions) -> Result<()> {
+  let encoding = parse_optional(opts.encoding.as_deref())?;
+  parse_compression(opts.compression.as_deref())?;
+  if opts.lines.is_some() && encoding.is_none() {
+    return Err(Error::from_reason(
+      "ERR_INVALID_ARG_VALUE: The 'lines' option requires an encoding; Buffer results have no lines",
+    ));
+  }
+  Ok(())
+}
 
+/// Returns the bytes of lines `range.from..=range.to` joined by `\n`, reading no further than
+/// the last requested line.
+fn read_file_with_lines(mut reader: impl BufRead, range: LineRange) -> std::io::Result<Vec<u8>> {
+  let mut result = Vec::new();
   if range.from < 1 || range.to < range.from {
-    return Ok(String::new());
+    return Ok(result);
   }
 
-  let file = open_opts.open(path).map_err(|e| {
-    if e.kind() == std::io::ErrorKind::NotFound {
-      Error::from_reason(format!(
-        "ENOENT: no such file or directory, open '{}'",
-        path.to_string_lossy()
-      ))
-    } else if e.kind() == std::io::ErrorKind::PermissionDenied {
-      Error::from_reason(format!(
-        "EACCES: permission denied, open '{}'",
-        path.to_string_lossy()
-      ))
-    } else if e.kind() == std::io::ErrorKind::AlreadyExists {
-      Error::from_reason(format!(
-        "EEXIST: file already exists, open '{}'",
-        path.to_string_lossy()
-      ))
-    } else {
-      Error::from_reason(e.to_string())
-    }
-  })?;
-
-  let reader = BufReader::with_capacity(64 * 1024, file);
-  let mut result = String::new();
+  let mut line = Vec::new();
   let mut current_line: u32 = 0;
 
-  for line_result in reader.lines() {
-    let line = line_result.map_err(|e| Error::from_reason(e.to_string()))?;
-    current_line += 1;
-
-    if current_line > range.to {
+  // Lines are collected as raw bytes so every encoding decodes the same bytes readFile would.
+  while current_line < range.to {
+    line.clear();
+    let n = reader.read_until(b'\n', &mut line)?;
+    if n == 0 {
       break;
     }
-
+    current_line += 1;
     if current_line >= range.from {
-      if !result.is_empty() {
-        result.push('\n');
+      if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+          line.pop();
+        }
       }
-      result.push_str(&line);
+      if current_line > range.from {
+        result.push(b'\n');
+      }
+      result.extend_from_slice(&line);
     }
   }
 
-  // Apply encoding transformation if needed
-  if encoding.is_some() && encoding != Some("utf8") && encoding != Some("utf-8") {
-    let bytes = result.into_bytes();
-    let decoded = decode_data(bytes, encoding)?;
-    match decoded {
-      Either::A(s) => Ok(s),
-      Either::B(_) => Ok(String::new()),
-    }
-  } else {
-    Ok(result)
-  }
+  Ok(result)
 }
 
 fn read_file_impl(
@@ -165,6 +100,7 @@ fn read_file_impl(
   let path = Path::new(&path_str);
   let opts = normalize_read_file_options(options);
 
+  validate_read_file_options(&opts)?;
   let flag = opts.flag.as_deref().unwrap_or("r");
 
   let mut open_opts = fs::OpenOptions::new();
@@ -198,7 +134,7 @@ fn read_file_impl(
     }
   }
 
-  let mut file = open_opts.open(path).map_err(|e| {
+  let file = open_opts.open(path).map_err(|e| {
     if e.kind() == std::io::ErrorKind::NotFound {
       Error::from_reason(format!(
         "ENOENT: no such file or directory, open '{}'",
@@ -219,25 +155,63 @@ fn read_file_impl(
     }
   })?;
 
-  // If lines option is specified with a text encoding, use streaming line-by-line reading
-  // to avoid loading the entire file into memory. Buffer mode (no encoding) ignores lines.
-  if let (Some(lines), Some(_)) = (&opts.lines, &opts.encoding) {
-    let contents = read_file_with_lines(
-      path,
-      &mut open_opts,
-      lines.clone(),
-      opts.encoding.as_deref(),
-    )?;
-    return Ok(Either::A(contents));
+  read_opened(file, &path_str, &opts)
+}
+
+/// Reads (and decodes / decompresses) an already opened file; `path_str` names it in errors and
+/// drives `compression: 'auto'`.
+pub(crate) fn read_opened(
+  mut file: fs::File,
+  path_str: &str,
+  opts: &ReadFileOptions,
+) -> Result<Either<String, Buffer>> {
+  let path = Path::new(path_str);
+  validate_read_file_options(opts)?;
+  let encoding = parse_optional(opts.encoding.as_deref())?;
+  let compression = parse_compression(opts.compression.as_deref())?;
+
+  if compression.is_none() && opts.lines.is_none() {
+    // Plain whole-file read: `read_to_end` sizes the Vec from the file length.
+    let mut data = Vec::new();
+    file
+      .read_to_end(&mut data)
+      .map_err(|e| Error::from_reason(e.to_string()))?;
+    return Ok(decode_to_js(data, encoding));
   }
 
-  use std::io::Read;
-  let mut data = Vec::new();
-  file
-    .read_to_end(&mut data)
+  let mut reader = BufReader::with_capacity(64 * 1024, file);
+  let codec = codec_for_read(compression, path, &mut reader)
     .map_err(|e| Error::from_reason(e.to_string()))?;
+  let mut reader: Box<dyn BufRead> = match codec {
+    // Decompression streams through the decoder, so `lines` stops inflating early too.
+    Some(codec) => Box::new(BufReader::with_capacity(
+      64 * 1024,
+      decoder(codec, reader).map_err(|e| decompress_error(codec, e, path_str))?,
+    )),
+    None => Box::new(reader),
+  };
+  let read_error = |e: std::io::Error| match codec {
+    Some(codec) => decompress_error(codec, e, path_str),
+    None => Error::from_reason(e.to_string()),
+  };
+
+  // `lines` reads line by line, so it never loads more of the file than it returns. It always
+  // comes with an encoding (see `validate_read_file_options`).
+  if let (Some(lines), Some(encoding)) = (&opts.lines, encoding) {
+    if !encoding.is_ascii_compatible() {
+      return Err(Error::from_reason(format!(
+        "ERR_INVALID_ARG_VALUE: The 'lines' option does not support the '{}' encoding",
+        opts.encoding.as_deref().unwrap_or_default()
+      )));
+    }
+    let contents = read_file_with_lines(reader, lines.clone()).map_err(read_error)?;
+    return Ok(Either::A(encoding.decode(&contents)));
+  }
+
+  let mut data = Vec::new();
+  reader.read_to_end(&mut data).map_err(read_error)?;
 
-  decode_data(data, opts.encoding.as_deref())
+  Ok(decode_to_js(data, encoding))
 }
 
 #[napi(js_name = "readFileSync")]
@@ -0,0 +1,343 @@
+use crate::encoding::{parse_optional, Encoding};
+use crate::utils::uv_error;
+use memchr::memrchr_iter;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::fs;
+use std::future::Future;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Lines per batch yielded by `readLines` unless `batchSize` is given.
+const DEFAULT_BATCH_SIZE: usize = 1024;
+/// Batches `readLines` reads ahead of the consumer.
+const READ_AHEAD: usize = 4;
+/// Chunk size `tail` reads backwards from EOF.
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// Parses a line API's `encoding`: `'buffer'` yields Buffers (`None`), and the default is utf8.
+/// UTF-16 is rejected because lines are split on the single byte `\n`.
+pub(crate) fn parse_line_encoding(encoding: Option<&str>) -> Result<Option<Encoding>> {
+  match encoding {
+    None => Ok(Some(Encoding::Utf8)),
+    Some(name) => match parse_optional(Some(name))? {
+      Some(encoding) if !encoding.is_ascii_compatible() => Err(Error::from_reason(format!(
+        "ERR_INVALID_ARG_VALUE: Line reading does not support the '{}' encoding",
+        name
+      ))),
+      encoding => Ok(encoding),
+    },
+  }
+}
+
+/// Strips the line terminator (`\n` or `\r\n`) from a line read with `read_until`.
+fn trim_line_ending(line: &mut Vec<u8>) {
+  if line.last() == Some(&b'\n') {
+    line.pop();
+    if line.last() == Some(&b'\r') {
+      line.pop();
+    }
+  }
+}
+
+pub(crate) fn encode_lines(
+  lines: Vec<Vec<u8>>,
+  encoding: Option<Encoding>,
+) -> Result<Either<Vec<String>, Vec<Buffer>>> {
+  Ok(match encoding {
+    Some(encoding) => Either::A(lines.iter().map(|l| encoding.decode(l)).collect()),
+    None => Either::B(lines.into_iter().map(Buffer::from).collect()),
+  })
+}
+
+fn abort_error() -> Error {
+  Error::from_reason("AbortError: The operation was aborted")
+}
+
+/// An `AbortSignal` reduced to a flag. `onabort` only reports aborts that happen later, so the
+/// signal's current `aborted` state is read up front as well.
+pub struct LinesAbortSignal {
+  aborted: Arc<AtomicBool>,
+}
+
+impl TypeName for LinesAbortSignal {
+  fn type_name() -> &'static str {
+    "AbortSignal"
+  }
+
+  fn value_type() -> ValueType {
+    ValueType::Object
+  }
+}
+
+impl ValidateNapiValue for LinesAbortSignal {}
+
+impl FromNapiValue for LinesAbortSignal {
+  unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+    let object = Object::from_napi_value(env, napi_val)?;
+    let aborted = Arc::new(AtomicBool::new(
+      object.get::<bool>("aborted")?.unwrap_or(false),
+    ));
+    let signal = AbortSignal::from_napi_value(env, napi_val)?;
+    let flag = aborted.clone();
+    signal.on_abort(move || flag.store(true, Ordering::SeqCst));
+    Ok(LinesAbortSignal { aborted })
+  }
+}
+
+#[napi(object, object_to_js = false)]
+pub struct ReadLinesOptions {
+  /// 'utf8' (default, invalid bytes become U+FFFD), 'latin1', 'ascii', ... or 'buffer' to
+  /// yield each line as a Buffer.
+  pub encoding: Option<String>,
+  /// 1-based line number to start from. Defaults to 1.
+  pub start: Option<u32>,
+  /// Maximum number of lines per batch. Defaults to 1024.
+  pub batch_size: Option<u32>,
+  /// Stops the iteration; pending and later `next()` calls reject with an AbortError.
+  #[napi(ts_type = "AbortSignal")]
+  pub signal: Option<LinesAbortSignal>,
+}
+
+type LineBatch = Result<Either<Vec<String>, Vec<Buffer>>>;
+
+/// Async iterable of line batches returned by `readLines`. Lines are read on a background
+/// thread with a `BufReader`, a few batches ahead of the consumer.
+#[napi(async_iterator)]
+pub struct LineReader {
+  receiver: Arc<tokio::sync::Mutex<mpsc::Receiver<LineBatch>>>,
+  aborted: Arc<AtomicBool>,
+}
+
+#[napi]
+impl AsyncGenerator for LineReader {
+  type Yield = Either<Vec<String>, Vec<Buffer>>;
+  type Next = ();
+  type Return = ();
+
+  fn next(
+    &mut self,
+    _value: Option<Self::Next>,
+  ) -> impl Future<Output = Result<Option<Self::Yield>>> + Send + 'static {
+    let receiver = self.receiver.clone();
+    let aborted = self.aborted.clone();
+    async move {
+      if aborted.load(Ordering::SeqCst) {
+        receiver.lock().await.close();
+        return Err(abort_error());
+      }
+      let batch = receiver.lock().await.recv().await;
+      // An abort also stops the worker, which ends the channel; report it instead of a
+      // normal end of file.
+      if aborted.load(Ordering::SeqCst) {
+        return Err(abort_error());
+      }
+      batch.transpose()
+    }
+  }
+
+  fn complete(
+    &mut self,
+    _value: Option<Self::Return>,
+  ) -> impl Future<Output = Result<Option<Self::Yield>>> + Send + 'static {
+    let receiver = self.receiver.clone();
+    async move {
+      receiver.lock().await.close();
+      Ok(None)
+    }
+  }
+}
+
+fn read_lines_worker(
+  path: String,
+  file: fs::File,
+  encoding: Option<Encoding>,
+  start: u64,
+  batch_size: usize,
+  aborted: Arc<AtomicBool>,
+  sender: mpsc::Sender<LineBatch>,
+) {
+  let mut reader = BufReader::with_capacity(64 * 1024, file);
+  let mut line_number: u64 = 0;
+  let mut batch = Vec::with_capacity(batch_size);
+  loop {
+    if aborted.load(Ordering::SeqCst) {
+      return;
+    }
+    let mut line = Vec::new();
+    match reader.read_until(b'\n', &mut line) {
+      Ok(0) => break,
+      Ok(_) => {}
+      Err(e) => {
+        let _ = sender.blocking_send(Err(uv_error(&e, "read", Path::new(&path))));
+        return;
+      }
+    }
+    line_number += 1;
+    if line_number < start {
+      continue;
+    }
+    trim_line_ending(&mut line);
+    batch.push(line);
+    if batch.len() == batch_size {
+      let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+      if sender.blocking_send(encode_lines(full, encoding)).is_err() {
+        return;
+      }
+    }
+  }
+  if !batch.is_empty() {
+    let _ = sender.blocking_send(encode_lines(batch, encoding));
+  }
+}
+
+#[napi(js_name = "readLines")]
+pub fn read_lines(path: String, options: Option<ReadLinesOptions>) -> Result<LineReader> {
+  let opts = options.unwrap_or(ReadLinesOptions {
+    encoding: None,
+    start: None,
+    batch_size: None,
+    signal: None,
+  });
+  let encoding = parse_line_encoding(opts.encoding.as_deref())?;
+  let start = opts.start.unwrap_or(1) as u64;
+  let batch_size = (opts.batch_size.unwrap_or(DEFAULT_BATCH_SIZE as u32) as usize).max(1);
+  let aborted = opts
+    .signal
+    .map(|s| s.aborted)
+    .unwrap_or_else(|| Arc::new(AtomicBool::new(false)));
+
+  let file = fs::File::open(&path).map_err(|e| uv_error(&e, "open", Path::new(&path)))?;
+  if file.metadata().is_ok_and(|m| m.is_dir()) {
+    return Err(uv_error(
+      &std::io::Error::from(std::io::ErrorKind::IsADirectory),
+      "read",
+      Path::new(&path),
+    ));
+  }
+
+  let (sender, receiver) = mpsc::channel(READ_AHEAD);
+  let worker_aborted = aborted.clone();
+  std::thread::spawn(move || {
+    read_lines_worker(
+      path,
+      file,
+      encoding,
+      start,
+      batch_size,
+      worker_aborted,
+      sender,
+    )
+  });
+  Ok(LineReader {
+    receiver: Arc::new(tokio::sync::Mutex::new(receiver)),
+    aborted,
+  })
+}
+
+// ========= tail =========
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct TailOptions {
+  /// Same encodings as `readLines`. Defaults to 'utf8'.
+  pub encoding: Option<String>,
+}
+
+/// Returns the byte range holding the last `n` lines of `file` (excluding a trailing newline),
+/// reading backwards in chunks. `None` when there are no lines to return.
+fn find_tail_range(file: &mut fs::File, n: u64) -> std::io::Result<Option<(u64, u64)>> {
+  let len = file.metadata()?.len();
+  if len == 0 || n == 0 {
+    return Ok(None);
+  }
+  let mut end = len;
+  let mut last = [0u8; 1];
+  file.seek(SeekFrom::Start(len - 1))?;
+  file.read_exact(&mut last)?;
+  // A trailing newline terminates the last line; it does not start an empty one.
+  if last[0] == b'\n' {
+    end -= 1;
+  }
+
+  let mut pos = end;
+  let mut found = 0;
+  let mut chunk = vec![0u8; TAIL_CHUNK as usize];
+  while pos > 0 {
+    let size = TAIL_CHUNK.min(pos);
+    pos -= size;
+    let buf = &mut chunk[..size as usize];
+    file.seek(SeekFrom::Start(pos))?;
+    file.read_exact(buf)?;
+    for i in memrchr_iter(b'\n', buf) {
+      found += 1;
+      if found == n {
+        return Ok(Some((pos + i as u64 + 1, end)));
+      }
+    }
+  }
+  Ok(Some((0, end)))
+}
+
+fn tail_impl(
+  path: String,
+  n: u32,
+  options: Option<TailOptions>,
+) -> Result<Either<Vec<String>, Vec<Buffer>>> {
+  let encoding = parse_line_encoding(options.and_then(|o| o.encoding).as_deref())?;
+  let mut file =
+    fs::File::open(Path::new(&path)).map_err(|e| uv_error(&e, "open", Path::new(&path)))?;
+  let Some((start, end)) =
+    find_tail_range(&mut file, n as u64).map_err(|e| uv_error(&e, "read", Path::new(&path)))?
+  else {
+    return encode_lines(Vec::new(), encoding);
+  };
+
+  let mut data = vec![0u8; (end - start) as usize];
+  file
+    .seek(SeekFrom::Start(start))
+    .and_then(|_| file.read_exact(&mut data))
+    .map_err(|e| uv_error(&e, "read", Path::new(&path)))?;
+  let lines = data
+    .split(|&b| b == b'\n')
+    .map(|l| l.strip_suffix(b"\r").unwrap_or(l).to_vec())
+    .collect();
+  encode_lines(lines, encoding)
+}
+
+#[napi(js_name = "tailSync")]
+pub fn tail_sync(
+  path: String,
+  n: u32,
+  options: Option<TailOptions>,
+) -> Result<Either<Vec<String>, Vec<Buffer>>> {
+  tail_impl(path, n, options)
+}
+
+pub struct TailTask {
+  pub path: String,
+  pub n: u32,
+  pub options: Option<TailOptions>,
+}
+
+impl Task for TailTask {
+  type Output = Either<Vec<String>, Vec<Buffer>>;
+  type JsValue = Either<Vec<String>, Vec<Buffer>>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    tail_impl(self.path.clone(), self.n, self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "tail")]
+pub fn tail(path: String, n: u32, options: Option<TailOptions>) -> AsyncTask<TailTask> {
+  AsyncTask::new(TailTask { path, n, options })
+}
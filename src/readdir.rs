@@ -1,5 +1,6 @@
+use crate::on_error::{aggregate_error, ErrorPolicy, PathError};
 use crate::types::Dirent;
-use crate::utils::get_file_type_id;
+use crate::utils::{get_file_type_id, uv_error};
 use jwalk::{Parallelism, WalkDir};
 use napi::bindgen_prelude::*;
 use napi::Task;
@@ -34,12 +35,18 @@ pub struct ReaddirOptions {
   pub concurrency: Option<u32>,
   pub recursive: Option<bool>,
   pub with_file_types: Option<bool>,
+  /// Rush-FS extension: what to do when an entry cannot be read (e.g. an unreadable
+  /// subdirectory with `recursive`). `'throw'` (default) fails, `'skip'` leaves it out, and
+  /// `'collect'` finishes the listing, then throws an `AggregateError` of every failure.
+  #[napi(ts_type = "'throw' | 'skip' | 'collect'")]
+  pub on_error: Option<String>,
 }
 
 // #[napi] // marco: expose the function to Node
 fn ls(
   path_str: String,
   options: Option<ReaddirOptions>,
+  errors: &mut Vec<PathError>,
 ) -> Result<Either<Vec<String>, Vec<Dirent>>> {
   let search_path_str = if path_str.is_empty() { "." } else { &path_str };
   let path = Path::new(search_path_str);
@@ -55,8 +62,19 @@ fn ls(
     concurrency: None,
     recursive: Some(false),
     with_file_types: Some(false),
+    on_error: None,
   });
+  let policy = ErrorPolicy::new(opts.on_error.as_deref())?;
+  let result = list(path, search_path_str, &opts, &policy);
+  policy.finish(result, "scandir", search_path_str, errors)
+}
 
+fn list(
+  path: &Path,
+  search_path_str: &str,
+  opts: &ReaddirOptions,
+  policy: &ErrorPolicy,
+) -> Result<Either<Vec<String>, Vec<Dirent>>> {
   let skip_hidden = opts.skip_hidden.unwrap_or(false);
   let recursive = opts.recursive.unwrap_or(false);
   let with_file_types = opts.with_file_types.unwrap_or(false);
@@ -80,7 +98,15 @@ fn ls(
     };
 
     for entry in entries {
-      let entry = entry.map_err(|e| Error::from_reason(e.to_string()))?;
+      let entry = match entry {
+        Ok(entry) => entry,
+        Err(e) => {
+          policy
+            .check(e, "scandir", path)
+            .map_err(|e| uv_error(&e, "scandir", path))?;
+          continue;
+        }
+      };
       let file_name = entry.file_name();
       let name_str = file_name.to_string_lossy();
       if skip_hidden && name_str.starts_with('.') {
@@ -114,11 +140,35 @@ fn ls(
 
   // TODO: maybe we'd better limit the max number of threads?
 
+  // jwalk reports a directory it could not read on the directory's own entry, and other
+  // failures as `Err` items.
+  let report = |e: jwalk::Error| -> Result<()> {
+    let failed = e.path().unwrap_or(path).to_path_buf();
+    let e = e
+      .into_io_error()
+      .unwrap_or_else(|| std::io::Error::other("filesystem loop"));
+    policy
+      .check(e, "scandir", &failed)
+      .map_err(|e| uv_error(&e, "scandir", &failed))
+  };
+  let mut entries = Vec::new();
+  for entry in walk_dir {
+    match entry {
+      Ok(mut entry) => {
+        if let Some(e) = entry.read_children_error.take() {
+          report(e)?;
+        }
+        if entry.depth() > 0 {
+          entries.push(entry);
+        }
+      }
+      Err(e) => report(e)?,
+    }
+  }
+
   if with_file_types {
-    let result = walk_dir
+    let result = entries
       .into_iter()
-      .filter_map(|e| e.ok())
-      .filter(|e| e.depth() > 0)
       .map(|e| {
         let p = e.path();
         let parent = p
@@ -139,10 +189,8 @@ fn ls(
     // When recursive is true and withFileTypes is false, Node.js returns relative paths.
     // But jwalk entries have full paths, We need to strip the root path.
     let root = path;
-    let result = walk_dir
+    let result = entries
       .into_iter()
-      .filter_map(|e| e.ok())
-      .filter(|e| e.depth() > 0)
       .map(|e| {
         // Get path relative to root
         let p = e.path();
@@ -158,10 +206,12 @@ fn ls(
 
 #[napi(js_name = "readdirSync")]
 pub fn readdir_sync(
+  env: Env,
   path: String,
   options: Option<ReaddirOptions>,
 ) -> Result<Either<Vec<String>, Vec<Dirent>>> {
-  ls(path, options)
+  let mut errors = Vec::new();
+  ls(path, options, &mut errors).map_err(|e| aggregate_error(&env, e, errors))
 }
 
 // ========= async version =========
@@ -169,6 +219,7 @@ pub fn readdir_sync(
 pub struct ReaddirTask {
   pub path: String,
   pub options: Option<ReaddirOptions>,
+  errors: Vec<PathError>,
 }
 
 impl Task for ReaddirTask {
@@ -176,14 +227,21 @@ impl Task for ReaddirTask {
   type JsValue = Either<Vec<String>, Vec<Dirent>>;
 
   fn compute(&mut self) -> Result<Self::Output> {
-    ls(self.path.clone(), self.options.clone())
+    ls(self.path.clone(), self.options.clone(), &mut self.errors)
   }
   fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
     Ok(output)
   }
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    Err(aggregate_error(&env, err, std::mem::take(&mut self.errors)))
+  }
 }
 
 #[napi(js_name = "readdir")]
 pub fn readdir(path: String, options: Option<ReaddirOptions>) -> AsyncTask<ReaddirTask> {
-  AsyncTask::new(ReaddirTask { path, options })
+  AsyncTask::new(ReaddirTask {
+    path,
+    options,
+    errors: Vec::new(),
+  })
 }
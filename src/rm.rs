@@ -1,6 +1,9 @@
+use crate::dry_run::{DryRunPlan, Planner};
+use crate::on_error::{aggregate_error, ErrorPolicy, PathError};
 use napi::bindgen_prelude::*;
 use napi::Task;
 use napi_derive::napi;
+#[cfg(not(unix))]
 use rayon::prelude::*;
 use std::fs;
 use std::path::Path;
@@ -14,6 +17,11 @@ use std::path::Path;
 ///   each try. This option represents the number of retries.
 /// - `retryDelay`: The amount of time in milliseconds to wait between retries (default 100ms).
 /// - `concurrency` (rush-fs extension): Number of parallel threads for recursive removal.
+/// - `dryRun` (rush-fs extension): Remove nothing and return the `DryRunPlan` instead.
+/// - `onError` (rush-fs extension): What to do when an entry inside the tree cannot be removed.
+///   `'throw'` (default) stops at the first error, `'skip'` leaves the entry (and its parent
+///   directories) in place and carries on, `'collect'` does the same and then throws an
+///   `AggregateError` of every failure. Errors on `path` itself always throw.
 
 #[napi(object)]
 #[derive(Clone)]
@@ -23,51 +31,157 @@ pub struct RmOptions {
   pub recursive: Option<bool>,
   pub retry_delay: Option<u32>,
   pub concurrency: Option<u32>,
+  pub dry_run: Option<bool>,
+  #[napi(ts_type = "'throw' | 'skip' | 'collect'")]
+  pub on_error: Option<String>,
 }
 
-fn remove_recursive(path: &Path, opts: &RmOptions) -> Result<()> {
-  let meta = fs::symlink_metadata(path).map_err(|e| Error::from_reason(e.to_string()))?;
+/// Removes `path` through directory fds (`openat` / `unlinkat` / `fdopendir` with `O_NOFOLLOW`,
+/// see `openat::remove_at`), so swapping a directory inside the tree for a symlink while it is
+/// being removed cannot make `rm` delete anything outside of it. With a `plan` (`dryRun`), the
+/// same traversal records what it would delete instead.
+#[cfg(unix)]
+fn remove_recursive(
+  path: &Path,
+  opts: &RmOptions,
+  policy: &ErrorPolicy,
+  plan: Option<&Planner>,
+) -> Result<()> {
+  use crate::openat;
+  use crate::utils::uv_error;
+  use std::os::fd::AsFd;
+
+  let recursive = opts.recursive.unwrap_or(false);
+  let parallel = opts.concurrency.unwrap_or(0) > 1;
+  let error = |e: std::io::Error| uv_error(&e, "rm", path);
+
+  // `..`, `a/..` and the like have no name to unlinkat in their parent: remove what they
+  // resolve to instead.
+  let resolved;
+  let path = match path.file_name() {
+    Some(_) => path,
+    None => {
+      resolved = fs::canonicalize(path).map_err(error)?;
+      &resolved
+    }
+  };
+  let Some(name) = path.file_name() else {
+    if let Some(plan) = plan {
+      plan.remove(path, 0);
+      return Ok(());
+    }
+    return fs::remove_dir(path).map_err(error);
+  };
+  let parent = match path.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  };
+  let dir = openat::open_dir(parent).map_err(error)?;
+  let name = openat::cstring(name).map_err(error)?;
+  let hook = |e: std::io::Error, failed: &Path| {
+    if failed == path {
+      return Err(e);
+    }
+    policy.check(e, "rm", failed)
+  };
+  let removal = openat::Removal {
+    parallel,
+    plan,
+    removed: None,
+    hook: &hook,
+  };
+  openat::remove_at_with(dir.as_fd(), &name, path, recursive, &removal)
+    .map(|_| ())
+    .map_err(error)
+}
+
+#[cfg(not(unix))]
+fn remove_recursive(
+  path: &Path,
+  opts: &RmOptions,
+  policy: &ErrorPolicy,
+  plan: Option<&Planner>,
+) -> Result<()> {
+  remove_tree(path, opts, policy, plan)
+    .map(|_| ())
+    .map_err(|e| crate::utils::uv_error(&e, "rm", path))
+}
+
+/// Returns whether `path` is gone: children failing under `onError: 'skip' | 'collect'` keep
+/// their parent directories. With a `plan`, records what would be deleted instead.
+#[cfg(not(unix))]
+fn remove_tree(
+  path: &Path,
+  opts: &RmOptions,
+  policy: &ErrorPolicy,
+  plan: Option<&Planner>,
+) -> std::io::Result<bool> {
+  let meta = fs::symlink_metadata(path)?;
+  let remove_dir = |path: &Path| match plan {
+    Some(plan) => {
+      plan.remove(path, 0);
+      Ok(())
+    }
+    None => fs::remove_dir(path),
+  };
 
   if meta.is_dir() {
     if opts.recursive.unwrap_or(false) {
-      let entries_iter = fs::read_dir(path).map_err(|e| Error::from_reason(e.to_string()))?;
+      let entries_iter = fs::read_dir(path)?;
+      let remove_child = |child: &Path| -> std::io::Result<bool> {
+        match remove_tree(child, opts, policy, plan) {
+          Ok(removed) => Ok(removed),
+          Err(e) => policy.check(e, "rm", child).map(|_| false),
+        }
+      };
 
       let concurrency = opts.concurrency.unwrap_or(0);
-      if concurrency > 1 {
-        let entries: Vec<_> = entries_iter
-          .collect::<std::io::Result<_>>()
-          .map_err(|e| Error::from_reason(e.to_string()))?;
+      let removed = if concurrency > 1 {
+        let entries: Vec<_> = entries_iter.collect::<std::io::Result<_>>()?;
 
         entries
           .par_iter()
-          .try_for_each(|entry| -> Result<()> { remove_recursive(&entry.path(), opts) })?;
+          .map(|entry| remove_child(&entry.path()))
+          .try_reduce(|| true, |a, b| Ok(a && b))?
       } else {
+        let mut removed = true;
         for entry in entries_iter {
-          let entry = entry.map_err(|e| Error::from_reason(e.to_string()))?;
-          remove_recursive(&entry.path(), opts)?;
+          removed &= remove_child(&entry?.path())?;
         }
+        removed
+      };
+      if !removed {
+        return Ok(false);
       }
 
-      fs::remove_dir(path).map_err(|e| Error::from_reason(e.to_string()))?;
+      remove_dir(path)?;
     } else {
-      fs::remove_dir(path).map_err(|e| {
-        if e.kind() == std::io::ErrorKind::AlreadyExists || e.to_string().contains("not empty") {
-          Error::from_reason(format!(
-            "ENOTEMPTY: directory not empty, rm '{}'",
-            path.to_string_lossy()
-          ))
+      let empty = match plan {
+        Some(_) => fs::read_dir(path)?.next().is_none(),
+        None => true,
+      };
+      let removed = if empty {
+        remove_dir(path)
+      } else {
+        Err(std::io::Error::from(std::io::ErrorKind::DirectoryNotEmpty))
+      };
+      removed.map_err(|e| {
+        if e.kind() == std::io::ErrorKind::AlreadyExists {
+          std::io::Error::from(std::io::ErrorKind::DirectoryNotEmpty)
         } else {
-          Error::from_reason(e.to_string())
+          e
         }
       })?;
     }
+  } else if let Some(plan) = plan {
+    plan.remove(path, if meta.is_file() { meta.len() } else { 0 });
   } else {
-    fs::remove_file(path).map_err(|e| Error::from_reason(e.to_string()))?;
+    fs::remove_file(path)?;
   }
-  Ok(())
+  Ok(true)
 }
 
-fn remove_with_retry(path: &Path, opts: &RmOptions) -> Result<()> {
+fn remove_with_retry(path: &Path, opts: &RmOptions, policy: &ErrorPolicy) -> Result<()> {
   let max_retries = opts.max_retries.unwrap_or(0) as usize;
   let retry_delay = opts.retry_delay.unwrap_or(100) as u64;
 
@@ -76,7 +190,7 @@ fn remove_with_retry(path: &Path, opts: &RmOptions) -> Result<()> {
     if attempt > 0 {
       std::thread::sleep(std::time::Duration::from_millis(retry_delay));
     }
-    match remove_recursive(path, opts) {
+    match remove_recursive(path, opts, policy, None) {
       Ok(()) => return Ok(()),
       Err(e) => last_err = Some(e),
     }
@@ -84,7 +198,11 @@ fn remove_with_retry(path: &Path, opts: &RmOptions) -> Result<()> {
   Err(last_err.unwrap())
 }
 
-fn remove(path_str: String, options: Option<RmOptions>) -> Result<()> {
+pub(crate) fn remove(
+  path_str: String,
+  options: Option<RmOptions>,
+  errors: &mut Vec<PathError>,
+) -> Result<Either<Undefined, DryRunPlan>> {
   let path = Path::new(&path_str);
 
   let opts = options.unwrap_or(RmOptions {
@@ -93,12 +211,20 @@ fn remove(path_str: String, options: Option<RmOptions>) -> Result<()> {
     max_retries: None,
     retry_delay: None,
     concurrency: None,
+    dry_run: None,
+    on_error: None,
   });
+  let policy = ErrorPolicy::new(opts.on_error.as_deref())?;
   let force = opts.force.unwrap_or(false);
+  let dry_run = opts.dry_run.unwrap_or(false);
 
-  if !path.exists() {
+  // symlink_metadata, not exists(): a dangling symlink is still something to remove.
+  if fs::symlink_metadata(path).is_err() {
     if force {
-      return Ok(());
+      if dry_run {
+        return Ok(Either::B(Planner::default().into_plan()));
+      }
+      return Ok(Either::A(()));
     }
     return Err(Error::from_reason(format!(
       "ENOENT: no such file or directory, rm '{}'",
@@ -106,12 +232,24 @@ fn remove(path_str: String, options: Option<RmOptions>) -> Result<()> {
     )));
   }
 
+  if dry_run {
+    let plan = Planner::default();
+    let result = remove_recursive(path, &opts, &policy, Some(&plan));
+    return policy.finish(
+      result.map(|_| Either::B(plan.into_plan())),
+      "rm",
+      &path_str,
+      errors,
+    );
+  }
+
   let max_retries = opts.max_retries.unwrap_or(0);
-  if max_retries > 0 {
-    remove_with_retry(path, &opts)
+  let result = if max_retries > 0 {
+    remove_with_retry(path, &opts, &policy)
   } else {
-    remove_recursive(path, &opts)
-  }
+    remove_recursive(path, &opts, &policy, None)
+  };
+  policy.finish(result.map(|_| Either::A(())), "rm", &path_str, errors)
 }
 
 // ========= async version =========
@@ -119,27 +257,41 @@ fn remove(path_str: String, options: Option<RmOptions>) -> Result<()> {
 pub struct RmTask {
   pub path: String,
   pub options: Option<RmOptions>,
+  errors: Vec<PathError>,
 }
 
 impl Task for RmTask {
-  type Output = ();
-  type JsValue = ();
+  type Output = Either<Undefined, DryRunPlan>;
+  type JsValue = Either<Undefined, DryRunPlan>;
 
   fn compute(&mut self) -> Result<Self::Output> {
-    remove(self.path.clone(), self.options.clone())
+    remove(self.path.clone(), self.options.clone(), &mut self.errors)
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
   }
 
-  fn resolve(&mut self, _env: Env, _output: Self::Output) -> Result<Self::JsValue> {
-    Ok(())
+  fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+    Err(aggregate_error(&env, err, std::mem::take(&mut self.errors)))
   }
 }
 
 #[napi(js_name = "rm")]
 pub fn rm(path: String, options: Option<RmOptions>) -> AsyncTask<RmTask> {
-  AsyncTask::new(RmTask { path, options })
+  AsyncTask::new(RmTask {
+    path,
+    options,
+    errors: Vec::new(),
+  })
 }
 
 #[napi(js_name = "rmSync")]
-pub fn rm_sync(path: String, options: Option<RmOptions>) -> Result<()> {
-  remove(path, options)
+pub fn rm_sync(
+  env: Env,
+  path: String,
+  options: Option<RmOptions>,
+) -> Result<Either<Undefined, DryRunPlan>> {
+  let mut errors = Vec::new();
+  remove(path, options, &mut errors).map_err(|e| aggregate_error(&env, e, errors))
 }
@@ -0,0 +1,501 @@
+//! `openRoot`: file access confined to one directory.
+//!
+//! `realpath` followed by a prefix check is racy: a component can be swapped for a symlink between
+//! the check and the open. A `Root` holds the directory open instead and resolves every path
+//! relative to that fd (see `openat.rs`), so no path, symlink or `..` can reach outside of it.
+
+use crate::read_file::ReadFileOptions;
+use crate::readdir::ReaddirOptions;
+use crate::types::{Dirent, Stats};
+use crate::{MkdirOptions, RmOptions, WriteFileOptions};
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::sync::Arc;
+
+#[cfg(unix)]
+use crate::openat;
+#[cfg(unix)]
+use crate::utils::uv_error;
+#[cfg(unix)]
+use std::os::fd::{AsFd, OwnedFd};
+
+struct RootInner {
+  #[cfg(unix)]
+  fd: OwnedFd,
+  path: String,
+}
+
+/// A directory opened with `openRoot`. Every method takes a path relative to it; absolute paths,
+/// `..` past the root and symlinks pointing outside of it fail with EXDEV.
+#[napi]
+pub struct Root {
+  inner: Arc<RootInner>,
+}
+
+/// Runs one `Root` operation on the libuv thread pool.
+pub struct RootTask<T> {
+  op: Option<Box<dyn FnOnce() -> Result<T> + Send>>,
+}
+
+impl<T: ToNapiValue + TypeName + Send + 'static> Task for RootTask<T> {
+  type Output = T;
+  type JsValue = T;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let op = self.op.take().expect("compute runs once");
+    op()
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+impl Root {
+  fn task<T, F>(&self, op: F) -> AsyncTask<RootTask<T>>
+  where
+    T: ToNapiValue + TypeName + Send + 'static,
+    F: FnOnce(&RootInner) -> Result<T> + Send + 'static,
+  {
+    let inner = Arc::clone(&self.inner);
+    AsyncTask::new(RootTask {
+      op: Some(Box::new(move || op(&inner))),
+    })
+  }
+}
+
+#[napi]
+impl Root {
+  /// The directory this root was opened on, as passed to `openRoot`.
+  #[napi(getter)]
+  pub fn path(&self) -> String {
+    self.inner.path.clone()
+  }
+
+  #[napi(js_name = "readFileSync")]
+  pub fn read_file_sync(
+    &self,
+    path: String,
+    options: Option<Either<String, ReadFileOptions>>,
+  ) -> Result<Either<String, Buffer>> {
+    read_file_impl(&self.inner, path, options)
+  }
+
+  #[napi(js_name = "readFile")]
+  pub fn read_file(
+    &self,
+    path: String,
+    options: Option<Either<String, ReadFileOptions>>,
+  ) -> AsyncTask<RootTask<Either<String, Buffer>>> {
+    self.task(move |inner| read_file_impl(inner, path, options))
+  }
+
+  #[napi(js_name = "writeFileSync")]
+  pub fn write_file_sync(
+    &self,
+    path: String,
+    data: Either<String, Buffer>,
+    options: Option<WriteFileOptions>,
+  ) -> Result<()> {
+    write_file_impl(&self.inner, path, data, options)
+  }
+
+  #[napi(js_name = "writeFile")]
+  pub fn write_file(
+    &self,
+    path: String,
+    data: Either<String, Buffer>,
+    options: Option<WriteFileOptions>,
+  ) -> AsyncTask<RootTask<()>> {
+    // Buffers cannot leave the JS thread; copy the bytes like `writeFile` does.
+    let data = match data {
+      Either::A(s) => Either::A(s),
+      Either::B(b) => Either::B(b.to_vec()),
+    };
+    self.task(move |inner| {
+      let data = match data {
+        Either::A(s) => Either::A(s),
+        Either::B(b) => Either::B(Buffer::from(b)),
+      };
+      write_file_impl(inner, path, data, options)
+    })
+  }
+
+  /// Like `statSync`: symlinks are followed, as long as they stay inside the root.
+  #[napi(js_name = "statSync")]
+  pub fn stat_sync(&self, path: String) -> Result<Stats> {
+    stat_impl(&self.inner, path)
+  }
+
+  #[napi(js_name = "stat")]
+  pub fn stat(&self, path: String) -> AsyncTask<RootTask<Stats>> {
+    self.task(move |inner| stat_impl(inner, path))
+  }
+
+  /// Like `readdirSync`; `recursive` never descends into symlinked directories.
+  #[napi(js_name = "readdirSync")]
+  pub fn readdir_sync(
+    &self,
+    path: String,
+    options: Option<ReaddirOptions>,
+  ) -> Result<Either<Vec<String>, Vec<Dirent>>> {
+    readdir_impl(&self.inner, path, options)
+  }
+
+  #[napi(js_name = "readdir")]
+  pub fn readdir(
+    &self,
+    path: String,
+    options: Option<ReaddirOptions>,
+  ) -> AsyncTask<RootTask<Either<Vec<String>, Vec<Dirent>>>> {
+    self.task(move |inner| readdir_impl(inner, path, options))
+  }
+
+  /// Like `mkdirSync`; with `recursive` returns the first directory created, relative to the root.
+  #[napi(js_name = "mkdirSync")]
+  pub fn mkdir_sync(&self, path: String, options: Option<MkdirOptions>) -> Result<Option<String>> {
+    mkdir_impl(&self.inner, path, options)
+  }
+
+  #[napi(js_name = "mkdir")]
+  pub fn mkdir(
+    &self,
+    path: String,
+    options: Option<MkdirOptions>,
+  ) -> AsyncTask<RootTask<Option<String>>> {
+    self.task(move |inner| mkdir_impl(inner, path, options))
+  }
+
+  /// Like `rmSync` (`force`, `recursive`, `concurrency`); symlinks are removed, never followed.
+  #[napi(js_name = "rmSync")]
+  pub fn rm_sync(&self, path: String, options: Option<RmOptions>) -> Result<()> {
+    rm_impl(&self.inner, path, options)
+  }
+
+  #[napi(js_name = "rm")]
+  pub fn rm(&self, path: String, options: Option<RmOptions>) -> AsyncTask<RootTask<()>> {
+    self.task(move |inner| rm_impl(inner, path, options))
+  }
+}
+
+// ========= operations =========
+
+#[cfg(unix)]
+fn root_error(e: std::io::Error, syscall: &str, path: &str) -> Error {
+  if e.raw_os_error() == Some(libc::EXDEV) {
+    return Error::from_reason(format!(
+      "EXDEV: path resolves outside of the root, {} '{}'",
+      syscall, path
+    ));
+  }
+  uv_error(&e, syscall, std::path::Path::new(path))
+}
+
+#[cfg(unix)]
+fn read_flags(flag: &str) -> libc::c_int {
+  match flag {
+    "r+" | "rs+" | "sr+" => libc::O_RDWR,
+    "a+" => libc::O_RDWR | libc::O_APPEND | libc::O_CREAT,
+    "ax+" | "xa+" => libc::O_RDWR | libc::O_APPEND | libc::O_CREAT | libc::O_EXCL,
+    "w+" => libc::O_RDWR | libc::O_CREAT | libc::O_TRUNC,
+    "wx+" | "xw+" => libc::O_RDWR | libc::O_CREAT | libc::O_EXCL,
+    _ => libc::O_RDONLY,
+  }
+}
+
+#[cfg(unix)]
+fn write_flags(flag: &str) -> libc::c_int {
+  match flag {
+    "wx" | "xw" => libc::O_WRONLY | libc::O_CREAT | libc::O_EXCL,
+    "a" => libc::O_WRONLY | libc::O_APPEND | libc::O_CREAT,
+    "ax" | "xa" => libc::O_WRONLY | libc::O_APPEND | libc::O_CREAT | libc::O_EXCL,
+    _ => libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC,
+  }
+}
+
+#[cfg(unix)]
+fn read_file_impl(
+  inner: &RootInner,
+  path: String,
+  options: Option<Either<String, ReadFileOptions>>,
+) -> Result<Either<String, Buffer>> {
+  let opts = crate::read_file::normalize_read_file_options(options);
+  crate::read_file::validate_read_file_options(&opts)?;
+  let flags = read_flags(opts.flag.as_deref().unwrap_or("r"));
+  let fd = openat::open_beneath(inner.fd.as_fd(), path.as_ref(), flags, 0o666)
+    .map_err(|e| root_error(e, "open", &path))?;
+  crate::read_file::read_opened(fd.into(), &path, &opts)
+}
+
+#[cfg(unix)]
+fn write_file_impl(
+  inner: &RootInner,
+  path: String,
+  data: Either<String, Buffer>,
+  options: Option<WriteFileOptions>,
+) -> Result<()> {
+  let opts = options.unwrap_or_else(crate::write_file::default_write_file_options);
+  let encoded = crate::write_file::encode_write(path.as_ref(), &data, &opts)?;
+  let flags = write_flags(opts.flag.as_deref().unwrap_or("w"));
+  let fd = openat::open_beneath(
+    inner.fd.as_fd(),
+    path.as_ref(),
+    flags,
+    opts.mode.unwrap_or(0o666),
+  )
+  .map_err(|e| root_error(e, "open", &path))?;
+  crate::write_file::write_encoded(&mut fd.into(), encoded, path.as_ref())
+}
+
+#[cfg(unix)]
+fn stat_impl(inner: &RootInner, path: String) -> Result<Stats> {
+  // O_PATH stats anything without reading it; elsewhere O_NONBLOCK keeps FIFOs from blocking.
+  #[cfg(any(target_os = "linux", target_os = "android"))]
+  let flags = libc::O_PATH;
+  #[cfg(not(any(target_os = "linux", target_os = "android")))]
+  let flags = libc::O_RDONLY | libc::O_NONBLOCK;
+  let file: std::fs::File = openat::open_beneath(inner.fd.as_fd(), path.as_ref(), flags, 0)
+    .map_err(|e| root_error(e, "stat", &path))?
+    .into();
+  let meta = file.metadata().map_err(|e| root_error(e, "stat", &path))?;
+  Ok(crate::stat::metadata_to_stats(&meta))
+}
+
+#[cfg(unix)]
+fn readdir_impl(
+  inner: &RootInner,
+  path: String,
+  options: Option<ReaddirOptions>,
+) -> Result<Either<Vec<String>, Vec<Dirent>>> {
+  use std::path::Path;
+  use std::rc::Rc;
+
+  let skip_hidden = options
+    .as_ref()
+    .and_then(|o| o.skip_hidden)
+    .unwrap_or(false);
+  let recursive = options.as_ref().and_then(|o| o.recursive).unwrap_or(false);
+  let with_file_types = options
+    .as_ref()
+    .and_then(|o| o.with_file_types)
+    .unwrap_or(false);
+  let dir_flags = libc::O_RDONLY | libc::O_DIRECTORY;
+
+  let root = openat::open_beneath(inner.fd.as_fd(), path.as_ref(), dir_flags, 0)
+    .map_err(|e| root_error(e, "scandir", &path))?;
+  let mut names = Vec::new();
+  let mut dirents = Vec::new();
+  // (parent directory fd, name, path relative to `path`) of the directories left to list. They
+  // are only opened once popped, so open fds follow the depth of the walk rather than its width.
+  let mut pending = vec![(Rc::new(root), c".".to_owned(), String::new())];
+  while let Some((parent, name, sub)) = pending.pop() {
+    let dir = openat::openat(parent.as_fd(), &name, dir_flags | libc::O_NOFOLLOW, 0)
+      .map_err(|e| root_error(e, "scandir", if sub.is_empty() { &path } else { &sub }))?;
+    drop(parent);
+    let walk_dir = if recursive {
+      Some(Rc::new(
+        dir
+          .try_clone()
+          .map_err(|e| root_error(e, "scandir", &path))?,
+      ))
+    } else {
+      None
+    };
+    let entries = openat::read_dir(dir).map_err(|e| root_error(e, "scandir", &path))?;
+    for (name, file_type) in entries {
+      let child = openat::cstring(&name).map_err(|e| root_error(e, "scandir", &path))?;
+      let name = name.to_string_lossy().to_string();
+      if skip_hidden && name.starts_with('.') {
+        continue;
+      }
+      let rel = if sub.is_empty() {
+        name.clone()
+      } else {
+        format!("{}/{}", sub, name)
+      };
+      // Symlinks report their own type here, so they are never descended into.
+      if let (Some(walk_dir), 2) = (&walk_dir, file_type) {
+        pending.push((Rc::clone(walk_dir), child, rel.clone()));
+      }
+      if with_file_types {
+        let parent = if sub.is_empty() {
+          path.clone()
+        } else {
+          Path::new(&path).join(&sub).to_string_lossy().to_string()
+        };
+        dirents.push(Dirent {
+          name,
+          parent_path: parent,
+          file_type,
+        });
+      } else {
+        names.push(rel);
+      }
+    }
+  }
+  if with_file_types {
+    Ok(Either::B(dirents))
+  } else {
+    Ok(Either::A(names))
+  }
+}
+
+#[cfg(unix)]
+fn mkdir_impl(
+  inner: &RootInner,
+  path: String,
+  options: Option<MkdirOptions>,
+) -> Result<Option<String>> {
+  use std::path::{Component, PathBuf};
+
+  let recursive = options.as_ref().and_then(|o| o.recursive).unwrap_or(false);
+  let mode = options.as_ref().and_then(|o| o.mode).unwrap_or(0o777);
+  let root = inner.fd.as_fd();
+  let error = |e| root_error(e, "mkdir", &path);
+
+  if !recursive {
+    let (dir, name) = openat::parent_beneath(root, path.as_ref()).map_err(error)?;
+    openat::mkdirat(dir.as_fd(), &name, mode).map_err(error)?;
+    return Ok(None);
+  }
+
+  let mut first_created = None;
+  let mut prefix = PathBuf::new();
+  for component in std::path::Path::new(&path).components() {
+    prefix.push(component);
+    if !matches!(component, Component::Normal(_)) {
+      continue;
+    }
+    let (dir, name) = openat::parent_beneath(root, &prefix).map_err(error)?;
+    match openat::mkdirat(dir.as_fd(), &name, mode) {
+      Ok(()) => {
+        first_created.get_or_insert_with(|| prefix.to_string_lossy().to_string());
+      }
+      Err(e) if e.raw_os_error() == Some(libc::EEXIST) => {
+        // Fine if it is a directory, or a symlink to one that stays inside the root.
+        match openat::open_beneath(root, &prefix, libc::O_RDONLY | libc::O_DIRECTORY, 0) {
+          Ok(_) => {}
+          Err(open_err) if open_err.raw_os_error() == Some(libc::ENOTDIR) => {
+            let code = if prefix.as_os_str() == path.as_str() {
+              e
+            } else {
+              open_err
+            };
+            return Err(error(code));
+          }
+          Err(open_err) => return Err(error(open_err)),
+        }
+      }
+      Err(e) => return Err(error(e)),
+    }
+  }
+  Ok(first_created)
+}
+
+#[cfg(unix)]
+fn rm_impl(inner: &RootInner, path: String, options: Option<RmOptions>) -> Result<()> {
+  let force = options.as_ref().and_then(|o| o.force).unwrap_or(false);
+  let recursive = options.as_ref().and_then(|o| o.recursive).unwrap_or(false);
+  let concurrency = options.as_ref().and_then(|o| o.concurrency).unwrap_or(0);
+  let result = openat::parent_beneath(inner.fd.as_fd(), path.as_ref())
+    .and_then(|(dir, name)| openat::remove_at(dir.as_fd(), &name, recursive, concurrency > 1));
+  match result {
+    Err(e) if force && e.raw_os_error() == Some(libc::ENOENT) => Ok(()),
+    result => result.map_err(|e| root_error(e, "rm", &path)),
+  }
+}
+
+#[cfg(not(unix))]
+fn unsupported<T>() -> Result<T> {
+  Err(Error::from_reason(
+    "ENOSYS: function not implemented, openRoot",
+  ))
+}
+
+#[cfg(not(unix))]
+fn read_file_impl(
+  _: &RootInner,
+  _: String,
+  _: Option<Either<String, ReadFileOptions>>,
+) -> Result<Either<String, Buffer>> {
+  unsupported()
+}
+
+#[cfg(not(unix))]
+fn write_file_impl(
+  _: &RootInner,
+  _: String,
+  _: Either<String, Buffer>,
+  _: Option<WriteFileOptions>,
+) -> Result<()> {
+  unsupported()
+}
+
+#[cfg(not(unix))]
+fn stat_impl(_: &RootInner, _: String) -> Result<Stats> {
+  unsupported()
+}
+
+#[cfg(not(unix))]
+fn readdir_impl(
+  _: &RootInner,
+  _: String,
+  _: Option<ReaddirOptions>,
+) -> Result<Either<Vec<String>, Vec<Dirent>>> {
+  unsupported()
+}
+
+#[cfg(not(unix))]
+fn mkdir_impl(_: &RootInner, _: String, _: Option<MkdirOptions>) -> Result<Option<String>> {
+  unsupported()
+}
+
+#[cfg(not(unix))]
+fn rm_impl(_: &RootInner, _: String, _: Option<RmOptions>) -> Result<()> {
+  unsupported()
+}
+
+fn open_root_impl(dir: String) -> Result<Root> {
+  #[cfg(unix)]
+  {
+    let fd = openat::open_dir(dir.as_ref()).map_err(|e| root_error(e, "openRoot", &dir))?;
+    Ok(Root {
+      inner: Arc::new(RootInner { fd, path: dir }),
+    })
+  }
+  #[cfg(not(unix))]
+  {
+    let _ = RootInner { path: dir };
+    unsupported()
+  }
+}
+
+#[napi(js_name = "openRootSync")]
+pub fn open_root_sync(dir: String) -> Result<Root> {
+  open_root_impl(dir)
+}
+
+// ========= async version =========
+
+pub struct OpenRootTask {
+  pub dir: String,
+}
+
+impl Task for OpenRootTask {
+  type Output = Root;
+  type JsValue = Root;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    open_root_impl(self.dir.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "openRoot")]
+pub fn open_root(dir: String) -> AsyncTask<OpenRootTask> {
+  AsyncTask::new(OpenRootTask { dir })
+}
@@ -0,0 +1,372 @@
+use crate::glob::build_overrides;
+use ignore::{overrides::Override, WalkBuilder};
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use regex::bytes::{Regex, RegexBuilder};
+use std::collections::VecDeque;
+use std::fs;
+use std::future::Future;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// Number of per-file batches `searchStream` buffers before the walker waits for the consumer.
+const STREAM_BUFFER: usize = 64;
+
+/// Read buffer of each file being searched.
+const READ_BUFFER: usize = 64 * 1024;
+
+/// Bytes at the start of a file checked for a NUL before it is searched.
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct SearchOptions {
+  /// Directory to search. Defaults to the current directory.
+  pub cwd: Option<String>,
+  /// Glob patterns (relative to `cwd`) a file must match to be searched.
+  pub include: Option<Vec<String>>,
+  /// Glob patterns (relative to `cwd`) to skip.
+  pub exclude: Option<Vec<String>>,
+  /// Respect .gitignore / .ignore files and skip hidden entries. Defaults to false.
+  pub git_ignore: Option<bool>,
+  /// Treat `pattern` as a regular expression instead of a literal string. Defaults to false.
+  pub regex: Option<bool>,
+  pub case_insensitive: Option<bool>,
+  /// Stop after this many matches in total. `search` returns the first ones in path order;
+  /// `searchStream` yields those of the files that finish first, which can change between runs.
+  pub max_matches: Option<u32>,
+  /// Number of lines of context to return before and after each match. Defaults to 0.
+  pub context: Option<u32>,
+  /// Threads for the parallel walk. Defaults to 4.
+  pub concurrency: Option<u32>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct SearchMatch {
+  /// Path relative to `cwd`.
+  pub path: String,
+  /// 1-based line number.
+  pub line: u32,
+  /// 1-based column of the match in UTF-16 code units, so `text.slice(column - 1)` starts at it.
+  pub column: u32,
+  /// The whole matching line, without its line terminator.
+  pub text: String,
+  /// Up to `context` lines before the match. Only set when `context > 0`.
+  pub before: Option<Vec<String>>,
+  /// Up to `context` lines after the match. Only set when `context > 0`.
+  pub after: Option<Vec<String>>,
+}
+
+struct SearchConfig {
+  root: PathBuf,
+  overrides: Override,
+  git_ignore: bool,
+  concurrency: usize,
+  regex: Regex,
+  context: usize,
+  max_matches: Option<usize>,
+}
+
+fn prepare(pattern: &str, options: Option<SearchOptions>) -> Result<SearchConfig> {
+  let opts = options.unwrap_or(SearchOptions {
+    cwd: None,
+    include: None,
+    exclude: None,
+    git_ignore: None,
+    regex: None,
+    case_insensitive: None,
+    max_matches: None,
+    context: None,
+    concurrency: None,
+  });
+  let cwd = opts.cwd.unwrap_or_else(|| ".".to_string());
+  if !Path::new(&cwd).exists() {
+    return Err(Error::from_reason(format!(
+      "ENOENT: no such file or directory, search '{}'",
+      cwd
+    )));
+  }
+
+  let literal = !opts.regex.unwrap_or(false);
+  let source = if !literal {
+    pattern.to_string()
+  } else {
+    regex::escape(pattern)
+  };
+  let regex = RegexBuilder::new(&source)
+    .case_insensitive(opts.case_insensitive.unwrap_or(false))
+    .build()
+    .map_err(|e| {
+      Error::from_reason(format!(
+        "ERR_INVALID_ARG_VALUE: Invalid search pattern '{}': {}",
+        pattern, e
+      ))
+    })?;
+
+  Ok(SearchConfig {
+    overrides: build_overrides(
+      &cwd,
+      opts.include.as_deref().unwrap_or_default(),
+      opts.exclude.as_deref().unwrap_or_default(),
+    )?,
+    root: PathBuf::from(cwd),
+    git_ignore: opts.git_ignore.unwrap_or(false),
+    concurrency: opts.concurrency.unwrap_or(4).max(1) as usize,
+    regex,
+    context: opts.context.unwrap_or(0) as usize,
+    max_matches: opts.max_matches.map(|n| n as usize),
+  })
+}
+
+fn lossy(bytes: &[u8]) -> String {
+  String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Searches one file line by line through a buffered reader, so no more than a buffer and the
+/// `context` window is held in memory. Files containing a NUL byte are treated as binary and
+/// skipped, like ripgrep and git: the first buffer is sniffed before searching, and a NUL met
+/// further on drops the file's matches.
+fn search_file(path: &Path, shown: &str, config: &SearchConfig) -> io::Result<Vec<SearchMatch>> {
+  let mut reader = BufReader::with_capacity(READ_BUFFER, fs::File::open(path)?);
+  let head = reader.fill_buf()?;
+  if memchr::memchr(0, &head[..head.len().min(BINARY_SNIFF_LEN)]).is_some() {
+    return Ok(Vec::new());
+  }
+
+  let (regex, context) = (&config.regex, config.context);
+  let mut matches: Vec<SearchMatch> = Vec::new();
+  // The last `context` lines, and the matches (indexes into `matches`) still collecting the
+  // lines after them.
+  let mut before: VecDeque<Vec<u8>> = VecDeque::with_capacity(context);
+  let mut waiting: VecDeque<usize> = VecDeque::new();
+  let mut line = Vec::new();
+  let mut number: u32 = 0;
+  loop {
+    line.clear();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+      break;
+    }
+    if memchr::memchr(0, &line).is_some() {
+      return Ok(Vec::new());
+    }
+    number += 1;
+    if line.last() == Some(&b'\n') {
+      line.pop();
+      if line.last() == Some(&b'\r') {
+        line.pop();
+      }
+    }
+
+    while waiting
+      .front()
+      .is_some_and(|&i| (number - matches[i].line) as usize > context)
+    {
+      waiting.pop_front();
+    }
+    if !waiting.is_empty() {
+      let text = lossy(&line);
+      for &i in &waiting {
+        if let Some(after) = matches[i].after.as_mut() {
+          after.push(text.clone());
+        }
+      }
+    }
+
+    let mut text = None;
+    for (nth, m) in regex.find_iter(&line).enumerate() {
+      // An empty match (e.g. `^`) is reported once per line, not at every position.
+      if m.is_empty() && nth > 0 {
+        continue;
+      }
+      let column = String::from_utf8_lossy(&line[..m.start()])
+        .encode_utf16()
+        .count()
+        + 1;
+      if context > 0 {
+        waiting.push_back(matches.len());
+      }
+      matches.push(SearchMatch {
+        path: shown.to_string(),
+        line: number,
+        column: column as u32,
+        text: text.get_or_insert_with(|| lossy(&line)).clone(),
+        before: (context > 0).then(|| before.iter().map(|l| lossy(l)).collect()),
+        after: (context > 0).then(Vec::new),
+      });
+    }
+
+    if context > 0 {
+      if before.len() == context {
+        before.pop_front();
+      }
+      before.push_back(line.clone());
+    }
+  }
+  if let Some(max) = config.max_matches {
+    matches.truncate(max);
+  }
+  Ok(matches)
+}
+
+/// Walks `config.root` in parallel and hands each file's matches to `emit`. Stops early once
+/// `emit` returns false, or with `stop_at_max`, once `max_matches` have been emitted.
+fn run_search(
+  config: &SearchConfig,
+  stop_at_max: bool,
+  emit: impl Fn(Vec<SearchMatch>) -> bool + Sync,
+) {
+  let found = AtomicUsize::new(0);
+  let mut builder = WalkBuilder::new(&config.root);
+  builder
+    .overrides(config.overrides.clone())
+    .standard_filters(config.git_ignore)
+    .threads(config.concurrency);
+
+  builder.build_parallel().run(|| {
+    let found = &found;
+    let emit = &emit;
+    Box::new(move |entry| {
+      let Ok(entry) = entry else {
+        return ignore::WalkState::Continue;
+      };
+      if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+        return ignore::WalkState::Continue;
+      }
+      let relative = entry
+        .path()
+        .strip_prefix(&config.root)
+        .ok()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(entry.path());
+      let Ok(mut matches) = search_file(entry.path(), &relative.to_string_lossy(), config) else {
+        return ignore::WalkState::Continue;
+      };
+      if matches.is_empty() {
+        return ignore::WalkState::Continue;
+      }
+
+      let mut done = false;
+      if let Some(max) = config.max_matches.filter(|_| stop_at_max) {
+        let before = found.fetch_add(matches.len(), Ordering::SeqCst);
+        if before >= max {
+          return ignore::WalkState::Quit;
+        }
+        if before + matches.len() >= max {
+          matches.truncate(max - before);
+          done = true;
+        }
+      }
+      if !emit(matches) || done {
+        return ignore::WalkState::Quit;
+      }
+      ignore::WalkState::Continue
+    })
+  });
+}
+
+fn search_impl(pattern: String, options: Option<SearchOptions>) -> Result<Vec<SearchMatch>> {
+  let config = prepare(&pattern, options)?;
+  // Files finish in any order, so the walk collects every file's matches (at most
+  // `max_matches` each) and the first `max_matches` in path order are kept afterwards.
+  let results = Mutex::new(Vec::new());
+  run_search(&config, false, |matches| {
+    results.lock().unwrap().extend(matches);
+    true
+  });
+  let mut results = results.into_inner().unwrap();
+  results.sort_by(|a, b| {
+    a.path
+      .cmp(&b.path)
+      .then(a.line.cmp(&b.line))
+      .then(a.column.cmp(&b.column))
+  });
+  if let Some(max) = config.max_matches {
+    results.truncate(max);
+  }
+  Ok(results)
+}
+
+#[napi(js_name = "searchSync")]
+pub fn search_sync(pattern: String, options: Option<SearchOptions>) -> Result<Vec<SearchMatch>> {
+  search_impl(pattern, options)
+}
+
+// ========= async version =========
+
+pub struct SearchTask {
+  pub pattern: String,
+  pub options: Option<SearchOptions>,
+}
+
+impl Task for SearchTask {
+  type Output = Vec<SearchMatch>;
+  type JsValue = Vec<SearchMatch>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    search_impl(self.pattern.clone(), self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "search")]
+pub fn search(pattern: String, options: Option<SearchOptions>) -> AsyncTask<SearchTask> {
+  AsyncTask::new(SearchTask { pattern, options })
+}
+
+// ========= streaming version =========
+
+/// Async iterable of match batches (one batch per file, in the order files finish), returned
+/// by `searchStream`. Breaking out of `for await` stops the walk.
+#[napi(async_iterator)]
+pub struct SearchStream {
+  receiver: Arc<tokio::sync::Mutex<mpsc::Receiver<Vec<SearchMatch>>>>,
+}
+
+#[napi]
+impl AsyncGenerator for SearchStream {
+  type Yield = Vec<SearchMatch>;
+  type Next = ();
+  type Return = ();
+
+  fn next(
+    &mut self,
+    _value: Option<Self::Next>,
+  ) -> impl Future<Output = Result<Option<Self::Yield>>> + Send + 'static {
+    let receiver = self.receiver.clone();
+    async move { Ok(receiver.lock().await.recv().await) }
+  }
+
+  fn complete(
+    &mut self,
+    _value: Option<Self::Return>,
+  ) -> impl Future<Output = Result<Option<Self::Yield>>> + Send + 'static {
+    let receiver = self.receiver.clone();
+    async move {
+      // The walker sees the closed channel on its next send and quits.
+      receiver.lock().await.close();
+      Ok(None)
+    }
+  }
+}
+
+#[napi(js_name = "searchStream")]
+pub fn search_stream(pattern: String, options: Option<SearchOptions>) -> Result<SearchStream> {
+  let config = prepare(&pattern, options)?;
+  let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+  std::thread::spawn(move || {
+    run_search(&config, true, |matches| {
+      sender.blocking_send(matches).is_ok()
+    });
+  });
+  Ok(SearchStream {
+    receiver: Arc::new(tokio::sync::Mutex::new(receiver)),
+  })
+}
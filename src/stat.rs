@@ -21,7 +21,7 @@ fn system_time_to_ms(t: std::time::SystemTime) -> f64 {
   }
 }
 
-fn metadata_to_stats(meta: &fs::Metadata) -> Stats {
+pub(crate) fn metadata_to_stats(meta: &fs::Metadata) -> Stats {
   #[cfg(unix)]
   {
     let atime_ms = secs_nanos_to_ms(meta.atime(), meta.atime_nsec());
@@ -0,0 +1,450 @@
+use crate::archive::{
+  entry_path, unix_time, unsafe_path_error, ArchiveSummary, EntryPath, Extractor,
+};
+use crate::compression::{codec_for_read, decoder, Compression};
+use crate::glob::build_overrides;
+use crate::utils::uv_error;
+use ignore::WalkBuilder;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tar::{EntryType, Header, HeaderMode};
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct TarCreateOptions {
+  /// Compress the archive with gzip (`.tar.gz`).
+  pub gzip: Option<bool>,
+  /// Glob patterns (relative to `dir`) of paths to leave out.
+  pub exclude: Option<Vec<String>>,
+  /// Honour `.gitignore` / `.ignore` files and skip hidden entries.
+  pub git_ignore: Option<bool>,
+  /// Metadata to record as is. See `TarPreserveOptions`.
+  pub preserve: Option<TarPreserveOptions>,
+}
+
+/// Metadata `tarCreate` records from the filesystem (all default to false).
+///
+/// Without them the archive is reproducible: modes are normalised to 0o755 (directories and
+/// executables) or 0o644, owners to 0:0 and mtimes to a fixed date, so the same tree always
+/// produces the same bytes.
+#[napi(object)]
+#[derive(Clone)]
+pub struct TarPreserveOptions {
+  pub mode: Option<bool>,
+  pub ownership: Option<bool>,
+  pub timestamps: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct TarExtractOptions {
+  /// Number of leading path components to drop from every entry (like `tar --strip-components`).
+  pub strip: Option<u32>,
+  /// Glob patterns (matched after `strip`); only matching entries are extracted.
+  pub filter: Option<Vec<String>>,
+  /// Number of threads writing files. 0 or 1 extracts sequentially. Default: 4.
+  pub concurrency: Option<u32>,
+}
+
+enum WalkedKind {
+  File,
+  Symlink,
+  Dir,
+}
+
+fn tar_create_impl(
+  dir: String,
+  out_file: String,
+  options: Option<TarCreateOptions>,
+) -> Result<ArchiveSummary> {
+  let opts = options.unwrap_or(TarCreateOptions {
+    gzip: None,
+    exclude: None,
+    git_ignore: None,
+    preserve: None,
+  });
+  let preserve = opts.preserve.unwrap_or(TarPreserveOptions {
+    mode: None,
+    ownership: None,
+    timestamps: None,
+  });
+
+  match fs::metadata(&dir) {
+    Ok(m) if m.is_dir() => {}
+    Ok(_) => {
+      return Err(Error::from_reason(format!(
+        "ENOTDIR: not a directory, tarCreate '{}'",
+        dir
+      )))
+    }
+    Err(_) => {
+      return Err(Error::from_reason(format!(
+        "ENOENT: no such file or directory, tarCreate '{}'",
+        dir
+      )))
+    }
+  }
+  let root = fs::canonicalize(&dir).map_err(|e| uv_error(&e, "tarCreate", Path::new(&dir)))?;
+  let root_str = root.to_string_lossy().to_string();
+
+  let overrides = build_overrides(&root_str, &[], opts.exclude.as_deref().unwrap_or_default())?;
+  let mut builder = WalkBuilder::new(&root);
+  builder
+    .overrides(overrides)
+    .standard_filters(opts.git_ignore.unwrap_or(false));
+
+  let walked: Arc<Mutex<Vec<(String, PathBuf, WalkedKind)>>> = Arc::new(Mutex::new(Vec::new()));
+  let first_error: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+
+  builder.build_parallel().run(|| {
+    let walked = walked.clone();
+    let first_error = first_error.clone();
+    let root = root.clone();
+    Box::new(move |entry| {
+      let entry = match entry {
+        Ok(e) => e,
+        Err(e) => {
+          first_error.lock().unwrap().get_or_insert(e.to_string());
+          return ignore::WalkState::Quit;
+        }
+      };
+      if entry.depth() == 0 {
+        return ignore::WalkState::Continue;
+      }
+      let kind = match entry.file_type() {
+        Some(ft) if ft.is_dir() => WalkedKind::Dir,
+        Some(ft) if ft.is_file() => WalkedKind::File,
+        Some(ft) if ft.is_symlink() => WalkedKind::Symlink,
+        // FIFOs, sockets and devices are not archived.
+        _ => return ignore::WalkState::Continue,
+      };
+      let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+      // Archive paths always use `/`, whatever the platform.
+      let name = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+      walked
+        .lock()
+        .unwrap()
+        .push((name, entry.path().to_path_buf(), kind));
+      ignore::WalkState::Continue
+    })
+  });
+
+  if let Some(reason) = first_error.lock().unwrap().take() {
+    return Err(Error::from_reason(reason));
+  }
+  let mut walked = std::mem::take(&mut *walked.lock().unwrap());
+  // Parents sort before their children, and the order no longer depends on thread timing.
+  walked.sort_by(|a, b| a.0.cmp(&b.0));
+
+  let file = fs::File::create(&out_file).map_err(|e| uv_error(&e, "open", Path::new(&out_file)))?;
+  // The archive may be written inside the directory it archives; never add it to itself.
+  let out_path =
+    fs::canonicalize(&out_file).map_err(|e| uv_error(&e, "open", Path::new(&out_file)))?;
+  let writer = BufWriter::with_capacity(64 * 1024, file);
+  let write_error = |e: io::Error| uv_error(&e, "write", Path::new(&out_file));
+
+  if opts.gzip.unwrap_or(false) {
+    let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    let summary = append_entries(&mut tar, &walked, &out_path, &preserve)?;
+    tar
+      .into_inner()
+      .and_then(|encoder| encoder.finish())
+      .and_then(|mut writer| writer.flush())
+      .map_err(write_error)?;
+    Ok(summary)
+  } else {
+    let mut tar = tar::Builder::new(writer);
+    let summary = append_entries(&mut tar, &walked, &out_path, &preserve)?;
+    tar
+      .into_inner()
+      .and_then(|mut writer| writer.flush())
+      .map_err(write_error)?;
+    Ok(summary)
+  }
+}
+
+fn append_entries<W: Write>(
+  tar: &mut tar::Builder<W>,
+  walked: &[(String, PathBuf, WalkedKind)],
+  out_path: &Path,
+  preserve: &TarPreserveOptions,
+) -> Result<ArchiveSummary> {
+  let mut summary = ArchiveSummary::default();
+  for (name, path, kind) in walked {
+    if path == out_path {
+      continue;
+    }
+    let meta = fs::symlink_metadata(path).map_err(|e| uv_error(&e, "lstat", path))?;
+    let mut header = header_for(&meta, preserve);
+    let append_error = |e: io::Error| uv_error(&e, "tarCreate", path);
+    match kind {
+      WalkedKind::Dir => {
+        header.set_size(0);
+        tar
+          .append_data(&mut header, format!("{}/", name), io::empty())
+          .map_err(append_error)?;
+        summary.directories += 1;
+      }
+      WalkedKind::File => {
+        let file = fs::File::open(path).map_err(|e| uv_error(&e, "open", path))?;
+        // The header already carries the size; a file growing meanwhile must not overrun it.
+        tar
+          .append_data(&mut header, name, file.take(meta.len()))
+          .map_err(append_error)?;
+        summary.files += 1;
+        summary.bytes += meta.len() as f64;
+      }
+      WalkedKind::Symlink => {
+        let target = fs::read_link(path).map_err(|e| uv_error(&e, "readlink", path))?;
+        header.set_size(0);
+        tar
+          .append_link(&mut header, name, target)
+          .map_err(append_error)?;
+        summary.symlinks += 1;
+      }
+    }
+  }
+  Ok(summary)
+}
+
+fn header_for(meta: &fs::Metadata, preserve: &TarPreserveOptions) -> Header {
+  let mut header = Header::new_gnu();
+  header.set_metadata_in_mode(meta, HeaderMode::Deterministic);
+  if preserve.mode.unwrap_or(false)
+    || preserve.ownership.unwrap_or(false)
+    || preserve.timestamps.unwrap_or(false)
+  {
+    let mut complete = Header::new_gnu();
+    complete.set_metadata_in_mode(meta, HeaderMode::Complete);
+    if preserve.mode.unwrap_or(false) {
+      if let Ok(mode) = complete.mode() {
+        header.set_mode(mode);
+      }
+    }
+    if preserve.ownership.unwrap_or(false) {
+      if let (Ok(uid), Ok(gid)) = (complete.uid(), complete.gid()) {
+        header.set_uid(uid);
+        header.set_gid(gid);
+      }
+    }
+    if preserve.timestamps.unwrap_or(false) {
+      if let Ok(mtime) = complete.mtime() {
+        header.set_mtime(mtime);
+      }
+    }
+  }
+  header
+}
+
+fn tar_extract_impl(
+  archive: String,
+  dest: String,
+  options: Option<TarExtractOptions>,
+) -> Result<ArchiveSummary> {
+  let opts = options.unwrap_or(TarExtractOptions {
+    strip: None,
+    filter: None,
+    concurrency: None,
+  });
+  let strip = opts.strip.unwrap_or(0) as usize;
+  let concurrency = opts.concurrency.unwrap_or(4) as usize;
+  let filter = match opts.filter.as_deref() {
+    Some(patterns) if !patterns.is_empty() => Some(build_overrides(&dest, patterns, &[])?),
+    _ => None,
+  };
+
+  let file = fs::File::open(&archive).map_err(|e| uv_error(&e, "open", Path::new(&archive)))?;
+  let mut reader = BufReader::with_capacity(64 * 1024, file);
+  // gzip / zstd archives are recognised by their magic bytes, brotli by `.br`.
+  let codec = codec_for_read(Some(Compression::Auto), Path::new(&archive), &mut reader)
+    .map_err(|e| uv_error(&e, "read", Path::new(&archive)))?;
+  let reader: Box<dyn Read> = match codec {
+    Some(codec) => decoder(codec, reader).map_err(|e| uv_error(&e, "read", Path::new(&archive)))?,
+    None => Box::new(reader),
+  };
+  let read_error = |e: io::Error| Error::from_reason(format!("{}, tarExtract '{}'", e, archive));
+
+  let mut extractor = Extractor::new(&dest, concurrency, "tarExtract", &archive)?;
+  let mut tar = tar::Archive::new(reader);
+  for entry in tar.entries().map_err(read_error)? {
+    let mut entry = entry.map_err(read_error)?;
+    let entry_type = entry.header().entry_type();
+    if !matches!(
+      entry_type,
+      EntryType::Regular
+        | EntryType::Continuous
+        | EntryType::GNUSparse
+        | EntryType::Directory
+        | EntryType::Symlink
+        | EntryType::Link
+    ) {
+      // Devices, FIFOs and the pax / GNU metadata records the tar crate has already applied.
+      continue;
+    }
+    let raw = entry.path().map_err(read_error)?.into_owned();
+    let rel = match entry_path(&raw, strip) {
+      EntryPath::Safe(rel) => rel,
+      EntryPath::Empty => continue,
+      EntryPath::Escapes => {
+        return Err(unsafe_path_error(
+          &raw.to_string_lossy(),
+          &dest,
+          "tarExtract",
+          &archive,
+        ))
+      }
+    };
+    let is_dir = entry_type == EntryType::Directory;
+    if let Some(filter) = &filter {
+      if !filter.matched(&rel, is_dir).is_whitelist() {
+        continue;
+      }
+    }
+    let mode = entry.header().mode().ok();
+    let mtime = entry.header().mtime().ok().map(unix_time);
+
+    match entry_type {
+      EntryType::Directory => extractor.dir(rel, mode, mtime)?,
+      EntryType::Symlink => {
+        let target = entry
+          .link_name()
+          .map_err(read_error)?
+          .map(|t| t.into_owned())
+          .unwrap_or_default();
+        extractor.symlink(rel, target)?;
+      }
+      EntryType::Link => {
+        let raw_target = entry
+          .link_name()
+          .map_err(read_error)?
+          .map(|t| t.into_owned())
+          .unwrap_or_default();
+        match entry_path(&raw_target, strip) {
+          EntryPath::Safe(target) => extractor.hardlink(rel, target)?,
+          _ => {
+            return Err(unsafe_path_error(
+              &raw_target.to_string_lossy(),
+              &dest,
+              "tarExtract",
+              &archive,
+            ))
+          }
+        }
+      }
+      _ => {
+        let size = entry.size();
+        if extractor.buffers(size) {
+          let mut data = Vec::with_capacity(size as usize);
+          entry.read_to_end(&mut data).map_err(read_error)?;
+          extractor.file(rel, data, mode, mtime)?;
+        } else {
+          extractor.file_from_reader(rel, &mut entry, mode, mtime)?;
+        }
+      }
+    }
+  }
+
+  extractor.finish()
+}
+
+#[napi(js_name = "tarCreateSync")]
+pub fn tar_create_sync(
+  dir: String,
+  out_file: String,
+  options: Option<TarCreateOptions>,
+) -> Result<ArchiveSummary> {
+  tar_create_impl(dir, out_file, options)
+}
+
+#[napi(js_name = "tarExtractSync")]
+pub fn tar_extract_sync(
+  archive: String,
+  dest_dir: String,
+  options: Option<TarExtractOptions>,
+) -> Result<ArchiveSummary> {
+  tar_extract_impl(archive, dest_dir, options)
+}
+
+// ========= async version =========
+
+pub struct TarCreateTask {
+  pub dir: String,
+  pub out_file: String,
+  pub options: Option<TarCreateOptions>,
+}
+
+impl Task for TarCreateTask {
+  type Output = ArchiveSummary;
+  type JsValue = ArchiveSummary;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    tar_create_impl(
+      self.dir.clone(),
+      self.out_file.clone(),
+      self.options.clone(),
+    )
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "tarCreate")]
+pub fn tar_create(
+  dir: String,
+  out_file: String,
+  options: Option<TarCreateOptions>,
+) -> AsyncTask<TarCreateTask> {
+  AsyncTask::new(TarCreateTask {
+    dir,
+    out_file,
+    options,
+  })
+}
+
+pub struct TarExtractTask {
+  pub archive: String,
+  pub dest_dir: String,
+  pub options: Option<TarExtractOptions>,
+}
+
+impl Task for TarExtractTask {
+  type Output = ArchiveSummary;
+  type JsValue = ArchiveSummary;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    tar_extract_impl(
+      self.archive.clone(),
+      self.dest_dir.clone(),
+      self.options.clone(),
+    )
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "tarExtract")]
+pub fn tar_extract(
+  archive: String,
+  dest_dir: String,
+  options: Option<TarExtractOptions>,
+) -> AsyncTask<TarExtractTask> {
+  AsyncTask::new(TarExtractTask {
+    archive,
+    dest_dir,
+    options,
+  })
+}
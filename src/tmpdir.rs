@@ -0,0 +1,146 @@
+use crate::cleanup::{forget_on_exit, remove_on_exit};
+use crate::mkdtemp::mkdtemp_impl;
+use crate::rm::{remove, RmOptions};
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::path::Path;
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct TmpdirOptions {
+  /// Name prefix, joined onto the OS temp directory (an absolute prefix is used as-is).
+  /// Default: `'rush-fs-'`.
+  pub prefix: Option<String>,
+  /// Leave the directory in place when the handle is disposed. Default: false.
+  pub keep: Option<bool>,
+  /// Remove the directory when the process exits, if it is still there. Default: false.
+  pub remove_on_exit: Option<bool>,
+}
+
+/// A temporary directory created by `tmpdir`. `await using` (`Symbol.asyncDispose`) and `using`
+/// (`Symbol.dispose`) remove it with `rm` unless it was created with `keep: true`.
+#[napi]
+pub struct TempDir {
+  #[napi(readonly)]
+  pub path: String,
+  #[napi(readonly)]
+  pub keep: bool,
+}
+
+fn remove_tree(path: &str) -> Result<()> {
+  let options = RmOptions {
+    force: Some(true),
+    recursive: Some(true),
+    max_retries: None,
+    retry_delay: None,
+    concurrency: None,
+    dry_run: None,
+    on_error: None,
+  };
+  remove(path.to_string(), Some(options), &mut Vec::new())?;
+  forget_on_exit(Path::new(path));
+  Ok(())
+}
+
+pub struct TempDirRemoveTask {
+  path: String,
+  skip: bool,
+}
+
+impl Task for TempDirRemoveTask {
+  type Output = ();
+  type JsValue = ();
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    if self.skip {
+      return Ok(());
+    }
+    remove_tree(&self.path)
+  }
+
+  fn resolve(&mut self, _env: Env, _output: Self::Output) -> Result<Self::JsValue> {
+    Ok(())
+  }
+}
+
+#[napi]
+impl TempDir {
+  /// Removes the directory tree, `keep` or not.
+  #[napi]
+  pub fn remove(&self) -> AsyncTask<TempDirRemoveTask> {
+    AsyncTask::new(TempDirRemoveTask {
+      path: self.path.clone(),
+      skip: false,
+    })
+  }
+
+  #[napi(js_name = "removeSync")]
+  pub fn remove_sync(&self) -> Result<()> {
+    remove_tree(&self.path)
+  }
+
+  /// `Symbol.asyncDispose`: `remove()` unless `keep` is set.
+  #[napi]
+  pub fn dispose(&self) -> AsyncTask<TempDirRemoveTask> {
+    AsyncTask::new(TempDirRemoveTask {
+      path: self.path.clone(),
+      skip: self.keep,
+    })
+  }
+
+  /// `Symbol.dispose`: `removeSync()` unless `keep` is set.
+  #[napi(js_name = "disposeSync")]
+  pub fn dispose_sync(&self) -> Result<()> {
+    if self.keep {
+      return Ok(());
+    }
+    remove_tree(&self.path)
+  }
+}
+
+fn create(options: Option<TmpdirOptions>) -> Result<TempDir> {
+  let options = options.unwrap_or(TmpdirOptions {
+    prefix: None,
+    keep: None,
+    remove_on_exit: None,
+  });
+  let prefix = std::env::temp_dir().join(options.prefix.as_deref().unwrap_or("rush-fs-"));
+  let path = mkdtemp_impl(prefix.to_string_lossy().into_owned())?;
+  if options.remove_on_exit.unwrap_or(false) {
+    remove_on_exit(Path::new(&path), None);
+  }
+  Ok(TempDir {
+    path,
+    keep: options.keep.unwrap_or(false),
+  })
+}
+
+#[napi(js_name = "tmpdirSync")]
+pub fn tmpdir_sync(options: Option<TmpdirOptions>) -> Result<TempDir> {
+  create(options)
+}
+
+// ========= async version =========
+
+pub struct TmpdirTask {
+  pub options: Option<TmpdirOptions>,
+}
+
+impl Task for TmpdirTask {
+  type Output = TempDir;
+  type JsValue = TempDir;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    create(self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "tmpdir")]
+pub fn tmpdir(options: Option<TmpdirOptions>) -> AsyncTask<TmpdirTask> {
+  AsyncTask::new(TmpdirTask { options })
+}
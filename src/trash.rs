@@ -0,0 +1,646 @@
+use crate::types::ms_to_datetime;
+use chrono::{DateTime, Local};
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+
+/// An entry of a trash directory, as returned by `trash` and `listTrash`.
+#[napi]
+pub struct TrashItem {
+  /// Name of the entry inside the trash (unique per trash directory).
+  #[napi(readonly)]
+  pub name: String,
+  /// Location of the trashed entry (`<trashDir>/files/<name>`), to pass to `restoreFromTrash`.
+  #[napi(readonly)]
+  pub path: String,
+  /// Absolute path the entry was trashed from.
+  #[napi(readonly, js_name = "originalPath")]
+  pub original_path: String,
+  /// The trash directory holding the entry: `$XDG_DATA_HOME/Trash` or a per-volume
+  /// `.Trash-$uid` / `.Trash/$uid`.
+  #[napi(readonly, js_name = "trashDir")]
+  pub trash_dir: String,
+  #[napi(readonly, js_name = "deletionDateMs")]
+  pub deletion_date_ms: f64,
+}
+
+#[napi]
+impl TrashItem {
+  /// Returns the deletion date as a Date object
+  #[napi(getter, js_name = "deletionDate")]
+  pub fn deletion_date(&self) -> DateTime<Local> {
+    ms_to_datetime(self.deletion_date_ms)
+  }
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct RestoreFromTrashOptions {
+  /// Replace an entry that now exists at the original path. Default: false (fails with EEXIST).
+  /// The entry is moved aside first and deleted only once the restore succeeded.
+  pub overwrite: Option<bool>,
+}
+
+/// The freedesktop.org Trash specification (https://specifications.freedesktop.org/trash-spec/),
+/// shared by Linux and BSD desktops. macOS and Windows keep their own formats.
+#[cfg(all(unix, not(target_os = "macos")))]
+mod freedesktop {
+  use super::TrashItem;
+  use crate::mkdtemp::generate_random_suffix;
+  use crate::utils::uv_error;
+  use chrono::{Local, NaiveDateTime, TimeZone};
+  use napi::bindgen_prelude::*;
+  use std::env;
+  use std::ffi::{OsStr, OsString};
+  use std::fs::{self, DirBuilder, File, OpenOptions};
+  use std::io::{self, Write};
+  use std::os::unix::ffi::{OsStrExt, OsStringExt};
+  use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+  use std::path::{Component, Path, PathBuf};
+
+  const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+  fn errno(code: i32) -> io::Error {
+    io::Error::from_raw_os_error(code)
+  }
+
+  /// `$XDG_DATA_HOME/Trash`, with `$XDG_DATA_HOME` defaulting to `$HOME/.local/share`.
+  fn home_trash() -> io::Result<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+      .map(PathBuf::from)
+      .filter(|dir| dir.is_absolute())
+      .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+      .map(|data| data.join("Trash"))
+      .ok_or_else(|| errno(libc::ENOENT))
+  }
+
+  fn ensure_trash(trash: &Path) -> io::Result<()> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true).mode(0o700);
+    builder.create(trash.join("files"))?;
+    builder.create(trash.join("info"))
+  }
+
+  /// Walks up from `dir` while the device stays the same, returning the mount point.
+  fn mount_top(dir: &Path) -> io::Result<PathBuf> {
+    let dev = fs::metadata(dir)?.dev();
+    let mut top = dir;
+    while let Some(parent) = top.parent() {
+      if fs::metadata(parent)?.dev() != dev {
+        break;
+      }
+      top = parent;
+    }
+    Ok(top.to_path_buf())
+  }
+
+  /// The trash directory of the volume mounted at `top`: `$top/.Trash/$uid` when the admin
+  /// provided a sticky (0o1000), non-symlink `$top/.Trash`, otherwise `$top/.Trash-$uid`.
+  fn volume_trash(top: &Path, create: bool) -> io::Result<PathBuf> {
+    let uid = unsafe { libc::getuid() };
+    let shared = top.join(".Trash");
+    if let Ok(meta) = fs::symlink_metadata(&shared) {
+      if meta.is_dir() && meta.mode() & 0o1000 != 0 {
+        let trash = shared.join(uid.to_string());
+        if !create || ensure_trash(&trash).is_ok() {
+          return Ok(trash);
+        }
+      }
+    }
+    let trash = top.join(format!(".Trash-{}", uid));
+    if create {
+      ensure_trash(&trash)?;
+    }
+    let meta = fs::symlink_metadata(&trash)?;
+    if !meta.is_dir() || meta.uid() != uid {
+      return Err(errno(libc::EACCES));
+    }
+    Ok(trash)
+  }
+
+  /// The volume a trash directory lives on, against which relative `Path=` keys resolve.
+  fn trash_top(trash: &Path) -> Option<&Path> {
+    let parent = trash.parent()?;
+    if parent.file_name() == Some(OsStr::new(".Trash")) {
+      parent.parent()
+    } else {
+      Some(parent)
+    }
+  }
+
+  fn encode_path(path: &Path) -> String {
+    let mut out = String::new();
+    for &b in path.as_os_str().as_bytes() {
+      if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+        out.push(b as char);
+      } else {
+        out.push_str(&format!("%{:02X}", b));
+      }
+    }
+    out
+  }
+
+  fn decode_path(value: &str) -> PathBuf {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+      // Work on bytes: the two characters after `%` need not be ASCII, or even whole chars.
+      if bytes[i] == b'%' && i + 2 < bytes.len() {
+        let digit = |b: u8| (b as char).to_digit(16);
+        if let (Some(hi), Some(lo)) = (digit(bytes[i + 1]), digit(bytes[i + 2])) {
+          out.push((hi * 16 + lo) as u8);
+          i += 3;
+          continue;
+        }
+      }
+      out.push(bytes[i]);
+      i += 1;
+    }
+    PathBuf::from(OsString::from_vec(out))
+  }
+
+  /// Parses a `.trashinfo` file into its (absolute) original path and deletion time in ms.
+  fn read_info(info: &Path, top: Option<&Path>) -> io::Result<(PathBuf, f64)> {
+    let content = fs::read_to_string(info)?;
+    let mut in_group = false;
+    let mut original = None;
+    let mut deleted = 0.0;
+    for line in content.lines() {
+      let line = line.trim();
+      if line.starts_with('[') {
+        in_group = line == "[Trash Info]";
+      } else if let (true, Some((key, value))) = (in_group, line.split_once('=')) {
+        match key.trim() {
+          "Path" => original = Some(decode_path(value.trim())),
+          "DeletionDate" => {
+            deleted = NaiveDateTime::parse_from_str(value.trim(), DATE_FORMAT)
+              .ok()
+              .and_then(|date| Local.from_local_datetime(&date).earliest())
+              .map(|date| date.timestamp_millis() as f64)
+              .unwrap_or(0.0)
+          }
+          _ => {}
+        }
+      }
+    }
+    let original = original.ok_or_else(|| errno(libc::EINVAL))?;
+    let original = match top {
+      Some(top) if original.is_relative() => top.join(original),
+      _ => original,
+    };
+    Ok((original, deleted))
+  }
+
+  /// `base`, then `stem.2.ext`, `stem.3.ext`, ... like common file managers.
+  fn candidate_name(base: &OsStr, n: u32) -> OsString {
+    if n == 1 {
+      return base.to_os_string();
+    }
+    let path = Path::new(base);
+    let mut name = path.file_stem().unwrap_or(base).to_os_string();
+    name.push(format!(".{}", n));
+    if let Some(ext) = path.extension() {
+      name.push(".");
+      name.push(ext);
+    }
+    name
+  }
+
+  /// Reserves a name in `trash` by creating its `.trashinfo` with O_EXCL, as the spec requires,
+  /// so concurrent trashers never pick the same name.
+  fn reserve(trash: &Path, base: &OsStr, original: &Path) -> io::Result<(OsString, PathBuf)> {
+    let date = Local::now().format(DATE_FORMAT);
+    for n in 1.. {
+      let name = candidate_name(base, n);
+      let mut info_name = name.clone();
+      info_name.push(".trashinfo");
+      let info = trash.join("info").join(info_name);
+      let mut file = match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&info)
+      {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+        Err(e) => return Err(e),
+      };
+      // A leftover entry without its info file still occupies the name.
+      if fs::symlink_metadata(trash.join("files").join(&name)).is_ok() {
+        drop(file);
+        let _ = fs::remove_file(&info);
+        continue;
+      }
+      let written = write!(
+        file,
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(original),
+        date
+      )
+      .and_then(|_| file.sync_all());
+      if let Err(e) = written {
+        let _ = fs::remove_file(&info);
+        return Err(e);
+      }
+      return Ok((name, info));
+    }
+    unreachable!()
+  }
+
+  /// Copies `src` to `dest` without following symlinks, keeping modes and file mtimes.
+  fn copy_tree(src: &Path, dest: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(src)?;
+    let file_type = meta.file_type();
+    if file_type.is_symlink() {
+      std::os::unix::fs::symlink(fs::read_link(src)?, dest)?;
+    } else if file_type.is_dir() {
+      DirBuilder::new().mode(0o700).create(dest)?;
+      for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        copy_tree(&entry.path(), &dest.join(entry.file_name()))?;
+      }
+      fs::set_permissions(dest, fs::Permissions::from_mode(meta.mode() & 0o7777))?;
+    } else if file_type.is_file() {
+      fs::copy(src, dest)?;
+      File::options()
+        .write(true)
+        .open(dest)?
+        .set_modified(meta.modified()?)?;
+    } else {
+      // Sockets, fifos and devices cannot be recreated portably.
+      return Err(errno(libc::EXDEV));
+    }
+    Ok(())
+  }
+
+  fn remove_tree(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+      fs::remove_dir_all(path)
+    } else {
+      fs::remove_file(path)
+    }
+  }
+
+  /// Renames `src` to `dest`, copying and deleting when they are on different devices.
+  fn move_entry(src: &Path, dest: &Path) -> io::Result<()> {
+    match fs::rename(src, dest) {
+      Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {
+        if let Err(e) = copy_tree(src, dest) {
+          let _ = remove_tree(dest);
+          return Err(e);
+        }
+        remove_tree(src)
+      }
+      result => result,
+    }
+  }
+
+  /// Absolute form of `path` with its parent resolved but the entry itself not followed, so a
+  /// symlink is trashed rather than its target.
+  fn absolute_entry(path: &Path) -> io::Result<PathBuf> {
+    match (path.parent(), path.components().next_back()) {
+      (Some(parent), Some(Component::Normal(name))) => {
+        let parent = if parent.as_os_str().is_empty() {
+          env::current_dir()?
+        } else {
+          fs::canonicalize(parent)?
+        };
+        Ok(parent.join(name))
+      }
+      _ => fs::canonicalize(path),
+    }
+  }
+
+  fn trash_error(e: io::Error, path: &str) -> Error {
+    uv_error(&e, "trash", Path::new(path))
+  }
+
+  pub(super) fn trash(path_str: &str) -> Result<TrashItem> {
+    let path = absolute_entry(Path::new(path_str)).map_err(|e| trash_error(e, path_str))?;
+    let meta = fs::symlink_metadata(&path).map_err(|e| trash_error(e, path_str))?;
+    let name = match path.file_name() {
+      Some(name) => name.to_os_string(),
+      None => return Err(trash_error(errno(libc::EBUSY), path_str)),
+    };
+
+    let home = home_trash().map_err(|e| trash_error(e, path_str))?;
+    ensure_trash(&home).map_err(|e| trash_error(e, &home.to_string_lossy()))?;
+    let home_dev = fs::metadata(&home)
+      .map_err(|e| trash_error(e, &home.to_string_lossy()))?
+      .dev();
+
+    // Entries on another volume go to that volume's trash when one can be used, so trashing
+    // stays a rename; otherwise they are moved across devices into the home trash.
+    let parent = path.parent().unwrap_or(Path::new("/"));
+    let (trash, recorded) = if meta.dev() == home_dev {
+      (home, path.clone())
+    } else {
+      let top = mount_top(parent).map_err(|e| trash_error(e, path_str))?;
+      match volume_trash(&top, true) {
+        Ok(trash) => {
+          let relative = path.strip_prefix(&top).unwrap_or(&path).to_path_buf();
+          (trash, relative)
+        }
+        Err(_) => (home, path.clone()),
+      }
+    };
+
+    let (name, info) =
+      reserve(&trash, &name, &recorded).map_err(|e| trash_error(e, &trash.to_string_lossy()))?;
+    let dest = trash.join("files").join(&name);
+    if let Err(e) = move_entry(&path, &dest) {
+      let _ = fs::remove_file(&info);
+      return Err(trash_error(e, path_str));
+    }
+    Ok(TrashItem {
+      name: name.to_string_lossy().to_string(),
+      path: dest.to_string_lossy().to_string(),
+      original_path: path.to_string_lossy().to_string(),
+      trash_dir: trash.to_string_lossy().to_string(),
+      deletion_date_ms: read_info(&info, None).map(|(_, ms)| ms).unwrap_or(0.0),
+    })
+  }
+
+  /// Undoes the escaping of `/proc/self/mountinfo` (`\040` for a space, ...).
+  fn unescape_mount(field: &str) -> PathBuf {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+      if bytes[i] == b'\\' && i + 3 < bytes.len() {
+        let octal = bytes[i + 1..i + 4]
+          .iter()
+          .try_fold(0u32, |n, &b| (b as char).to_digit(8).map(|d| n * 8 + d));
+        if let Some(b) = octal.filter(|&b| b <= 0xff) {
+          out.push(b as u8);
+          i += 4;
+          continue;
+        }
+      }
+      out.push(bytes[i]);
+      i += 1;
+    }
+    PathBuf::from(OsString::from_vec(out))
+  }
+
+  /// Mount points worth probing for a per-volume trash. Only Linux exposes them cheaply; on
+  /// other systems `listTrash` covers the home trash.
+  fn mount_points() -> Vec<PathBuf> {
+    const VIRTUAL: &[&str] = &[
+      "autofs",
+      "binfmt_misc",
+      "bpf",
+      "cgroup",
+      "cgroup2",
+      "configfs",
+      "debugfs",
+      "devpts",
+      "fusectl",
+      "hugetlbfs",
+      "mqueue",
+      "proc",
+      "pstore",
+      "securityfs",
+      "sysfs",
+      "tracefs",
+    ];
+    let Ok(content) = fs::read_to_string("/proc/self/mountinfo") else {
+      return Vec::new();
+    };
+    let mut mounts: Vec<PathBuf> = content
+      .lines()
+      .filter_map(|line| {
+        let (fields, fs_fields) = line.split_once(" - ")?;
+        let fs_type = fs_fields.split(' ').next()?;
+        if VIRTUAL.contains(&fs_type) {
+          return None;
+        }
+        fields.split(' ').nth(4).map(unescape_mount)
+      })
+      .collect();
+    mounts.sort();
+    mounts.dedup();
+    mounts
+  }
+
+  fn list_dir(trash: &Path, top: Option<&Path>, items: &mut Vec<TrashItem>) {
+    let Ok(entries) = fs::read_dir(trash.join("info")) else {
+      return;
+    };
+    for entry in entries.flatten() {
+      let info_name = entry.file_name();
+      let Some(name) = info_name.as_bytes().strip_suffix(b".trashinfo") else {
+        continue;
+      };
+      let name = OsStr::from_bytes(name);
+      let path = trash.join("files").join(name);
+      if fs::symlink_metadata(&path).is_err() {
+        continue;
+      }
+      if let Ok((original, deleted)) = read_info(&entry.path(), top) {
+        items.push(TrashItem {
+          name: name.to_string_lossy().to_string(),
+          path: path.to_string_lossy().to_string(),
+          original_path: original.to_string_lossy().to_string(),
+          trash_dir: trash.to_string_lossy().to_string(),
+          deletion_date_ms: deleted,
+        });
+      }
+    }
+  }
+
+  pub(super) fn list() -> Result<Vec<TrashItem>> {
+    let mut items = Vec::new();
+    if let Ok(home) = home_trash() {
+      list_dir(&home, None, &mut items);
+    }
+    for top in mount_points() {
+      if let Ok(trash) = volume_trash(&top, false) {
+        list_dir(&trash, Some(&top), &mut items);
+      }
+    }
+    items.sort_by(|a, b| {
+      a.deletion_date_ms
+        .total_cmp(&b.deletion_date_ms)
+        .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(items)
+  }
+
+  pub(super) fn restore(path_str: &str, overwrite: bool) -> Result<String> {
+    let restore_error =
+      |e: io::Error, path: &str| uv_error(&e, "restoreFromTrash", Path::new(path));
+    let path = Path::new(path_str);
+    let (Some(name), Some(files)) = (path.file_name(), path.parent()) else {
+      return Err(restore_error(errno(libc::EINVAL), path_str));
+    };
+    let trash = match files.parent() {
+      Some(trash) if files.file_name() == Some(OsStr::new("files")) => trash,
+      _ => return Err(restore_error(errno(libc::EINVAL), path_str)),
+    };
+    let mut info_name = name.to_os_string();
+    info_name.push(".trashinfo");
+    let info = trash.join("info").join(info_name);
+    fs::symlink_metadata(path).map_err(|e| restore_error(e, path_str))?;
+    let (original, _) =
+      read_info(&info, trash_top(trash)).map_err(|e| restore_error(e, &info.to_string_lossy()))?;
+    let original_str = original.to_string_lossy().to_string();
+
+    // With `overwrite`, the existing entry is only moved aside (to a random name next to it)
+    // until the restore has succeeded, and put back if it fails.
+    let mut aside = None;
+    if fs::symlink_metadata(&original).is_ok() {
+      if !overwrite {
+        return Err(restore_error(errno(libc::EEXIST), &original_str));
+      }
+      let mut aside_name = OsString::from(".");
+      aside_name.push(original.file_name().unwrap_or_default());
+      aside_name.push(format!(".{}.rush-fs-restore", generate_random_suffix()));
+      let aside_path = original.with_file_name(aside_name);
+      fs::rename(&original, &aside_path).map_err(|e| restore_error(e, &original_str))?;
+      aside = Some(aside_path);
+    }
+    let restored = match original.parent() {
+      Some(parent) => fs::create_dir_all(parent).map_err(|e| restore_error(e, &original_str)),
+      None => Ok(()),
+    }
+    .and_then(|_| move_entry(path, &original).map_err(|e| restore_error(e, path_str)));
+    if let Err(e) = restored {
+      if let Some(aside) = &aside {
+        let _ = fs::rename(aside, &original);
+      }
+      return Err(e);
+    }
+    if let Some(aside) = &aside {
+      let _ = remove_tree(aside);
+    }
+    let _ = fs::remove_file(&info);
+    Ok(original_str)
+  }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+mod freedesktop {
+  use super::TrashItem;
+  use napi::bindgen_prelude::*;
+
+  fn unsupported(syscall: &str) -> Error {
+    Error::from_reason(format!("ENOSYS: function not implemented, {}", syscall))
+  }
+
+  pub(super) fn trash(_path: &str) -> Result<TrashItem> {
+    Err(unsupported("trash"))
+  }
+
+  pub(super) fn list() -> Result<Vec<TrashItem>> {
+    Err(unsupported("listTrash"))
+  }
+
+  pub(super) fn restore(_path: &str, _overwrite: bool) -> Result<String> {
+    Err(unsupported("restoreFromTrash"))
+  }
+}
+
+fn trash_impl(paths: &[String]) -> Result<Vec<TrashItem>> {
+  paths.iter().map(|path| freedesktop::trash(path)).collect()
+}
+
+fn restore_impl(path: &str, options: Option<RestoreFromTrashOptions>) -> Result<String> {
+  let overwrite = options.and_then(|o| o.overwrite).unwrap_or(false);
+  freedesktop::restore(path, overwrite)
+}
+
+/// Moves `paths` to the trash following the freedesktop.org Trash specification, returning
+/// the new entries in order. Stops at the first failure; earlier paths stay trashed.
+#[napi(js_name = "trashSync")]
+pub fn trash_sync(paths: Vec<String>) -> Result<Vec<TrashItem>> {
+  trash_impl(&paths)
+}
+
+/// Lists the entries of the home trash and of the per-volume trashes of mounted filesystems,
+/// oldest first.
+#[napi(js_name = "listTrashSync")]
+pub fn list_trash_sync() -> Result<Vec<TrashItem>> {
+  freedesktop::list()
+}
+
+/// Moves a trashed entry (`TrashItem.path`) back to its original location, recreating missing
+/// parent directories. Returns the restored path.
+#[napi(js_name = "restoreFromTrashSync")]
+pub fn restore_from_trash_sync(
+  path: String,
+  options: Option<RestoreFromTrashOptions>,
+) -> Result<String> {
+  restore_impl(&path, options)
+}
+
+// ========= async version =========
+
+pub struct TrashTask {
+  pub paths: Vec<String>,
+}
+
+impl Task for TrashTask {
+  type Output = Vec<TrashItem>;
+  type JsValue = Vec<TrashItem>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    trash_impl(&self.paths)
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "trash")]
+pub fn trash(paths: Vec<String>) -> AsyncTask<TrashTask> {
+  AsyncTask::new(TrashTask { paths })
+}
+
+pub struct ListTrashTask;
+
+impl Task for ListTrashTask {
+  type Output = Vec<TrashItem>;
+  type JsValue = Vec<TrashItem>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    freedesktop::list()
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "listTrash")]
+pub fn list_trash() -> AsyncTask<ListTrashTask> {
+  AsyncTask::new(ListTrashTask)
+}
+
+pub struct RestoreFromTrashTask {
+  pub path: String,
+  pub options: Option<RestoreFromTrashOptions>,
+}
+
+impl Task for RestoreFromTrashTask {
+  type Output = String;
+  type JsValue = String;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    restore_impl(&self.path, self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "restoreFromTrash")]
+pub fn restore_from_trash(
+  path: String,
+  options: Option<RestoreFromTrashOptions>,
+) -> AsyncTask<RestoreFromTrashTask> {
+  AsyncTask::new(RestoreFromTrashTask { path, options })
+}
@@ -162,7 +162,7 @@ impl Stats {
   }
 }
 
-fn ms_to_datetime(ms: f64) -> DateTime<Local> {
+pub(crate) fn ms_to_datetime(ms: f64) -> DateTime<Local> {
   // Node.js Stats.mtime (Date) effectively rounds the underlying *Ms value to
   // the nearest integer millisecond. Align with that behavior to avoid 1ms
   // mismatches in tests.
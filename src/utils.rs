@@ -1,5 +1,7 @@
+use std::io;
 #[cfg(unix)]
 use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
 
 pub fn get_file_type_id(ft: &std::fs::FileType) -> u8 {
   if ft.is_file() {
@@ -27,3 +29,56 @@ pub fn get_file_type_id(ft: &std::fs::FileType) -> u8 {
     0
   }
 }
+
+/// The libuv code and description of an OS error, when it has one.
+pub(crate) fn errno_name(e: &io::Error) -> Option<(&'static str, &'static str)> {
+  use io::ErrorKind::*;
+  #[cfg(unix)]
+  match e.raw_os_error() {
+    Some(libc::EPERM) => return Some(("EPERM", "operation not permitted")),
+    Some(libc::ELOOP) => return Some(("ELOOP", "too many symbolic links encountered")),
+    Some(libc::EINVAL) => return Some(("EINVAL", "invalid argument")),
+    Some(libc::EFBIG) => return Some(("EFBIG", "file too large")),
+    Some(libc::ENOSYS) => return Some(("ENOSYS", "function not implemented")),
+    Some(code) if code == libc::ENOTSUP || code == libc::EOPNOTSUPP => {
+      return Some(("ENOTSUP", "operation not supported"))
+    }
+    // A missing extended attribute (ENOATTR on macOS).
+    #[cfg(target_os = "linux")]
+    Some(libc::ENODATA) => return Some(("ENODATA", "no data available")),
+    #[cfg(target_os = "macos")]
+    Some(libc::ENOATTR) => return Some(("ENODATA", "no data available")),
+    _ => {}
+  }
+  Some(match e.kind() {
+    NotFound => ("ENOENT", "no such file or directory"),
+    AlreadyExists => ("EEXIST", "file already exists"),
+    NotADirectory => ("ENOTDIR", "not a directory"),
+    IsADirectory => ("EISDIR", "illegal operation on a directory"),
+    DirectoryNotEmpty => ("ENOTEMPTY", "directory not empty"),
+    PermissionDenied => ("EACCES", "permission denied"),
+    ResourceBusy => ("EBUSY", "resource busy or locked"),
+    CrossesDevices => ("EXDEV", "cross-device link not permitted"),
+    InvalidFilename => ("ENAMETOOLONG", "name too long"),
+    ReadOnlyFilesystem => ("EROFS", "read-only file system"),
+    StorageFull => ("ENOSPC", "no space left on device"),
+    WouldBlock => ("EAGAIN", "resource temporarily unavailable"),
+    Unsupported => ("ENOTSUP", "operation not supported"),
+    _ => return None,
+  })
+}
+
+/// Formats an OS error like libuv does (`ENOENT: no such file or directory, scandir 'path'`),
+/// on every platform.
+pub(crate) fn uv_error(e: &io::Error, syscall: &str, path: &Path) -> napi::Error {
+  match errno_name(e) {
+    Some((code, desc)) => napi::Error::from_reason(format!(
+      "{}: {}, {} '{}'",
+      code,
+      desc,
+      syscall,
+      path.to_string_lossy()
+    )),
+    None => napi::Error::from_reason(format!("{}, {} '{}'", e, syscall, path.to_string_lossy())),
+  }
+}
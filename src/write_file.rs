@@ -1,92 +1,143 @@
+use crate::compression::{codec_for_write, compress, parse_compression, Codec};
+use crate::encoding::{parse_optional, Encoding};
 use napi::bindgen_prelude::*;
 use napi::Task;
 use napi_derive::napi;
-use std::fs::OpenOptions;
+use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::Path;
 
-fn encode_string(s: &str, encoding: Option<&str>) -> Result<Vec<u8>> {
-  match encoding {
-    None | Some("utf8" | "utf-8") => Ok(s.as_bytes().to_vec()),
-    Some("ascii") => Ok(s.bytes().map(|b| b & 0x7f).collect()),
-    Some("latin1" | "binary") => Ok(s.chars().map(|c| c as u8).collect()),
-    Some("base64") => base64_decode(s, false),
-    Some("base64url") => base64_decode(s, true),
-    Some("hex") => hex_decode(s),
-    Some(enc) => Err(Error::from_reason(format!("Unknown encoding: {}", enc))),
+#[napi(object)]
+#[derive(Clone)]
+pub struct WriteFileOptions {
+  pub encoding: Option<String>,
+  pub mode: Option<u32>,
+  pub flag: Option<String>,
+  /// Reject base64/hex strings containing characters outside the alphabet (or an odd number of
+  /// hex digits), and characters the target encoding cannot represent, instead of skipping or
+  /// replacing them like Node. Defaults to false.
+  pub strict: Option<bool>,
+  /// Start a string written as UTF-8 or UTF-16 with a byte order mark. Only written when the
+  /// file is empty, so appending never puts one mid-file. Defaults to false.
+  pub bom: Option<bool>,
+  /// Compress the data: 'gzip', 'zstd', 'brotli', or 'auto' to pick one from the file extension
+  /// (`.gz`, `.zst`, `.br`) and write other files uncompressed.
+  pub compression: Option<String>,
+  /// Compression level: 0-9 for gzip (default 6), 1-22 for zstd (default 3, negative values
+  /// trade ratio for speed), 0-11 for brotli (default 11).
+  pub level: Option<i32>,
+}
+
+/// Data encoded (and the compression settled) before the file is opened, so invalid options
+/// never create or truncate anything.
+pub(crate) struct EncodedWrite {
+  bytes: Vec<u8>,
+  bom: Option<&'static [u8]>,
+  codec: Option<(Codec, i32)>,
+  mode: Option<u32>,
+}
+
+pub(crate) fn default_write_file_options() -> WriteFileOptions {
+  WriteFileOptions {
+    encoding: None,
+    mode: None,
+    flag: None,
+    strict: None,
+    bom: None,
+    compression: None,
+    level: None,
   }
 }
 
-fn base64_decode(s: &str, url_safe: bool) -> Result<Vec<u8>> {
-  let mut buf = Vec::with_capacity(s.len() * 3 / 4);
-  let mut acc: u32 = 0;
-  let mut bits: u32 = 0;
-  for c in s.chars() {
-    let val = if url_safe {
-      match c {
-        'A'..='Z' => c as u32 - 'A' as u32,
-        'a'..='z' => c as u32 - 'a' as u32 + 26,
-        '0'..='9' => c as u32 - '0' as u32 + 52,
-        '-' => 62,
-        '_' => 63,
-        '=' => continue,
-        _ => continue,
-      }
-    } else {
-      match c {
-        'A'..='Z' => c as u32 - 'A' as u32,
-        'a'..='z' => c as u32 - 'a' as u32 + 26,
-        '0'..='9' => c as u32 - '0' as u32 + 52,
-        '+' => 62,
-        '/' => 63,
-        '=' => continue,
-        _ => continue,
-      }
-    };
-    acc = (acc << 6) | val;
-    bits += 6;
-    if bits >= 8 {
-      bits -= 8;
-      buf.push((acc >> bits) as u8);
-      acc &= (1 << bits) - 1;
+pub(crate) fn encode_write(
+  path: &Path,
+  data: &Either<String, Buffer>,
+  opts: &WriteFileOptions,
+) -> Result<EncodedWrite> {
+  let encoding = parse_optional(opts.encoding.as_deref())?.unwrap_or(Encoding::Utf8);
+  let (bytes, bom) = match data {
+    Either::A(s) => {
+      let bom = if opts.bom.unwrap_or(false) {
+        Some(encoding.bom().ok_or_else(|| {
+          Error::from_reason(format!(
+            "ERR_INVALID_ARG_VALUE: The 'bom' option is not supported for the '{}' encoding",
+            opts.encoding.as_deref().unwrap_or("utf8")
+          ))
+        })?)
+      } else {
+        None
+      };
+      (encoding.encode(s, opts.strict.unwrap_or(false))?, bom)
     }
-  }
-  Ok(buf)
+    // Like the encoding, the BOM only applies to string data.
+    Either::B(b) => (b.to_vec(), None),
+  };
+  let codec = codec_for_write(parse_compression(opts.compression.as_deref())?, path)
+    .map(|codec| codec.level(opts.level).map(|level| (codec, level)))
+    .transpose()?;
+  Ok(EncodedWrite {
+    bytes,
+    bom,
+    codec,
+    mode: opts.mode,
+  })
 }
 
-fn hex_decode(s: &str) -> Result<Vec<u8>> {
-  let s = s.trim();
-  if !s.len().is_multiple_of(2) {
-    return Err(Error::from_reason("Invalid hex string".to_string()));
-  }
-  let mut buf = Vec::with_capacity(s.len() / 2);
-  let bytes = s.as_bytes();
-  for i in (0..bytes.len()).step_by(2) {
-    let hi = hex_val(bytes[i])?;
-    let lo = hex_val(bytes[i + 1])?;
-    buf.push((hi << 4) | lo);
+/// Writes encoded data to an already opened file; `path` names it in errors.
+pub(crate) fn write_encoded(file: &mut File, encoded: EncodedWrite, path: &Path) -> Result<()> {
+  let EncodedWrite {
+    bytes,
+    bom,
+    codec,
+    mode,
+  } = encoded;
+  let empty = if bom.is_some() || codec.is_some() {
+    file
+      .metadata()
+      .map_err(|e| Error::from_reason(e.to_string()))?
+      .len()
+      == 0
+  } else {
+    false
+  };
+  let bom = bom.filter(|_| empty).unwrap_or_default();
+  if let Some((codec, level)) = codec {
+    if !empty && !codec.concatenates() {
+      return Err(Error::from_reason(format!(
+        "ERR_INVALID_ARG_VALUE: Cannot append to a {} stream, open '{}'",
+        codec.name(),
+        path.to_string_lossy()
+      )));
+    }
+    let with_bom;
+    let data: &[u8] = if bom.is_empty() {
+      &bytes
+    } else {
+      with_bom = [bom, &bytes].concat();
+      &with_bom
+    };
+    // Appended gzip members / zstd frames read back as one stream.
+    let compressed = compress(codec, data, level).map_err(|e| Error::from_reason(e.to_string()))?;
+    file
+      .write_all(&compressed)
+      .map_err(|e| Error::from_reason(e.to_string()))?;
+  } else {
+    file
+      .write_all(bom)
+      .and_then(|_| file.write_all(&bytes))
+      .map_err(|e| Error::from_reason(e.to_string()))?;
   }
-  Ok(buf)
-}
 
-fn hex_val(b: u8) -> Result<u8> {
-  match b {
-    b'0'..=b'9' => Ok(b - b'0'),
-    b'a'..=b'f' => Ok(b - b'a' + 10),
-    b'A'..=b'F' => Ok(b - b'A' + 10),
-    _ => Err(Error::from_reason(format!(
-      "Invalid hex character: {}",
-      b as char
-    ))),
+  #[cfg(unix)]
+  if let Some(mode) = mode {
+    use std::fs::Permissions;
+    use std::os::unix::fs::PermissionsExt;
+    let _ = file.set_permissions(Permissions::from_mode(mode));
   }
-}
+  #[cfg(not(unix))]
+  let _ = mode;
 
-#[napi(object)]
-#[derive(Clone)]
-pub struct WriteFileOptions {
-  pub encoding: Option<String>,
-  pub mode: Option<u32>,
-  pub flag: Option<String>,
+  Ok(())
 }
 
 fn write_file_impl(
@@ -95,18 +146,10 @@ fn write_file_impl(
   options: Option<WriteFileOptions>,
 ) -> Result<()> {
   let path = Path::new(&path_str);
-  let opts = options.unwrap_or(WriteFileOptions {
-    encoding: None,
-    mode: None,
-    flag: None,
-  });
+  let opts = options.unwrap_or_else(default_write_file_options);
 
   let flag = opts.flag.as_deref().unwrap_or("w");
-  let encoding = opts.encoding.as_deref();
-  let bytes: Vec<u8> = match &data {
-    Either::A(s) => encode_string(s, encoding)?,
-    Either::B(b) => b.to_vec(),
-  };
+  let encoded = encode_write(path, &data, &opts)?;
 
   let mut open_opts = OpenOptions::new();
   match flag {
@@ -143,18 +186,7 @@ fn write_file_impl(
     }
   })?;
 
-  file
-    .write_all(&bytes)
-    .map_err(|e| Error::from_reason(e.to_string()))?;
-
-  #[cfg(unix)]
-  if let Some(mode) = opts.mode {
-    use std::fs;
-    use std::os::unix::fs::PermissionsExt;
-    let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode));
-  }
-
-  Ok(())
+  write_encoded(&mut file, encoded, path)
 }
 
 #[napi(js_name = "writeFileSync")]
@@ -218,15 +250,15 @@ fn append_file_impl(
   data: Either<String, Buffer>,
   options: Option<WriteFileOptions>,
 ) -> Result<()> {
-  let opts = options.unwrap_or(WriteFileOptions {
-    encoding: None,
-    mode: None,
-    flag: None,
-  });
+  let opts = options.unwrap_or_else(default_write_file_options);
   let merged = WriteFileOptions {
     encoding: opts.encoding,
     mode: opts.mode,
     flag: Some(opts.flag.unwrap_or_else(|| "a".to_string())),
+    strict: opts.strict,
+    bom: opts.bom,
+    compression: opts.compression,
+    level: opts.level,
   };
   write_file_impl(path_str, data, Some(merged))
 }
@@ -0,0 +1,495 @@
+use crate::utils::uv_error;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::path::Path;
+
+// Raw extended attribute primitives shared with `cp` (preserve.xattrs).
+// `follow: false` selects the l* variants that operate on a symlink itself.
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod sys {
+  use std::ffi::CString;
+  use std::io;
+  use std::os::unix::ffi::OsStrExt;
+  use std::path::Path;
+
+  fn c_path(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid path"))
+  }
+
+  fn c_name(name: &[u8]) -> io::Result<CString> {
+    CString::new(name)
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid attribute name"))
+  }
+
+  /// Calls `f` first with an empty buffer to learn the size, then with a buffer of that
+  /// size. Retries when the value grew in between (ERANGE).
+  fn read_sized(f: impl Fn(*mut libc::c_void, usize) -> isize) -> io::Result<Vec<u8>> {
+    loop {
+      let size = f(std::ptr::null_mut(), 0);
+      if size < 0 {
+        return Err(io::Error::last_os_error());
+      }
+      let mut buf = vec![0u8; size as usize];
+      let len = f(buf.as_mut_ptr() as *mut libc::c_void, buf.len());
+      if len < 0 {
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() == Some(libc::ERANGE) {
+          continue;
+        }
+        return Err(e);
+      }
+      buf.truncate(len as usize);
+      return Ok(buf);
+    }
+  }
+
+  fn check(ret: libc::c_int) -> io::Result<()> {
+    if ret != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(())
+  }
+
+  pub fn list(path: &Path, follow: bool) -> io::Result<Vec<Vec<u8>>> {
+    let p = c_path(path)?;
+    let names = read_sized(|buf, size| unsafe {
+      let buf = buf as *mut libc::c_char;
+      #[cfg(target_os = "linux")]
+      {
+        if follow {
+          libc::listxattr(p.as_ptr(), buf, size)
+        } else {
+          libc::llistxattr(p.as_ptr(), buf, size)
+        }
+      }
+      #[cfg(target_os = "macos")]
+      {
+        let opts = if follow { 0 } else { libc::XATTR_NOFOLLOW };
+        libc::listxattr(p.as_ptr(), buf, size, opts)
+      }
+    })?;
+    Ok(
+      names
+        .split(|&b| b == 0)
+        .filter(|n| !n.is_empty())
+        .map(|n| n.to_vec())
+        .collect(),
+    )
+  }
+
+  pub fn get(path: &Path, name: &[u8], follow: bool) -> io::Result<Vec<u8>> {
+    let p = c_path(path)?;
+    let n = c_name(name)?;
+    read_sized(|buf, size| unsafe {
+      #[cfg(target_os = "linux")]
+      {
+        if follow {
+          libc::getxattr(p.as_ptr(), n.as_ptr(), buf, size)
+        } else {
+          libc::lgetxattr(p.as_ptr(), n.as_ptr(), buf, size)
+        }
+      }
+      #[cfg(target_os = "macos")]
+      {
+        let opts = if follow { 0 } else { libc::XATTR_NOFOLLOW };
+        libc::getxattr(p.as_ptr(), n.as_ptr(), buf, size, 0, opts)
+      }
+    })
+  }
+
+  pub fn set(
+    path: &Path,
+    name: &[u8],
+    value: &[u8],
+    create: bool,
+    replace: bool,
+    follow: bool,
+  ) -> io::Result<()> {
+    let p = c_path(path)?;
+    let n = c_name(name)?;
+    let mut flags = 0;
+    if create {
+      flags |= libc::XATTR_CREATE;
+    }
+    if replace {
+      flags |= libc::XATTR_REPLACE;
+    }
+    let value_ptr = value.as_ptr() as *const libc::c_void;
+    check(unsafe {
+      #[cfg(target_os = "linux")]
+      {
+        if follow {
+          libc::setxattr(p.as_ptr(), n.as_ptr(), value_ptr, value.len(), flags)
+        } else {
+          libc::lsetxattr(p.as_ptr(), n.as_ptr(), value_ptr, value.len(), flags)
+        }
+      }
+      #[cfg(target_os = "macos")]
+      {
+        let opts = if follow {
+          flags
+        } else {
+          flags | libc::XATTR_NOFOLLOW
+        };
+        libc::setxattr(p.as_ptr(), n.as_ptr(), value_ptr, value.len(), 0, opts)
+      }
+    })
+  }
+
+  pub fn remove(path: &Path, name: &[u8], follow: bool) -> io::Result<()> {
+    let p = c_path(path)?;
+    let n = c_name(name)?;
+    check(unsafe {
+      #[cfg(target_os = "linux")]
+      {
+        if follow {
+          libc::removexattr(p.as_ptr(), n.as_ptr())
+        } else {
+          libc::lremovexattr(p.as_ptr(), n.as_ptr())
+        }
+      }
+      #[cfg(target_os = "macos")]
+      {
+        let opts = if follow { 0 } else { libc::XATTR_NOFOLLOW };
+        libc::removexattr(p.as_ptr(), n.as_ptr(), opts)
+      }
+    })
+  }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod sys {
+  use std::io;
+  use std::path::Path;
+
+  fn unsupported<T>() -> io::Result<T> {
+    Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      "extended attributes are not supported on this platform",
+    ))
+  }
+
+  pub fn list(_path: &Path, _follow: bool) -> io::Result<Vec<Vec<u8>>> {
+    unsupported()
+  }
+
+  pub fn get(_path: &Path, _name: &[u8], _follow: bool) -> io::Result<Vec<u8>> {
+    unsupported()
+  }
+
+  pub fn set(
+    _path: &Path,
+    _name: &[u8],
+    _value: &[u8],
+    _create: bool,
+    _replace: bool,
+    _follow: bool,
+  ) -> io::Result<()> {
+    unsupported()
+  }
+
+  pub fn remove(_path: &Path, _name: &[u8], _follow: bool) -> io::Result<()> {
+    unsupported()
+  }
+}
+
+pub(crate) use sys::{get as get_raw, list as list_raw, remove as remove_raw, set as set_raw};
+
+/// Whether an xattr error means "the filesystem or platform has no xattr support".
+pub(crate) fn is_unsupported(e: &std::io::Error) -> bool {
+  if e.kind() == std::io::ErrorKind::Unsupported {
+    return true;
+  }
+  #[cfg(unix)]
+  {
+    matches!(e.raw_os_error(), Some(code) if code == libc::ENOTSUP || code == libc::EOPNOTSUPP)
+  }
+  #[cfg(not(unix))]
+  {
+    false
+  }
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct SetxattrOptions {
+  /// Fail with `EEXIST` if the attribute already exists.
+  pub create: Option<bool>,
+  /// Fail with `ENODATA` if the attribute does not exist yet.
+  pub replace: Option<bool>,
+}
+
+fn getxattr_impl(path_str: &str, name: &str, follow: bool) -> Result<Vec<u8>> {
+  let syscall = if follow { "getxattr" } else { "lgetxattr" };
+  get_raw(Path::new(path_str), name.as_bytes(), follow)
+    .map_err(|e| uv_error(&e, syscall, Path::new(path_str)))
+}
+
+fn setxattr_impl(
+  path_str: &str,
+  name: &str,
+  value: &[u8],
+  options: Option<SetxattrOptions>,
+  follow: bool,
+) -> Result<()> {
+  let syscall = if follow { "setxattr" } else { "lsetxattr" };
+  let create = options.as_ref().and_then(|o| o.create).unwrap_or(false);
+  let replace = options.as_ref().and_then(|o| o.replace).unwrap_or(false);
+  set_raw(
+    Path::new(path_str),
+    name.as_bytes(),
+    value,
+    create,
+    replace,
+    follow,
+  )
+  .map_err(|e| uv_error(&e, syscall, Path::new(path_str)))
+}
+
+fn listxattr_impl(path_str: &str, follow: bool) -> Result<Vec<String>> {
+  let syscall = if follow { "listxattr" } else { "llistxattr" };
+  let names = list_raw(Path::new(path_str), follow)
+    .map_err(|e| uv_error(&e, syscall, Path::new(path_str)))?;
+  Ok(
+    names
+      .iter()
+      .map(|n| String::from_utf8_lossy(n).to_string())
+      .collect(),
+  )
+}
+
+fn removexattr_impl(path_str: &str, name: &str, follow: bool) -> Result<()> {
+  let syscall = if follow {
+    "removexattr"
+  } else {
+    "lremovexattr"
+  };
+  remove_raw(Path::new(path_str), name.as_bytes(), follow)
+    .map_err(|e| uv_error(&e, syscall, Path::new(path_str)))
+}
+
+fn value_bytes(value: Either<String, Buffer>) -> Vec<u8> {
+  match value {
+    Either::A(s) => s.into_bytes(),
+    Either::B(b) => b.to_vec(),
+  }
+}
+
+#[napi(js_name = "getxattrSync")]
+pub fn getxattr_sync(path: String, name: String) -> Result<Buffer> {
+  getxattr_impl(&path, &name, true).map(Buffer::from)
+}
+
+#[napi(js_name = "lgetxattrSync")]
+pub fn lgetxattr_sync(path: String, name: String) -> Result<Buffer> {
+  getxattr_impl(&path, &name, false).map(Buffer::from)
+}
+
+#[napi(js_name = "setxattrSync")]
+pub fn setxattr_sync(
+  path: String,
+  name: String,
+  value: Either<String, Buffer>,
+  options: Option<SetxattrOptions>,
+) -> Result<()> {
+  setxattr_impl(&path, &name, &value_bytes(value), options, true)
+}
+
+#[napi(js_name = "lsetxattrSync")]
+pub fn lsetxattr_sync(
+  path: String,
+  name: String,
+  value: Either<String, Buffer>,
+  options: Option<SetxattrOptions>,
+) -> Result<()> {
+  setxattr_impl(&path, &name, &value_bytes(value), options, false)
+}
+
+#[napi(js_name = "listxattrSync")]
+pub fn listxattr_sync(path: String) -> Result<Vec<String>> {
+  listxattr_impl(&path, true)
+}
+
+#[napi(js_name = "llistxattrSync")]
+pub fn llistxattr_sync(path: String) -> Result<Vec<String>> {
+  listxattr_impl(&path, false)
+}
+
+#[napi(js_name = "removexattrSync")]
+pub fn removexattr_sync(path: String, name: String) -> Result<()> {
+  removexattr_impl(&path, &name, true)
+}
+
+#[napi(js_name = "lremovexattrSync")]
+pub fn lremovexattr_sync(path: String, name: String) -> Result<()> {
+  removexattr_impl(&path, &name, false)
+}
+
+// ========= async version =========
+
+pub struct GetxattrTask {
+  pub path: String,
+  pub name: String,
+  pub follow: bool,
+}
+
+impl Task for GetxattrTask {
+  type Output = Vec<u8>;
+  type JsValue = Buffer;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    getxattr_impl(&self.path, &self.name, self.follow)
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(Buffer::from(output))
+  }
+}
+
+#[napi(js_name = "getxattr")]
+pub fn getxattr(path: String, name: String) -> AsyncTask<GetxattrTask> {
+  AsyncTask::new(GetxattrTask {
+    path,
+    name,
+    follow: true,
+  })
+}
+
+#[napi(js_name = "lgetxattr")]
+pub fn lgetxattr(path: String, name: String) -> AsyncTask<GetxattrTask> {
+  AsyncTask::new(GetxattrTask {
+    path,
+    name,
+    follow: false,
+  })
+}
+
+pub struct SetxattrTask {
+  pub path: String,
+  pub name: String,
+  pub value: Vec<u8>,
+  pub options: Option<SetxattrOptions>,
+  pub follow: bool,
+}
+
+impl Task for SetxattrTask {
+  type Output = ();
+  type JsValue = ();
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    setxattr_impl(
+      &self.path,
+      &self.name,
+      &self.value,
+      self.options.clone(),
+      self.follow,
+    )
+  }
+
+  fn resolve(&mut self, _env: Env, _output: Self::Output) -> Result<Self::JsValue> {
+    Ok(())
+  }
+}
+
+#[napi(js_name = "setxattr")]
+pub fn setxattr(
+  path: String,
+  name: String,
+  value: Either<String, Buffer>,
+  options: Option<SetxattrOptions>,
+) -> AsyncTask<SetxattrTask> {
+  AsyncTask::new(SetxattrTask {
+    path,
+    name,
+    value: value_bytes(value),
+    options,
+    follow: true,
+  })
+}
+
+#[napi(js_name = "lsetxattr")]
+pub fn lsetxattr(
+  path: String,
+  name: String,
+  value: Either<String, Buffer>,
+  options: Option<SetxattrOptions>,
+) -> AsyncTask<SetxattrTask> {
+  AsyncTask::new(SetxattrTask {
+    path,
+    name,
+    value: value_bytes(value),
+    options,
+    follow: false,
+  })
+}
+
+pub struct ListxattrTask {
+  pub path: String,
+  pub follow: bool,
+}
+
+impl Task for ListxattrTask {
+  type Output = Vec<String>;
+  type JsValue = Vec<String>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    listxattr_impl(&self.path, self.follow)
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "listxattr")]
+pub fn listxattr(path: String) -> AsyncTask<ListxattrTask> {
+  AsyncTask::new(ListxattrTask { path, follow: true })
+}
+
+#[napi(js_name = "llistxattr")]
+pub fn llistxattr(path: String) -> AsyncTask<ListxattrTask> {
+  AsyncTask::new(ListxattrTask {
+    path,
+    follow: false,
+  })
+}
+
+pub struct RemovexattrTask {
+  pub path: String,
+  pub name: String,
+  pub follow: bool,
+}
+
+impl Task for RemovexattrTask {
+  type Output = ();
+  type JsValue = ();
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    removexattr_impl(&self.path, &self.name, self.follow)
+  }
+
+  fn resolve(&mut self, _env: Env, _output: Self::Output) -> Result<Self::JsValue> {
+    Ok(())
+  }
+}
+
+#[napi(js_name = "removexattr")]
+pub fn removexattr(path: String, name: String) -> AsyncTask<RemovexattrTask> {
+  AsyncTask::new(RemovexattrTask {
+    path,
+    name,
+    follow: true,
+  })
+}
+
+#[napi(js_name = "lremovexattr")]
+pub fn lremovexattr(path: String, name: String) -> AsyncTask<RemovexattrTask> {
+  AsyncTask::new(RemovexattrTask {
+    path,
+    name,
+    follow: false,
+  })
+}
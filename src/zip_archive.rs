@@ -0,0 +1,427 @@
+use crate::archive::{entry_path, unsafe_path_error, ArchiveSummary, EntryPath, Extractor};
+use crate::glob::build_overrides;
+use crate::types::ms_to_datetime;
+use crate::utils::uv_error;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use memmap2::Mmap;
+use napi::bindgen_prelude::*;
+use napi::Task;
+use napi_derive::napi;
+use std::fs;
+use std::io::{self, BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// Most `zipRead` allocates before the entry data shows it really is that large.
+const READ_CAPACITY: u64 = 64 * 1024;
+
+/// Longest symlink target accepted from an archive (Linux's PATH_MAX).
+const SYMLINK_MAX: u64 = 4096;
+
+/// An entry of a zip archive, shaped like `Dirent`.
+#[napi]
+pub struct ZipEntry {
+  /// Last path component (without the trailing `/` of directories).
+  #[napi(readonly)]
+  pub name: String,
+  /// Directory of the entry inside the archive (`''` at the top level).
+  #[napi(readonly, js_name = "parentPath")]
+  pub parent_path: String,
+  /// Full name as stored in the archive, to pass to `zipRead`.
+  #[napi(readonly, js_name = "entryName")]
+  pub entry_name: String,
+  /// Uncompressed size in bytes.
+  #[napi(readonly)]
+  pub size: f64,
+  #[napi(readonly, js_name = "compressedSize")]
+  pub compressed_size: f64,
+  #[napi(readonly)]
+  pub crc32: u32,
+  /// Unix mode (file type and permission bits) when the archive recorded one.
+  #[napi(readonly)]
+  pub mode: Option<u32>,
+  #[napi(readonly)]
+  pub encrypted: bool,
+  #[napi(readonly, js_name = "mtimeMs")]
+  pub mtime_ms: f64,
+  // 1: file, 2: dir, 3: symlink
+  file_type: u8,
+}
+
+#[napi]
+impl ZipEntry {
+  #[napi(js_name = "isFile")]
+  pub fn is_file(&self) -> bool {
+    self.file_type == 1
+  }
+
+  #[napi(js_name = "isDirectory")]
+  pub fn is_directory(&self) -> bool {
+    self.file_type == 2
+  }
+
+  #[napi(js_name = "isSymbolicLink")]
+  pub fn is_symbolic_link(&self) -> bool {
+    self.file_type == 3
+  }
+
+  /// Returns mtime as a Date object
+  #[napi(getter)]
+  pub fn mtime(&self) -> DateTime<Local> {
+    ms_to_datetime(self.mtime_ms)
+  }
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct ZipExtractOptions {
+  /// Number of threads decompressing and writing entries. 0 or 1 extracts sequentially.
+  /// Default: 4.
+  pub concurrency: Option<u32>,
+  /// Glob patterns; only matching entries are extracted.
+  pub filter: Option<Vec<String>>,
+}
+
+/// The mapped archive, shared by the per-thread `ZipArchive` clones.
+#[derive(Clone)]
+struct SharedMap(Arc<Mmap>);
+
+impl AsRef<[u8]> for SharedMap {
+  fn as_ref(&self) -> &[u8] {
+    &self.0
+  }
+}
+
+fn io_error(e: io::Error, syscall: &str, path: &str) -> Error {
+  // Corrupt deflate streams and CRC mismatches surface as InvalidData while reading.
+  if e.kind() == io::ErrorKind::InvalidData {
+    return Error::from_reason(format!(
+      "ERR_INVALID_ARCHIVE: invalid zip archive ({}), {} '{}'",
+      e, syscall, path
+    ));
+  }
+  uv_error(&e, syscall, Path::new(path))
+}
+
+fn zip_error(e: ZipError, syscall: &str, path: &str) -> Error {
+  match e {
+    ZipError::Io(e) => io_error(e, syscall, path),
+    ZipError::InvalidArchive(reason) => Error::from_reason(format!(
+      "ERR_INVALID_ARCHIVE: invalid zip archive ({}), {} '{}'",
+      reason, syscall, path
+    )),
+    ZipError::FileNotFound => Error::from_reason(format!(
+      "ENOENT: no such file or directory, {} '{}'",
+      syscall, path
+    )),
+    other => Error::from_reason(format!(
+      "ERR_UNSUPPORTED_ARCHIVE: unsupported zip archive ({}), {} '{}'",
+      other, syscall, path
+    )),
+  }
+}
+
+fn open_archive(path: &str, syscall: &str) -> Result<ZipArchive<BufReader<fs::File>>> {
+  let file = fs::File::open(path).map_err(|e| io_error(e, "open", path))?;
+  if file.metadata().is_ok_and(|m| m.is_dir()) {
+    return Err(io_error(io::ErrorKind::IsADirectory.into(), "read", path));
+  }
+  ZipArchive::new(BufReader::new(file)).map_err(|e| zip_error(e, syscall, path))
+}
+
+/// Maps the archive so every rayon worker can clone a reader without reopening the file
+/// or parsing the central directory again.
+fn map_archive(path: &str, syscall: &str) -> Result<ZipArchive<Cursor<SharedMap>>> {
+  let file = fs::File::open(path).map_err(|e| io_error(e, "open", path))?;
+  let meta = file.metadata().map_err(|e| io_error(e, "fstat", path))?;
+  if meta.is_dir() {
+    return Err(io_error(io::ErrorKind::IsADirectory.into(), "read", path));
+  }
+  if meta.len() == 0 {
+    return Err(zip_error(
+      ZipError::InvalidArchive("empty file".into()),
+      syscall,
+      path,
+    ));
+  }
+  // SAFETY: the mapping is only ever read. Like any mmap reader, truncating the archive while
+  // it is being extracted would fault.
+  let map = unsafe { Mmap::map(&file) }.map_err(|e| io_error(e, "mmap", path))?;
+  ZipArchive::new(Cursor::new(SharedMap(Arc::new(map)))).map_err(|e| zip_error(e, syscall, path))
+}
+
+/// DOS timestamps carry no time zone; like `unzip`, read them as local time.
+fn dos_time(datetime: Option<zip::DateTime>) -> Option<SystemTime> {
+  let datetime = datetime?;
+  let naive = NaiveDate::from_ymd_opt(
+    datetime.year() as i32,
+    datetime.month() as u32,
+    datetime.day() as u32,
+  )?
+  .and_hms_opt(
+    datetime.hour() as u32,
+    datetime.minute() as u32,
+    datetime.second() as u32,
+  )?;
+  let local = Local.from_local_datetime(&naive).earliest()?;
+  let secs = u64::try_from(local.timestamp()).ok()?;
+  Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+fn zip_list_impl(path: String) -> Result<Vec<ZipEntry>> {
+  let mut archive = open_archive(&path, "zipList")?;
+  let mut entries = Vec::with_capacity(archive.len());
+  for i in 0..archive.len() {
+    let file = archive
+      .by_index_raw(i)
+      .map_err(|e| zip_error(e, "zipList", &path))?;
+    let entry_name = file.name().to_string();
+    let trimmed = entry_name.trim_end_matches('/');
+    let (parent_path, name) = match trimmed.rfind('/') {
+      Some(idx) => (&trimmed[..idx], &trimmed[idx + 1..]),
+      None => ("", trimmed),
+    };
+    let file_type = if file.is_dir() {
+      2
+    } else if file.is_symlink() {
+      3
+    } else {
+      1
+    };
+    let mtime_ms = dos_time(file.last_modified())
+      .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+      .map(|d| d.as_millis() as f64)
+      .unwrap_or(0.0);
+    entries.push(ZipEntry {
+      name: name.to_string(),
+      parent_path: parent_path.to_string(),
+      size: file.size() as f64,
+      compressed_size: file.compressed_size() as f64,
+      crc32: file.crc32(),
+      mode: file.unix_mode(),
+      encrypted: file.encrypted(),
+      mtime_ms,
+      file_type,
+      entry_name,
+    });
+  }
+  Ok(entries)
+}
+
+fn zip_read_impl(path: String, entry_name: String) -> Result<Buffer> {
+  let mut archive = open_archive(&path, "zipRead")?;
+  let mut file = archive.by_name(&entry_name).map_err(|e| match e {
+    ZipError::FileNotFound => Error::from_reason(format!(
+      "ENOENT: no such file or directory, zipRead '{}' -> '{}'",
+      path, entry_name
+    )),
+    other => zip_error(other, "zipRead", &path),
+  })?;
+  if file.is_dir() {
+    return Err(Error::from_reason(format!(
+      "EISDIR: illegal operation on a directory, zipRead '{}' -> '{}'",
+      path, entry_name
+    )));
+  }
+  // The header size is only a claim: grow the buffer from a bounded start instead of
+  // allocating it up front, and never read past it.
+  let size = file.size();
+  let mut data = Vec::with_capacity(size.min(READ_CAPACITY) as usize);
+  (&mut file)
+    .take(size)
+    .read_to_end(&mut data)
+    .map_err(|e| io_error(e, "zipRead", &path))?;
+  // The CRC is checked once the entry has been read to the end.
+  let mut rest = [0u8; 1];
+  let extra = file
+    .read(&mut rest)
+    .map_err(|e| io_error(e, "zipRead", &path))?;
+  if extra > 0 || (data.len() as u64) < size {
+    return Err(io_error(
+      io::Error::new(
+        io::ErrorKind::InvalidData,
+        "entry size does not match its header",
+      ),
+      "zipRead",
+      &path,
+    ));
+  }
+  Ok(data.into())
+}
+
+fn zip_extract_impl(
+  path: String,
+  dest: String,
+  options: Option<ZipExtractOptions>,
+) -> Result<ArchiveSummary> {
+  let opts = options.unwrap_or(ZipExtractOptions {
+    concurrency: None,
+    filter: None,
+  });
+  let concurrency = opts.concurrency.unwrap_or(4) as usize;
+  let filter = match opts.filter.as_deref() {
+    Some(patterns) if !patterns.is_empty() => Some(build_overrides(&dest, patterns, &[])?),
+    _ => None,
+  };
+
+  let mut archive = map_archive(&path, "zipExtract")?;
+  let mut extractor = Extractor::new(&dest, concurrency, "zipExtract", &path)?;
+
+  // Directories and symlinks are cheap and handled in order; file contents are inflated
+  // in parallel afterwards.
+  let mut files = Vec::new();
+  for i in 0..archive.len() {
+    let mut file = archive
+      .by_index(i)
+      .map_err(|e| zip_error(e, "zipExtract", &path))?;
+    // Some Windows tools store `\` separators.
+    let raw = PathBuf::from(file.name().replace('\\', "/"));
+    let rel = match entry_path(&raw, 0) {
+      EntryPath::Safe(rel) => rel,
+      EntryPath::Empty => continue,
+      EntryPath::Escapes => return Err(unsafe_path_error(file.name(), &dest, "zipExtract", &path)),
+    };
+    let is_dir = file.is_dir();
+    if let Some(filter) = &filter {
+      if !filter.matched(&rel, is_dir).is_whitelist() {
+        continue;
+      }
+    }
+    let mode = file.unix_mode().map(|mode| mode & 0o7777);
+    let mtime = dos_time(file.last_modified());
+
+    if is_dir {
+      extractor.dir(rel, mode, mtime)?;
+    } else if file.is_symlink() {
+      let mut target = String::new();
+      (&mut file)
+        .take(SYMLINK_MAX + 1)
+        .read_to_string(&mut target)
+        .map_err(|e| io_error(e, "zipExtract", &path))?;
+      if target.len() as u64 > SYMLINK_MAX {
+        return Err(Error::from_reason(format!(
+          "ENAMETOOLONG: name too long, zipExtract '{}' -> '{}'",
+          path,
+          file.name()
+        )));
+      }
+      extractor.symlink(rel, PathBuf::from(target))?;
+    } else {
+      files.push((rel, i, mode, mtime));
+    }
+  }
+
+  extractor.files_with(
+    files,
+    || archive.clone(),
+    |archive, &index, out| {
+      let mut entry = archive
+        .by_index(index)
+        .map_err(|e| zip_error(e, "zipExtract", &path))?;
+      io::copy(&mut entry, out).map_err(|e| io_error(e, "zipExtract", &path))
+    },
+  )?;
+
+  extractor.finish()
+}
+
+#[napi(js_name = "zipListSync")]
+pub fn zip_list_sync(path: String) -> Result<Vec<ZipEntry>> {
+  zip_list_impl(path)
+}
+
+#[napi(js_name = "zipReadSync")]
+pub fn zip_read_sync(path: String, entry_name: String) -> Result<Buffer> {
+  zip_read_impl(path, entry_name)
+}
+
+#[napi(js_name = "zipExtractSync")]
+pub fn zip_extract_sync(
+  path: String,
+  dest: String,
+  options: Option<ZipExtractOptions>,
+) -> Result<ArchiveSummary> {
+  zip_extract_impl(path, dest, options)
+}
+
+// ========= async version =========
+
+pub struct ZipListTask {
+  pub path: String,
+}
+
+impl Task for ZipListTask {
+  type Output = Vec<ZipEntry>;
+  type JsValue = Vec<ZipEntry>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    zip_list_impl(self.path.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "zipList")]
+pub fn zip_list(path: String) -> AsyncTask<ZipListTask> {
+  AsyncTask::new(ZipListTask { path })
+}
+
+pub struct ZipReadTask {
+  pub path: String,
+  pub entry_name: String,
+}
+
+impl Task for ZipReadTask {
+  type Output = Buffer;
+  type JsValue = Buffer;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    zip_read_impl(self.path.clone(), self.entry_name.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "zipRead")]
+pub fn zip_read(path: String, entry_name: String) -> AsyncTask<ZipReadTask> {
+  AsyncTask::new(ZipReadTask { path, entry_name })
+}
+
+pub struct ZipExtractTask {
+  pub path: String,
+  pub dest: String,
+  pub options: Option<ZipExtractOptions>,
+}
+
+impl Task for ZipExtractTask {
+  type Output = ArchiveSummary;
+  type JsValue = ArchiveSummary;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    zip_extract_impl(self.path.clone(), self.dest.clone(), self.options.clone())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(js_name = "zipExtract")]
+pub fn zip_extract(
+  path: String,
+  dest: String,
+  options: Option<ZipExtractOptions>,
+) -> AsyncTask<ZipExtractTask> {
+  AsyncTask::new(ZipExtractTask {
+    path,
+    dest,
+    options,
+  })
+}